@@ -0,0 +1,59 @@
+-- Sample data used by the test suite when `TEST_DB_SOURCE` is `sqlite`.
+-- The tables mirror the sample datasets used while developing Basable.
+
+CREATE TABLE vgchartz (
+    id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    console TEXT,
+    genre TEXT,
+    publisher TEXT,
+    developer TEXT,
+    critic_score REAL,
+    total_sales REAL,
+    release_date TEXT
+);
+
+INSERT INTO vgchartz (title, console, genre, publisher, developer, critic_score, total_sales, release_date) VALUES
+    ('Grand Theft Auto V', 'PS3', 'Action', 'Rockstar Games', 'Rockstar North', 9.4, 20.32, '2013-09-17'),
+    ('Red Dead Redemption', 'PS3', 'Action-Adventure', 'Rockstar Games', 'Rockstar San Diego', 9.5, 6.67, '2010-05-18'),
+    ('Call of Duty: Black Ops', 'X360', 'Shooter', 'Activision', 'Treyarch', 8.8, 14.74, '2010-11-09'),
+    ('Halo: Reach', 'X360', 'Shooter', 'Microsoft Game Studios', 'Bungie', 9.4, 9.86, '2010-09-14'),
+    ('Fallout: New Vegas', 'PS3', 'Role-Playing', 'Bethesda Softworks', 'Obsidian Entertainment', 8.6, 4.97, '2010-10-19'),
+    ('FIFA 11', 'PS3', 'Sports', 'EA Sports', 'EA Canada', 9.0, 6.12, '2010-09-28'),
+    ('Gran Turismo 5', 'PS3', 'Racing', 'Sony Computer Entertainment', 'Polyphony Digital', 8.6, 7.97, '2010-11-24'),
+    ('Minecraft', 'PC', 'Misc', 'Mojang', 'Mojang AB', NULL, NULL, '2011-11-18'),
+    ('Grand Theft Auto IV', 'X360', 'Action', 'Rockstar Games', 'Rockstar North', 9.9, 11.02, '2008-04-29'),
+    ('The Elder Scrolls V: Skyrim', 'X360', 'Role-Playing', 'Bethesda Softworks', 'Bethesda Game Studios', 9.3, 8.84, '2011-11-11');
+
+CREATE TABLE patients (
+    Id TEXT PRIMARY KEY,
+    BIRTHDATE TEXT,
+    FIRST TEXT,
+    LAST TEXT,
+    GENDER TEXT,
+    CITY TEXT,
+    STATE TEXT
+);
+
+INSERT INTO patients (Id, BIRTHDATE, FIRST, LAST, GENDER, CITY, STATE) VALUES
+    ('p-1', '1989-05-25', 'Jacinto', 'Kris', 'M', 'Springfield', 'Massachusetts'),
+    ('p-2', '1975-03-12', 'Alva', 'Krajcik', 'F', 'Boston', 'Massachusetts'),
+    ('p-3', '2001-11-30', 'Jimmie', 'Harris', 'M', 'Worcester', 'Massachusetts'),
+    ('p-4', '1962-07-04', 'Gregorio', 'Auer', 'M', 'Lowell', 'Massachusetts');
+
+CREATE TABLE encounters (
+    Id TEXT PRIMARY KEY,
+    START TEXT,
+    STOP TEXT,
+    PATIENT TEXT REFERENCES patients (Id),
+    ENCOUNTERCLASS TEXT,
+    DESCRIPTION TEXT,
+    TOTAL_CLAIM_COST REAL
+);
+
+INSERT INTO encounters (Id, START, STOP, PATIENT, ENCOUNTERCLASS, DESCRIPTION, TOTAL_CLAIM_COST) VALUES
+    ('e-1', '2019-02-17 05:07:38', '2019-02-17 05:22:38', 'p-1', 'wellness', 'Well child visit', 129.16),
+    ('e-2', '2019-08-02 12:41:09', '2019-08-02 13:41:09', 'p-1', 'ambulatory', 'Encounter for symptom', 87.00),
+    ('e-3', '2020-01-11 09:00:00', '2020-01-11 09:30:00', 'p-2', 'outpatient', 'Follow-up encounter', 142.58),
+    ('e-4', '2020-03-23 16:20:00', '2020-03-23 17:00:00', 'p-3', 'emergency', 'Emergency room admission', 1254.03),
+    ('e-5', '2021-06-05 10:15:00', '2021-06-05 10:45:00', 'p-1', 'wellness', 'General examination', 129.16);
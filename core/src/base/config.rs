@@ -1,19 +1,82 @@
-use serde::Deserialize;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 
+use super::{local::local_db_path, user::Role, AppError};
+
+/// Directory of file data sources when `BASABLE_DATA_DIR` is not set.
+pub(crate) const DEFAULT_DATA_DIR: &str = "data";
+
+/// Directory that file data sources, such as SQLite databases and CSV files, must be in. It is
+/// `BASABLE_DATA_DIR` if set, and [`DEFAULT_DATA_DIR`] otherwise.
+pub(crate) fn data_dir() -> PathBuf {
+    PathBuf::from(env::var("BASABLE_DATA_DIR").unwrap_or(DEFAULT_DATA_DIR.to_string()))
+}
+
+/// Resolves `path` of a file data source. Clients choose the path, so it must be in
+/// [`data_dir`] once links are followed, and it can't be the local store or one of its
+/// journal files.
+pub(crate) fn resolve_data_path(path: &Path) -> Result<PathBuf, AppError> {
+    let not_found = || {
+        AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find the given path in the data directory.",
+        )
+    };
+
+    let dir = data_dir().canonicalize().map_err(|_| not_found())?;
+    let path = path.canonicalize().map_err(|_| not_found())?;
+    if !path.starts_with(&dir) {
+        return Err(not_found());
+    }
+
+    if let Ok(store) = Path::new(&local_db_path()).canonicalize() {
+        let store = store.to_string_lossy().to_string();
+        let is_store = ["", "-wal", "-shm", "-journal"]
+            .iter()
+            .any(|suffix| path == Path::new(&format!("{store}{suffix}")));
+
+        if is_store {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                "The local store can't be opened as a data source.",
+            ));
+        }
+    }
+
+    Ok(path)
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub(crate) enum Database {
     Mysql,
     Postgres,
-    Oracle
+    Oracle,
+    Sqlite,
 }
 
 impl Database {
-    fn to_str(self) -> &'static str {
+    fn to_str(&self) -> &'static str {
         match self {
             Database::Mysql => "mysql",
             Database::Postgres => "postgres",
             Database::Oracle => "oracle",
+            Database::Sqlite => "sqlite",
+        }
+    }
+
+    /// Port used by the database server if none is configured.
+    fn default_port(&self) -> u16 {
+        match self {
+            Database::Mysql => 3306,
+            Database::Postgres => 5432,
+            Database::Oracle => 1521,
+            Database::Sqlite => 0,
         }
     }
 }
@@ -24,17 +87,30 @@ impl From<&str> for Database {
             "postgres" => Self::Postgres,
             "oracle" => Self::Oracle,
             "mysql" => Self::Mysql,
+            "sqlite" => Self::Sqlite,
             &_ => Self::Mysql,
         }
     }
 }
 
+/// Format of a file-based data source.
 #[derive(Deserialize, Clone, Debug)]
-pub(crate) enum Cloud { Firebase }
+pub(crate) enum FileType {
+    Csv,
+}
+
+impl From<&str> for FileType {
+    fn from(value: &str) -> Self {
+        match value {
+            "csv" => Self::Csv,
+            &_ => Self::Csv,
+        }
+    }
+}
 
 #[derive(Deserialize, Clone, Debug)]
 pub(crate) enum SourceType {
-    Database(Database), Cloud, File
+    Database(Database), Cloud, File(FileType)
 }
 
 impl SourceType {
@@ -42,22 +118,29 @@ impl SourceType {
         match src_type {
             "database" => Self::Database(src_val.into()),
             "cloud" => Self::Cloud,
-            "file" => Self::File,
-            &_ => Self::File
+            "file" => Self::File(src_val.into()),
+            &_ => Self::File(src_val.into())
         }
     }
 }
 
 /// Configuration options for a new `BasableConnection`.
-#[derive(Deserialize, Clone, Debug)]
+///
+/// `password` is never serialized, so that it is not sent back to clients or stored in
+/// plain text.
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub(crate) struct ConnectionConfig {
     pub source_type: String,
     pub source: String,
     pub username: Option<String>,
+    #[serde(skip_serializing)]
     pub password: Option<String>,
     pub host: Option<String>,
     pub port: Option<u16>,
     pub db_name: Option<String>,
+
+    /// Path to the data source on local disk, for file-based sources such as SQLite and CSV.
+    pub path: Option<String>,
 }
 
 impl Default for ConnectionConfig {
@@ -68,6 +151,7 @@ impl Default for ConnectionConfig {
             host: None,
             port: None,
             db_name: None,
+            path: None,
             source_type: String::from("database"),
             source: String::from("mysql")
         }
@@ -80,12 +164,12 @@ impl ConnectionConfig {
 
         match src_type {
             SourceType::Database(src) => {
+                let port = self.port.unwrap_or(src.default_port());
                 let src = src.to_str();
 
                 let username = self.username.clone().unwrap_or("root".to_string());
                 let password = self.password.clone().unwrap_or_default();
                 let host = self.host.clone().unwrap_or("localhost".to_string());
-                let port = self.port.unwrap_or(3306);
                 let db = self.db_name.clone().unwrap_or_default();
 
                 format!(
@@ -107,4 +191,41 @@ impl ConnectionConfig {
     pub fn source_type(&self) -> SourceType {
         SourceType::from_str(&self.source_type, &self.source)
     }
+
+    /// Whether the data source is a file on the server, such as a SQLite database or CSV
+    /// files. Only registered users can open them.
+    pub fn is_local_file(&self) -> bool {
+        matches!(
+            self.source_type(),
+            SourceType::Database(Database::Sqlite) | SourceType::File(_)
+        )
+    }
+
+    /// Name given to a saved connection until its user renames it.
+    pub fn default_name(&self) -> String {
+        let target = self
+            .db_name
+            .as_deref()
+            .filter(|n| !n.is_empty())
+            .or(self.path.as_deref());
+
+        match target {
+            Some(target) => format!("{} ({})", target, self.source),
+            None => self.source.clone(),
+        }
+    }
+}
+
+/// A [`ConnectionConfig`] saved for a registered user. `id` is the id of the connection
+/// opened from it, which the user passes as `Connection-Id`.
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SavedConnection {
+    pub id: String,
+    pub name: String,
+    pub config: ConnectionConfig,
+    pub created_at: String,
+    pub updated_at: String,
+
+    /// Role of the user the connection was read for.
+    pub role: Role,
 }
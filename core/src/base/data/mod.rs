@@ -1 +1,3 @@
-pub(crate) mod table;
\ No newline at end of file
+pub(crate) mod row;
+pub(crate) mod table;
+pub(crate) mod value;
@@ -0,0 +1,44 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::value::{BasableValue, FromBasableValue};
+
+/// A row returned by a [`Connector`](`crate::base::imp::connector::Connector`) query.
+///
+/// Every data source converts its native rows into [`BasableRow`] so that rows from
+/// different backends can be handled the same way across the app.
+#[derive(Clone, Debug)]
+pub(crate) struct BasableRow {
+    columns: Arc<[String]>,
+    values: Vec<BasableValue>,
+}
+
+impl BasableRow {
+    pub fn new(columns: Arc<[String]>, values: Vec<BasableValue>) -> Self {
+        BasableRow { columns, values }
+    }
+
+    /// Get the raw value of column `name`. Returns `None` if the column does not exist.
+    ///
+    /// Some databases change the case of unquoted column names (Postgres folds them to lower
+    /// case), so a case-insensitive match is used when there's no exact match.
+    pub fn value(&self, name: &str) -> Option<&BasableValue> {
+        let index = self
+            .columns
+            .iter()
+            .position(|c| c == name)
+            .or_else(|| self.columns.iter().position(|c| c.eq_ignore_ascii_case(name)))?;
+
+        self.values.get(index)
+    }
+
+    /// Get the value of column `name` converted to `T`. Returns `None` if the column does
+    /// not exist or if the value can't be converted to `T`.
+    pub fn get<T: FromBasableValue>(&self, name: &str) -> Option<T> {
+        T::from_value(self.value(name)?)
+    }
+
+    /// Values of the row, by column name.
+    pub fn into_map(self) -> HashMap<String, BasableValue> {
+        self.columns.iter().cloned().zip(self.values).collect()
+    }
+}
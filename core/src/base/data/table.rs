@@ -1,6 +1,19 @@
 use std::collections::HashMap;
 
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::base::{
+    query::{
+        filter::{Filter, FilterChain},
+        QueryOrder,
+    },
+    AppError,
+};
+
+use super::value::BasableValue;
 
 pub(crate) type TableSummaries = Vec<TableSummary>;
 
@@ -15,6 +28,7 @@ pub(crate) struct HistoryColumn {
 
 /// The type of `SpecialColumn`
 #[derive(Deserialize, Serialize, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) enum SpecialValueType {
     Image,
     Audio,
@@ -74,7 +88,7 @@ pub(crate) struct NotifyEvent {
     on_error: OnNotifyError,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub(crate) struct TableConfig {
     pub table_id: String,
 
@@ -100,36 +114,179 @@ impl PartialEq for TableConfig {
     }
 }
 
-impl Default for TableConfig {
-    fn default() -> Self {
-        TableConfig {
-            pk: None,
-            table_id: String::new(),
-            created_column: None,
-            updated_column: None,
-            special_columns: None,
-            events: None,
-        }
-    }
-}
-
+/// Options of [`Table::query_data`](crate::base::imp::table::Table::query_data). Column
+/// names are given by users, and are resolved by the table.
 pub struct DataQueryFilter {
     /// Query pagination
     pub limit: usize,
 
+    /// Number of rows to skip.
+    pub offset: usize,
+
+    /// Columns to query. All columns are queried by default.
+    pub columns: Option<Vec<String>>,
+
     /// Columns to exclude from query
     pub exclude: Option<Vec<String>>,
+
+    pub filters: FilterChain,
+
+    pub order_by: Option<QueryOrder>,
+
+    /// Column that orders rows for cursor pagination, such as the primary key. Pages have no
+    /// cursors without it, or when rows are sorted by another column.
+    pub cursor_key: Option<String>,
+
+    /// Cursor of the page to query, from a previous [`DataPage`].
+    pub cursor: Option<DataCursor>,
 }
 
 impl Default for DataQueryFilter {
     fn default() -> Self {
         DataQueryFilter {
-            limit: 100,
+            limit: DEFAULT_DATA_LIMIT,
+            offset: 0,
+            columns: None,
             exclude: None,
+            filters: FilterChain::new(),
+            order_by: None,
+            cursor_key: None,
+            cursor: None,
+        }
+    }
+}
+
+/// Number of rows queried when no limit is given.
+const DEFAULT_DATA_LIMIT: usize = 100;
+
+const MAX_DATA_LIMIT: usize = 1000;
+
+impl DataQueryFilter {
+    /// Builds the filter from the query params of a request. `filter` params are encoded
+    /// [`Filter`]s, and can be repeated; rows must match all of them. `columns` and `exclude` are comma separated lists of
+    /// columns. Rows are sorted by the `sort` column, in `order` (`asc` or `desc`). A page is
+    /// selected with `limit`, and either `offset` or `page` (starting from 1), or `cursor`.
+    /// The order of a cursor's pages is kept in the cursor, so `cursor` can't be used with
+    /// `sort`, `order`, `offset` or `page`.
+    pub fn from_query_params(params: &[(String, String)]) -> Result<Self, AppError> {
+        let err = |msg: String| AppError::new(StatusCode::BAD_REQUEST, &msg);
+        let number = |key: &str, value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| err(format!("'{key}' must be a positive integer.")))
+        };
+        let list = |value: &str| value.split(',').map(|c| c.trim().to_string()).collect();
+
+        let mut filter = DataQueryFilter::default();
+        let mut page = None;
+        let mut offset = None;
+        let mut sort = None;
+        let mut order = None;
+
+        for (key, value) in params {
+            match key.as_str() {
+                "filter" => filter
+                    .filters
+                    .add_one(Filter::try_from(value.clone()).map_err(err)?),
+                "columns" => filter.columns = Some(list(value)),
+                "exclude" => filter.exclude = Some(list(value)),
+                "sort" => sort = Some(value.clone()),
+                "order" => order = Some(value.to_lowercase()),
+                "limit" => filter.limit = number(key, value)?.clamp(1, MAX_DATA_LIMIT),
+                "offset" => offset = Some(number(key, value)?),
+                "page" => page = Some(number(key, value)?),
+                "cursor" => {
+                    filter.cursor = Some(DataCursor::try_from(value.as_str()).map_err(err)?)
+                }
+                _ => {}
+            }
         }
+
+        if filter.cursor.is_some()
+            && (page.is_some() || offset.is_some() || sort.is_some() || order.is_some())
+        {
+            return Err(err(
+                "'cursor' can't be used with 'sort', 'order', 'offset' or 'page'.".to_string(),
+            ));
+        }
+
+        filter.offset = match (page, offset) {
+            (Some(_), Some(_)) => {
+                return Err(err("Use either 'page' or 'offset'.".to_string()));
+            }
+            (Some(0), None) => return Err(err("Pages start from 1.".to_string())),
+            (Some(page), None) => (page - 1) * filter.limit,
+            (None, offset) => offset.unwrap_or(0),
+        };
+
+        filter.order_by = match (sort, order.as_deref()) {
+            (Some(col), None | Some("asc")) => Some(QueryOrder::ASC(col)),
+            (Some(col), Some("desc")) => Some(QueryOrder::DESC(col)),
+            (Some(_), Some(_)) => {
+                return Err(err("'order' must be either 'asc' or 'desc'.".to_string()));
+            }
+            (None, Some(_)) => return Err(err("Please provide 'sort' query param.".to_string())),
+            (None, None) => None,
+        };
+
+        Ok(filter)
+    }
+}
+
+/// Position of a page of rows for keyset pagination: the page after (or before, if `prev`)
+/// the row whose `key` column has `value`. Rows are ordered by `key`, in descending order
+/// if `desc`. `filters` is the hash of the filters of the pages, see
+/// [`DataCursor::hash_filters`], so that a cursor isn't used with other filters.
+///
+/// Pages found with a cursor are as fast to query at any depth, unlike pages found with an
+/// offset. Cursors are opaque to clients, see [`DataCursor::encode`].
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub(crate) struct DataCursor {
+    pub key: String,
+    pub value: String,
+    pub desc: bool,
+    pub prev: bool,
+    pub filters: String,
+}
+
+impl DataCursor {
+    /// SHA-256 hash of `filters`, URL-safe base64 encoded.
+    pub fn hash_filters(filters: &FilterChain) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(filters.to_string()))
+    }
+
+    /// The cursor as URL-safe base64 encoded JSON.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap())
     }
 }
 
+impl TryFrom<&str> for DataCursor {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| "Invalid cursor.".to_string())
+    }
+}
+
+/// A page of rows of a table. `total` is the number of rows that match the filters.
+///
+/// `next_cursor` and `prev_cursor` are cursors of the next and previous pages, when there are
+/// such pages and the rows are ordered by [`DataQueryFilter::cursor_key`].
+#[derive(Serialize)]
+pub(crate) struct DataPage {
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    pub rows: Vec<HashMap<String, BasableValue>>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
 #[derive(Serialize)]
 pub(crate) struct TableSummary {
     pub name: String,
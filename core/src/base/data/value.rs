@@ -0,0 +1,194 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+/// Client side representation of a column value, independent of the data source it was read from.
+///
+/// Every data source converts the values it reads into [`BasableValue`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) enum BasableValue {
+    #[serde(rename = "NULL")]
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    /// Exact numeric value, such as `DECIMAL`. It's kept as text so that no precision is lost.
+    Decimal(String),
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    /// year, month, day, hour, minutes, seconds, micro seconds
+    Date(i32, u8, u8, u8, u8, u8, u32),
+    /// is negative, days, hours, minutes, seconds, micro seconds
+    Time(bool, u32, u8, u8, u8, u32),
+}
+
+impl Display for BasableValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BasableValue::Null => write!(f, "NULL"),
+            BasableValue::Bool(v) => write!(f, "{v}"),
+            BasableValue::Int(v) => write!(f, "{v}"),
+            BasableValue::UInt(v) => write!(f, "{v}"),
+            BasableValue::Float(v) => write!(f, "{v}"),
+            BasableValue::Double(v) => write!(f, "{v}"),
+            BasableValue::Decimal(v) | BasableValue::Text(v) => write!(f, "{v}"),
+            BasableValue::Bytes(v) => write!(f, "{}", String::from_utf8_lossy(v)),
+            BasableValue::Json(v) => write!(f, "{v}"),
+            BasableValue::Date(y, m, d, 0, 0, 0, 0) => write!(f, "{y:04}-{m:02}-{d:02}"),
+            BasableValue::Date(y, m, d, h, min, s, 0) => {
+                write!(f, "{y:04}-{m:02}-{d:02} {h:02}:{min:02}:{s:02}")
+            }
+            BasableValue::Date(y, m, d, h, min, s, us) => {
+                write!(f, "{y:04}-{m:02}-{d:02} {h:02}:{min:02}:{s:02}.{us:06}")
+            }
+            BasableValue::Time(neg, days, h, min, s, _) => {
+                let sign = if *neg { "-" } else { "" };
+                let h = *days * 24 + u32::from(*h);
+                write!(f, "{sign}{h:02}:{min:02}:{s:02}")
+            }
+        }
+    }
+}
+
+/// Conversion of a [`BasableValue`] into a Rust type.
+pub(crate) trait FromBasableValue: Sized {
+    /// Returns `None` if `value` can't be represented as `Self`.
+    fn from_value(value: &BasableValue) -> Option<Self>;
+}
+
+impl FromBasableValue for BasableValue {
+    fn from_value(value: &BasableValue) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+/// `NULL` converts to `Some(None)`, every other value is converted to `T`.
+impl<T: FromBasableValue> FromBasableValue for Option<T> {
+    fn from_value(value: &BasableValue) -> Option<Self> {
+        match value {
+            BasableValue::Null => Some(None),
+            v => T::from_value(v).map(Some),
+        }
+    }
+}
+
+impl FromBasableValue for String {
+    fn from_value(value: &BasableValue) -> Option<Self> {
+        match value {
+            BasableValue::Null => None,
+            BasableValue::Bytes(v) => String::from_utf8(v.clone()).ok(),
+            v => Some(v.to_string()),
+        }
+    }
+}
+
+macro_rules! impl_from_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromBasableValue for $t {
+                fn from_value(value: &BasableValue) -> Option<Self> {
+                    match value {
+                        BasableValue::Bool(v) => Some(<$t>::from(*v)),
+                        BasableValue::Int(v) => <$t>::try_from(*v).ok(),
+                        BasableValue::UInt(v) => <$t>::try_from(*v).ok(),
+                        BasableValue::Decimal(v) | BasableValue::Text(v) => v.trim().parse().ok(),
+                        BasableValue::Bytes(v) => std::str::from_utf8(v).ok()?.trim().parse().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_from_value_float {
+    ($($t:ty),*) => {
+        $(
+            impl FromBasableValue for $t {
+                fn from_value(value: &BasableValue) -> Option<Self> {
+                    match value {
+                        BasableValue::Int(v) => Some(*v as $t),
+                        BasableValue::UInt(v) => Some(*v as $t),
+                        BasableValue::Float(v) => Some(*v as $t),
+                        BasableValue::Double(v) => Some(*v as $t),
+                        BasableValue::Decimal(v) | BasableValue::Text(v) => v.trim().parse().ok(),
+                        BasableValue::Bytes(v) => std::str::from_utf8(v).ok()?.trim().parse().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_float!(f32, f64);
+
+impl FromBasableValue for bool {
+    fn from_value(value: &BasableValue) -> Option<Self> {
+        match value {
+            BasableValue::Bool(v) => Some(*v),
+            BasableValue::Int(v) => Some(*v != 0),
+            BasableValue::UInt(v) => Some(*v != 0),
+            BasableValue::Text(v) => match v.to_lowercase().as_str() {
+                "1" | "true" => Some(true),
+                "0" | "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Only the date part of a date time value is kept. Text is read as `YYYY-MM-DD`, followed by
+/// anything.
+impl FromBasableValue for Date {
+    fn from_value(value: &BasableValue) -> Option<Self> {
+        match value {
+            BasableValue::Date(y, m, d, ..) => {
+                Date::from_calendar_date(*y, Month::try_from(*m).ok()?, *d).ok()
+            }
+            BasableValue::Text(v) => {
+                let mut parts = v.get(..10)?.splitn(3, '-');
+
+                let y = parts.next()?.parse().ok()?;
+                let m: u8 = parts.next()?.parse().ok()?;
+                let d = parts.next()?.parse().ok()?;
+
+                Date::from_calendar_date(y, Month::try_from(m).ok()?, d).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month};
+
+    use super::{BasableValue, FromBasableValue};
+
+    #[test]
+    fn test_value_conversion() {
+        assert_eq!(u32::from_value(&BasableValue::Int(7)), Some(7));
+        assert_eq!(u32::from_value(&BasableValue::Int(-7)), None);
+        assert_eq!(f64::from_value(&BasableValue::Decimal("2.50".into())), Some(2.5));
+        assert_eq!(
+            Option::<String>::from_value(&BasableValue::Null),
+            Some(None)
+        );
+        assert_eq!(
+            Date::from_value(&BasableValue::Text("2013-09-17 10:00:00".into())),
+            Date::from_calendar_date(2013, Month::September, 17).ok()
+        );
+        assert_eq!(
+            BasableValue::Date(2013, 9, 17, 8, 5, 0, 0).to_string(),
+            "2013-09-17 08:05:00"
+        );
+    }
+}
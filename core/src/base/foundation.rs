@@ -5,15 +5,21 @@ use uuid::Uuid;
 
 use crate::imp::database::mysql::connector::MysqlConnector;
 use crate::imp::database::mysql::db::MySqlDB;
+use crate::imp::database::postgres::connector::PostgresConnector;
+use crate::imp::database::postgres::db::PostgresDB;
+use crate::imp::database::sqlite::connector::SqliteConnector;
+use crate::imp::database::sqlite::db::SqliteDB;
+use crate::imp::file::csv::connector::CsvConnector;
+use crate::imp::file::csv::db::CsvDB;
 use crate::User;
 
 use super::imp::connector::Connector;
-use super::imp::db::DB;
-use super::imp::SharedDB;
+use super::imp::{DbType, SharedDB};
+use super::local::LocalDB;
 use super::{
-    config::{ConnectionConfig, Database, SourceType},
+    config::{ConnectionConfig, Database, FileType, SourceType},
     user::{create_jwt, JwtSession},
-    AppError,
+    AppError, BasableError,
 };
 
 #[derive(Default)]
@@ -29,44 +35,89 @@ impl Basable {
         config: &ConnectionConfig,
         user_id: String,
     ) -> Result<SharedDB, AppError> {
-        let mut db = match config.source_type() {
+        Self::open_connection(config, user_id, Uuid::new_v4())
+    }
+
+    /// Creates a [`SharedDB`] like [`Basable::create_connection`], with a known `id`. It is
+    /// used to open a saved connection again under the id its user already knows.
+    pub(crate) fn open_connection(
+        config: &ConnectionConfig,
+        user_id: String,
+        id: Uuid,
+    ) -> Result<SharedDB, AppError> {
+        let mut db: Box<DbType> = match config.source_type() {
             SourceType::Database(db) => match db {
                 Database::Mysql => {
                     let conn = MysqlConnector::new(config.clone())?;
-                    MySqlDB::new(Arc::new(conn), user_id)
+                    Box::new(MySqlDB::new(Arc::new(conn), user_id, id))
+                }
+                Database::Postgres => {
+                    let conn = PostgresConnector::new(config.clone())?;
+                    Box::new(PostgresDB::new(Arc::new(conn), user_id, id))
+                }
+                Database::Sqlite => {
+                    let conn = SqliteConnector::new(config.clone())?;
+                    Box::new(SqliteDB::new(Arc::new(conn), user_id, id))
+                }
+                Database::Oracle => {
+                    let msg = "Oracle data sources are not supported yet.".to_string();
+                    return Err(BasableError::Unsupported(msg).into());
+                }
+            },
+            SourceType::File(file) => match file {
+                FileType::Csv => {
+                    let conn = CsvConnector::new(config.clone())?;
+                    Box::new(CsvDB::new(Arc::new(conn), user_id, id))
                 }
-                _ => todo!(),
             },
-            _ => todo!(),
+            SourceType::Cloud => {
+                let msg = "Cloud data sources are not supported yet.".to_string();
+                return Err(BasableError::Unsupported(msg).into());
+            }
         };
 
         let conn = db.connector().clone();
         db.load_tables(conn)?;
 
-        Ok(Arc::new(db))
+        Ok(Arc::from(db))
     }
 
-    /// Creates a new guest user using the request `SocketAddr`
-    pub(crate) fn create_guest_user(req_ip: &str) -> Result<JwtSession, AppError> {
+    /// Creates a new guest user with a random id
+    pub(crate) fn create_guest_user(db: &LocalDB) -> Result<JwtSession, AppError> {
         let user = User {
-            id: req_ip.to_owned(),
+            id: Uuid::new_v4().to_string(),
             ..Default::default()
         };
 
-        let session_id = create_jwt(user)?; // jwt encode the ip
+        let session_id = create_jwt(db, user)?;
         Ok(session_id)
     }
 
+    /// Open connections of the user.
+    pub(crate) fn user_connections(&self, user_id: &str) -> Vec<SharedDB> {
+        self.connections
+            .iter()
+            .filter(|c| c.user_id() == user_id)
+            .cloned()
+            .collect()
+    }
+
     /// Add connection.
     pub(crate) fn add_connection(&mut self, db: &SharedDB) {
         self.connections.push(db.clone());
     }
 
+    /// Removes connection `id` of the user, if it is open.
+    pub(crate) fn remove_connection(&mut self, id: &Uuid, user_id: &str) {
+        self.connections
+            .retain(|c| c.id() != id || c.user_id() != user_id);
+    }
+
     pub fn get_connection(&self, id: &str, user_id: &str) -> Option<SharedDB> {
-        let id = Uuid::from_str(id).unwrap();
+        let id = Uuid::from_str(id).ok()?;
 
         self.connections.iter()
             .find(|c| *c.id() == id && c.user_id() == user_id)
-            .map(|c| c.clone())
+            .cloned()
     }
 }
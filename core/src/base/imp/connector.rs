@@ -1,16 +1,61 @@
-use crate::base::{config::ConnectionConfig, AppError};
+use crate::base::{
+    config::ConnectionConfig,
+    data::{row::BasableRow, value::BasableValue},
+    AppError, BasableError,
+};
+
+use super::ConnectorType;
+
+/// Function run in a transaction by [`Connector::transaction`]. Its queries must be run with
+/// the given [`QueryExecutor`] to be part of the transaction.
+pub(crate) type TransactionFn<'a> =
+    dyn FnMut(&dyn QueryExecutor) -> Result<(), AppError> + Send + 'a;
 
 /// Facilitates connection and run queries between `Basable` instance and a databse server
 pub(crate) trait Connector: Send + Sync {
-    type Row;
-    type Error;
     /// Create a new connector
     fn new(conn: ConnectionConfig) -> Result<Self, AppError>
     where
         Self: Sized;
 
     /// Execute a database query and return results
-    fn exec_query(&self, query: &str) -> Result<Vec<Self::Row>, Self::Error>;
+    fn exec_query(&self, query: &str) -> Result<Vec<BasableRow>, BasableError> {
+        self.exec_query_params(query, &[])
+    }
+
+    /// Execute a database query with `params` bound to its placeholders and return results.
+    /// Values from users must always be passed as `params`, never spliced into `query`.
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError>;
+
+    /// Runs `f` in a transaction of one connection. The transaction is committed if `f`
+    /// succeeds, and rolled back otherwise. Rows read in the transaction can't be changed
+    /// by other connections until it ends, so that rows can be checked before they are
+    /// changed.
+    fn transaction(&self, f: &mut TransactionFn) -> Result<(), AppError>;
 
     fn config(&self) -> &ConnectionConfig;
-}
\ No newline at end of file
+}
+
+/// Runs queries, either on any connection of a [`Connector`] or in one of its transactions.
+pub(crate) trait QueryExecutor {
+    /// Execute a database query with `params` bound to its placeholders and return results.
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError>;
+}
+
+impl QueryExecutor for ConnectorType {
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        self.as_ref().exec_query_params(query, params)
+    }
+}
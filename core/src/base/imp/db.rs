@@ -1,25 +1,28 @@
-use mysql::MySqlError;
+use std::collections::HashMap;
+
 use uuid::Uuid;
 
+use crate::base::imp::graphs::chrono::{ChronoAnalysisBasis, ChronoTimeZone};
 use crate::base::query::filter::{Filter, FilterChain, FilterCondition, FilterOperator};
-use crate::base::query::{BasableQuery, QueryOperation};
-use crate::base::{data::table::TableSummaries, AppError};
-use crate::imp::database::mysql::db::MySqlDB;
+use crate::base::query::{quote_ident, BasableQuery, QueryOperation, SqlQuery};
+use crate::base::{
+    data::{
+        row::BasableRow,
+        table::{DataQueryResult, TableSummaries},
+        value::BasableValue,
+    },
+    AppError, BasableError,
+};
 use crate::imp::database::DbConnectionDetails;
 
+use super::connector::QueryExecutor;
 use super::graphs::VisualizeDB;
 use super::{ConnectorType, SharedTable};
 
 pub type DBQueryResult<R, E> = Result<Vec<R>, E>;
 
-pub type DBError = <MySqlDB as DB>::Error;
-
 /// An abstraction of database connection.
 pub trait DB: VisualizeDB + QuerySqlParser + Send + Sync {
-    type Row;
-    type Error;
-    type ColumnValue;
-
     fn id(&self) -> &Uuid;
 
     fn user_id(&self) -> &str;
@@ -36,9 +39,9 @@ pub trait DB: VisualizeDB + QuerySqlParser + Send + Sync {
     fn tables(&self) -> &Vec<SharedTable>;
 
     /// Query [`DB`] server for information about available tables. It only queries the database server and
-    /// return results as [`DB::Row`]. It is different from [`DB::load_tables`] which actually loads the [`Table`]
+    /// return results as [`BasableRow`]. It is different from [`DB::load_tables`] which actually loads the [`Table`]
     /// abstraction into memory.
-    fn query_tables(&self) -> DBQueryResult<Self::Row, Self::Error>;
+    fn query_tables(&self) -> DBQueryResult<BasableRow, BasableError>;
 
     /// Get an instance of a [`SharedTable`], as a mutable thread-safe reference.
     fn get_table(&self, name: &str) -> Option<&SharedTable>;
@@ -51,36 +54,224 @@ pub trait DB: VisualizeDB + QuerySqlParser + Send + Sync {
 
     /// Get total number of columns
     fn query_column_count(&self, table_name: &str) -> Result<u32, AppError>;
+
+    /// Get the [`SharedTable`] named `name`, to build a query on it. Unknown tables are a
+    /// [`BasableError::Identifier`].
+    fn resolve_table(&self, name: &str) -> Result<&SharedTable, BasableError> {
+        self.get_table(name)
+            .ok_or_else(|| BasableError::Identifier(format!("Unknown table '{name}'.")))
+    }
+
+    /// Rows of table `name` whose column `col` has one of `values`, with all of the table's
+    /// columns. The rows are read with `exec`, which can be a transaction of
+    /// [`Connector::transaction`](super::connector::Connector::transaction), like the rows
+    /// written by the other row methods.
+    fn query_rows(
+        &self,
+        exec: &dyn QueryExecutor,
+        name: &str,
+        col: &str,
+        values: &[String],
+    ) -> DataQueryResult<BasableValue, BasableError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = self.resolve_table(name)?;
+        let cols = table.query_columns()?;
+        let col = table.resolve_in(&cols, &[col])?.pop().unwrap();
+
+        let mut filters = FilterChain::new();
+        filters.add_one(Filter::Condition(FilterCondition {
+            column: self.quote_ident(&col),
+            operator: FilterOperator::Contains(values.to_vec()),
+        }));
+
+        let query = BasableQuery {
+            table: self.quote_table(table.name()),
+            filters,
+            ..Default::default()
+        };
+        let SqlQuery { sql, params } = self.generate_sql(query)?;
+        let rows = exec.exec_query_params(&sql, &params)?;
+
+        let data = rows
+            .iter()
+            .map(|r| {
+                cols.iter()
+                    .map(|c| {
+                        let v = r.value(&c.name).cloned().unwrap_or(BasableValue::Null);
+                        (c.name.clone(), v)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(data)
+    }
+
+    /// Inserts `row` into table `name`. Unlike [`TableCRUD::insert_data`](super::table::TableCRUD::insert_data),
+    /// the values keep their types, so that rows read with [`DB::query_rows`] can be written back.
+    ///
+    /// Returns the inserted row as the data source stored it, if the data source can return it
+    /// (see [`QuerySqlParser::parse_returning`]).
+    fn insert_row(
+        &self,
+        exec: &dyn QueryExecutor,
+        name: &str,
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<Option<HashMap<String, BasableValue>>, BasableError> {
+        let table = self.resolve_table(name)?;
+        let SqlQuery { sql, params } = table.generate_sql(table.insert_query(row)?)?;
+        let inserted = exec.exec_query_params(&sql, &params)?.pop();
+
+        Ok(inserted.map(BasableRow::into_map))
+    }
+
+    /// Inserts `row` into table `name`, or updates the row with the same value of column
+    /// `key`. Returns the written row, like [`DB::insert_row`].
+    fn upsert_row(
+        &self,
+        exec: &dyn QueryExecutor,
+        name: &str,
+        key: &str,
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<Option<HashMap<String, BasableValue>>, BasableError> {
+        let table = self.resolve_table(name)?;
+        let SqlQuery { sql, params } = table.generate_sql(table.upsert_query(key, row)?)?;
+        let upserted = exec.exec_query_params(&sql, &params)?.pop();
+
+        Ok(upserted.map(BasableRow::into_map))
+    }
+
+    /// Sets the columns of the rows of table `name` whose column `col` has `value` to the
+    /// values in `row`. The values keep their types, like [`DB::insert_row`].
+    fn update_row(
+        &self,
+        exec: &dyn QueryExecutor,
+        name: &str,
+        col: &str,
+        value: &str,
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<(), BasableError> {
+        let table = self.resolve_table(name)?;
+        let query = table.update_query(col, value, row)?;
+
+        table.exec_query_with(exec, query)
+    }
+
+    /// Deletes the rows of table `name` whose column `col` has `value`.
+    fn delete_rows(
+        &self,
+        exec: &dyn QueryExecutor,
+        name: &str,
+        col: &str,
+        value: &str,
+    ) -> Result<(), BasableError> {
+        let table = self.resolve_table(name)?;
+        let query = table.delete_query(col, value)?;
+
+        table.exec_query_with(exec, query)
+    }
 }
 
 pub trait QuerySqlParser {
-    fn parse_filter_operator(fo: &FilterOperator) -> String
-    where
-        Self: Sized,
-    {
+    /// Quotes a table or column `name` for use in SQL.
+    fn quote_ident(&self, name: &str) -> String {
+        quote_ident(name)
+    }
+
+    /// Quotes a table `name` for use in SQL. Override it for data sources whose table names
+    /// are qualified.
+    fn quote_table(&self, name: &str) -> String {
+        self.quote_ident(name)
+    }
+
+    /// Placeholder of the `index`th parameter of a query. `index` starts from 1.
+    fn placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
+
+    /// Adds `value` to `params` and returns its placeholder.
+    fn bind(&self, value: &str, params: &mut Vec<BasableValue>) -> String {
+        self.bind_value(BasableValue::Text(value.to_string()), params)
+    }
+
+    /// Adds `value` to `params`, keeping its type, and returns its placeholder.
+    fn bind_value(&self, value: BasableValue, params: &mut Vec<BasableValue>) -> String {
+        params.push(value);
+        self.placeholder(params.len())
+    }
+
+    /// Clause of an upsert that follows its `INSERT`. The row is updated when it conflicts
+    /// on the `keys` columns, and `cols` are set to the inserted values.
+    fn parse_upsert(&self, keys: &[String], cols: &[String]) -> String {
+        let keys = keys.join(", ");
+        if cols.is_empty() {
+            return format!("ON CONFLICT ({keys}) DO NOTHING");
+        }
+
+        let cols: Vec<String> = cols.iter().map(|c| format!("{c} = excluded.{c}")).collect();
+        format!("ON CONFLICT ({keys}) DO UPDATE SET {}", cols.join(", "))
+    }
+
+    /// Clause of an insert or upsert that returns the written row, with all of its columns.
+    /// `None` for data sources that can't return it.
+    fn parse_returning(&self) -> Option<&'static str> {
+        Some("RETURNING *")
+    }
+
+    /// Operator that matches a value against a regular expression, or that doesn't match it
+    /// when `negated`.
+    fn regex_operator(&self, negated: bool) -> &'static str {
+        match negated {
+            true => "NOT REGEXP",
+            false => "REGEXP",
+        }
+    }
+
+    fn parse_filter_operator(&self, fo: &FilterOperator, params: &mut Vec<BasableValue>) -> String {
         match fo {
-            FilterOperator::Eq(v) => format!("= '{v}'"),
-            FilterOperator::NotEq(v) => format!("!= '{v}'"),
-            FilterOperator::Gt(v) => format!("> '{v}'"),
-            FilterOperator::Lt(v) => format!("< '{v}'"),
-            FilterOperator::Gte(v) => format!(">= '{v}'"),
-            FilterOperator::Lte(v) => format!("<= '{v}'"),
-            FilterOperator::Like(v) => format!("LIKE '{v}%'"),
-            FilterOperator::NotLike(v) => format!("NOT LIKE '{v}%'"),
-            FilterOperator::LikeSingle(v) => format!("LIKE '_{v}%'"),
-            FilterOperator::NotLikeSingle(v) => format!("NOT LIKE '_{v}%'"),
-            FilterOperator::Regex(v) => format!("REGEXP '{v}'"),
-            FilterOperator::NotRegex(v) => format!("NOT REGEXP '{v}'"),
-            FilterOperator::Btw(start, end) => format!("BETWEEN '{start}' AND '{end}'"),
-            FilterOperator::NotBtw(start, end) => format!("NOT BETWEEN '{start}' AND '{end}'"),
+            FilterOperator::Eq(v) => format!("= {}", self.bind(v, params)),
+            FilterOperator::NotEq(v) => format!("!= {}", self.bind(v, params)),
+            FilterOperator::Gt(v) => format!("> {}", self.bind(v, params)),
+            FilterOperator::Lt(v) => format!("< {}", self.bind(v, params)),
+            FilterOperator::Gte(v) => format!(">= {}", self.bind(v, params)),
+            FilterOperator::Lte(v) => format!("<= {}", self.bind(v, params)),
+            FilterOperator::Like(v) => format!("LIKE {}", self.bind(&format!("{v}%"), params)),
+            FilterOperator::NotLike(v) => {
+                format!("NOT LIKE {}", self.bind(&format!("{v}%"), params))
+            }
+            FilterOperator::LikeSingle(v) => {
+                format!("LIKE {}", self.bind(&format!("_{v}%"), params))
+            }
+            FilterOperator::NotLikeSingle(v) => {
+                format!("NOT LIKE {}", self.bind(&format!("_{v}%"), params))
+            }
+            FilterOperator::Regex(v) => {
+                format!("{} {}", self.regex_operator(false), self.bind(v, params))
+            }
+            FilterOperator::NotRegex(v) => {
+                format!("{} {}", self.regex_operator(true), self.bind(v, params))
+            }
+            FilterOperator::Btw(start, end) => format!(
+                "BETWEEN {} AND {}",
+                self.bind(start, params),
+                self.bind(end, params)
+            ),
+            FilterOperator::NotBtw(start, end) => format!(
+                "NOT BETWEEN {} AND {}",
+                self.bind(start, params),
+                self.bind(end, params)
+            ),
             FilterOperator::Contains(values) => {
-                let v: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                let v: Vec<String> = values.iter().map(|v| self.bind(v, params)).collect();
                 let v = v.join(", ");
 
                 format!("IN ({v})")
             }
             FilterOperator::NotContains(values) => {
-                let v: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                let v: Vec<String> = values.iter().map(|v| self.bind(v, params)).collect();
                 let v = v.join(", ");
 
                 format!("NOT IN ({v})")
@@ -90,46 +281,129 @@ pub trait QuerySqlParser {
         }
     }
 
-    fn parse_filter_condition(c: &FilterCondition) -> String
-    where
-        Self: Sized,
-    {
-        format!("{} {}", c.column, Self::parse_filter_operator(&c.operator))
+    fn parse_filter_condition(
+        &self,
+        c: &FilterCondition,
+        params: &mut Vec<BasableValue>,
+    ) -> String {
+        format!(
+            "{} {}",
+            c.column,
+            self.parse_filter_operator(&c.operator, params)
+        )
     }
 
-    fn parse_filter(filter: &Filter) -> String
-    where
-        Self: Sized,
-    {
+    /// SQL of `filter`. Groups are parenthesized, and empty groups are rendered as constant
+    /// conditions: true for `AND`, false for `OR`.
+    fn parse_filter(&self, filter: &Filter, params: &mut Vec<BasableValue>) -> String {
+        let mut group = |filters: &[Filter], join: &str, empty: &str| {
+            if filters.is_empty() {
+                return empty.to_string();
+            }
+
+            let filters: Vec<String> = filters
+                .iter()
+                .map(|f| self.parse_filter(f, params))
+                .collect();
+            format!("({})", filters.join(join))
+        };
+
         match filter {
-            Filter::BASE(c) => Self::parse_filter_condition(c),
-            Filter::AND(c) => format!("AND {}", Self::parse_filter_condition(c)),
-            Filter::OR(c) => format!("OR {}", Self::parse_filter_condition(c)),
+            Filter::Condition(c) => self.parse_filter_condition(c, params),
+            Filter::And(filters) => group(filters, " AND ", "1 = 1"),
+            Filter::Or(filters) => group(filters, " OR ", "1 = 0"),
+            Filter::Not(filter) => format!("NOT ({})", self.parse_filter(filter, params)),
         }
     }
 
-    fn parse_filter_chain(filters: &FilterChain) -> String
-    where
-        Self: Sized,
-    {
+    fn parse_filter_chain(&self, filters: &FilterChain, params: &mut Vec<BasableValue>) -> String {
         let filters: Vec<String> = filters
             .all()
             .iter()
-            .map(|f| Self::parse_filter(f))
+            .map(|f| self.parse_filter(f, params))
             .collect();
-        filters.join(" ")
+        filters.join(" AND ")
     }
 
-    fn generate_sql(&self, query: BasableQuery) -> Result<String, MySqlError> {
+    /// Columns and placeholders of the values of `row`, for an `INSERT`.
+    fn parse_row(
+        &self,
+        row: Vec<(String, BasableValue)>,
+        params: &mut Vec<BasableValue>,
+    ) -> Result<(String, String), BasableError> {
+        if row.is_empty() {
+            return Err(BasableError::Query(
+                "At least one column must be inserted.".to_string(),
+            ));
+        }
+
+        let (cols, values): (Vec<String>, Vec<String>) = row
+            .into_iter()
+            .map(|(c, v)| (c, self.bind_value(v, params)))
+            .unzip();
+
+        Ok((cols.join(", "), values.join(", ")))
+    }
+
+    /// SQL expression that reduces `col` to the bucket of the given [`ChronoAnalysisBasis`],
+    /// labelled as described by the basis. The default is written for MySQL.
+    fn parse_chrono_basis(&self, basis: &ChronoAnalysisBasis, col: &str) -> String {
+        match basis {
+            ChronoAnalysisBasis::Hourly => format!("DATE_FORMAT({col}, '%Y-%m-%d %H:00:00')"),
+            ChronoAnalysisBasis::Daily => format!("DATE({col})"),
+            ChronoAnalysisBasis::Weekly => format!("DATE(DATE_SUB({col}, INTERVAL WEEKDAY({col}) DAY))"),
+            ChronoAnalysisBasis::Monthly => format!("DATE(DATE_FORMAT({col}, '%Y-%m-01'))"),
+            ChronoAnalysisBasis::Quarterly => {
+                format!("MAKEDATE(YEAR({col}), 1) + INTERVAL QUARTER({col}) - 1 QUARTER")
+            }
+            ChronoAnalysisBasis::Yearly => format!("MAKEDATE(YEAR({col}), 1)"),
+            ChronoAnalysisBasis::DayOfWeek => format!("WEEKDAY({col}) + 1"),
+            ChronoAnalysisBasis::HourOfDay => format!("HOUR({col})"),
+            ChronoAnalysisBasis::Minutes(n) => format!(
+                "DATE_FORMAT(TIMESTAMP('1970-01-01') + INTERVAL FLOOR(TIMESTAMPDIFF(MINUTE, '1970-01-01', {col}) / {n}) * {n} MINUTE, '%Y-%m-%d %H:%i:00')"
+            ),
+            ChronoAnalysisBasis::Days(n) => format!(
+                "DATE('1970-01-01') + INTERVAL FLOOR(DATEDIFF({col}, '1970-01-01') / {n}) * {n} DAY"
+            ),
+        }
+    }
+
+    /// SQL expression that casts `expr` to text.
+    fn parse_text_cast(&self, expr: &str) -> String {
+        format!("CAST({expr} AS CHAR)")
+    }
+
+    /// SQL expression of the continuous `percentile` of `col`, a fraction between 0 and 1.
+    fn parse_percentile(&self, _percentile: f64, _col: &str) -> Result<String, BasableError> {
+        Err(BasableError::Unsupported(
+            "Percentiles are not supported by this data source.".to_string(),
+        ))
+    }
+
+    /// SQL expression that converts `col` to `time_zone`, for bucketing. The default is
+    /// written for MySQL, where named time zones need the server's time zone tables.
+    fn parse_time_zone(
+        &self,
+        col: &str,
+        time_zone: &ChronoTimeZone,
+    ) -> Result<String, BasableError> {
+        Ok(format!(
+            "CONVERT_TZ({col}, @@session.time_zone, '{time_zone}')"
+        ))
+    }
+
+    fn generate_sql(&self, query: BasableQuery) -> Result<SqlQuery, BasableError> {
         let BasableQuery {
             table,
             operation,
             filters,
             limit,
+            offset,
             order_by,
             group_by,
             left_join,
             having,
+            mut params,
         } = query;
 
         // Parse query operation type
@@ -142,6 +416,53 @@ pub trait QuerySqlParser {
 
                 format!("SELECT {select_cols} FROM {table}")
             }
+            QueryOperation::Insert(row) => {
+                let (cols, values) = self.parse_row(row, &mut params)?;
+                let mut sql = format!("INSERT INTO {table} ({cols}) VALUES ({values})");
+                if let Some(returning) = self.parse_returning() {
+                    sql = format!("{sql} {returning}");
+                }
+
+                return Ok(SqlQuery { sql, params });
+            }
+            QueryOperation::Upsert { row, keys } => {
+                if keys.is_empty() {
+                    return Err(BasableError::Query(
+                        "Upserts need at least one key column.".to_string(),
+                    ));
+                }
+
+                let updated: Vec<String> = row
+                    .iter()
+                    .map(|(c, _)| c.clone())
+                    .filter(|c| !keys.contains(c))
+                    .collect();
+                let (cols, values) = self.parse_row(row, &mut params)?;
+                let mut sql = format!(
+                    "INSERT INTO {table} ({cols}) VALUES ({values}) {}",
+                    self.parse_upsert(&keys, &updated)
+                );
+                if let Some(returning) = self.parse_returning() {
+                    sql = format!("{sql} {returning}");
+                }
+
+                return Ok(SqlQuery { sql, params });
+            }
+            QueryOperation::Update(row) => {
+                if row.is_empty() {
+                    return Err(BasableError::Query(
+                        "At least one column must be updated.".to_string(),
+                    ));
+                }
+
+                let data: Vec<String> = row
+                    .into_iter()
+                    .map(|(c, v)| format!("{c} = {}", self.bind_value(v, &mut params)))
+                    .collect();
+
+                format!("UPDATE {table} SET {}", data.join(", "))
+            }
+            QueryOperation::Delete => format!("DELETE FROM {table}"),
         };
 
         // Parse left join
@@ -151,7 +472,7 @@ pub trait QuerySqlParser {
 
         // Parse query filters
         if filters.not_empty() {
-            let filter_chain = <MySqlDB as QuerySqlParser>::parse_filter_chain(&filters);
+            let filter_chain = self.parse_filter_chain(&filters, &mut params);
             sql.push_str(format!(" WHERE {filter_chain}").as_str())
         }
 
@@ -163,7 +484,7 @@ pub trait QuerySqlParser {
 
         // Parse HAVING
         if having.not_empty() {
-            let filter_chain = <MySqlDB as QuerySqlParser>::parse_filter_chain(&having);
+            let filter_chain = self.parse_filter_chain(&having, &mut params);
             sql.push_str(format!(" HAVING {filter_chain}").as_str())
         }
 
@@ -177,21 +498,31 @@ pub trait QuerySqlParser {
             sql.push_str(format!(" LIMIT {limit}").as_str());
         }
 
-        Ok(sql)
+        // Parse OFFSET
+        if let Some(offset) = offset {
+            sql.push_str(format!(" OFFSET {offset}").as_str());
+        }
+
+        Ok(SqlQuery { sql, params })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{collections::HashMap, slice};
+
+    use axum::http::StatusCode;
+
     use crate::{
         base::{
+            data::value::BasableValue,
             query::{
                 filter::{Filter, FilterChain, FilterCondition, FilterOperator},
-                BasableQuery, QueryOperation,
+                BasableQuery, QueryOperation, SqlQuery,
             },
             AppError,
         },
-        tests::common::create_test_db,
+        tests::common::{create_test_db, get_test_db_table},
     };
 
     #[test]
@@ -208,7 +539,16 @@ mod tests {
             operator: FilterOperator::Btw("2010-09-01".to_string(), "2010-11-30".to_string()),
         };
 
-        filters.add_multiple(vec![Filter::BASE(c1), Filter::AND(c2)]);
+        let c3 = FilterCondition {
+            column: "critic_score".to_string(),
+            operator: FilterOperator::Null,
+        };
+
+        filters.add_one(Filter::Condition(c1));
+        filters.add_one(Filter::Or(vec![
+            Filter::Condition(c2),
+            Filter::Not(Box::new(Filter::Condition(c3))),
+        ]));
 
         let query = BasableQuery {
             table: "vhchartz".to_string(),
@@ -223,6 +563,108 @@ mod tests {
 
         assert!(sql.is_ok());
 
+        // Values are bound as parameters, not written into the SQL.
+        let SqlQuery { sql, params } = sql.unwrap();
+        assert!(!sql.contains("Rockstar Games"));
+        assert!(sql.contains(" AND (release_date BETWEEN "));
+        assert!(sql.contains(" OR NOT (critic_score IS NULL))"));
+        assert_eq!(
+            params,
+            ["Rockstar Games", "2010-09-01", "2010-11-30"]
+                .map(|v| BasableValue::Text(v.to_string()))
+        );
+
+        // Writes
+        let key = || {
+            let mut filters = FilterChain::new();
+            filters.add_one(Filter::Condition(FilterCondition {
+                column: "id".to_string(),
+                operator: FilterOperator::Eq("1".to_string()),
+            }));
+            filters
+        };
+        let title = || ("title".to_string(), BasableValue::Text("Halo".to_string()));
+
+        let queries = [
+            (QueryOperation::Insert(vec![title()]), FilterChain::new()),
+            (QueryOperation::Update(vec![title()]), key()),
+            (QueryOperation::Delete, key()),
+        ];
+        for ((operation, filters), expected) in queries.into_iter().zip([
+            "INSERT INTO vgchartz (title) VALUES ",
+            "UPDATE vgchartz SET title = ",
+            "DELETE FROM vgchartz WHERE id = ",
+        ]) {
+            let query = BasableQuery {
+                table: "vgchartz".to_string(),
+                operation,
+                filters,
+                ..Default::default()
+            };
+            let SqlQuery { sql, params } = db.generate_sql(query)?;
+            assert!(sql.starts_with(expected), "{sql}");
+            assert!(!params.is_empty());
+        }
+
+        let upsert = BasableQuery {
+            table: "vgchartz".to_string(),
+            operation: QueryOperation::Upsert {
+                row: vec![("id".to_string(), BasableValue::Int(1)), title()],
+                keys: vec!["id".to_string()],
+            },
+            ..Default::default()
+        };
+        let SqlQuery { sql, params } = db.generate_sql(upsert)?;
+        assert!(sql.starts_with("INSERT INTO vgchartz (id, title) VALUES "));
+        // Only the columns that aren't keys are updated.
+        assert!(sql.contains("title = ") && !sql.contains("id = "), "{sql}");
+        assert_eq!(params[0], BasableValue::Int(1));
+
+        let empty = BasableQuery {
+            operation: QueryOperation::Update(Vec::new()),
+            ..Default::default()
+        };
+        assert!(db.generate_sql(empty).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table = get_test_db_table();
+        let title = "Basable transaction".to_string();
+        let row = HashMap::from([("title".to_string(), BasableValue::Text(title.clone()))]);
+        let count = || -> Result<usize, AppError> {
+            let rows = db.query_rows(db.connector(), &table, "title", slice::from_ref(&title))?;
+            Ok(rows.len())
+        };
+
+        // Rows written in a failed transaction are rolled back.
+        let failed = db.connector().transaction(&mut |exec| {
+            db.insert_row(exec, &table, &row)?;
+            let rows = db.query_rows(exec, &table, "title", slice::from_ref(&title))?;
+            assert_eq!(rows.len(), 1);
+
+            Err(AppError::new(StatusCode::CONFLICT, "Rolled back"))
+        });
+        assert!(matches!(failed, Err(AppError(StatusCode::CONFLICT, _))));
+        assert_eq!(count()?, 0);
+
+        db.connector().transaction(&mut |exec| {
+            // The inserted row is returned as it was stored, with its generated id.
+            if let Some(inserted) = db.insert_row(exec, &table, &row)? {
+                assert!(matches!(inserted["id"], BasableValue::Int(_)));
+                assert_eq!(inserted["title"], row["title"]);
+            }
+
+            Ok(())
+        })?;
+        assert_eq!(count()?, 1);
+
+        db.delete_rows(db.connector(), &table, "title", &title)?;
+        assert_eq!(count()?, 0);
+
         Ok(())
     }
 }
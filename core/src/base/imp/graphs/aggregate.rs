@@ -0,0 +1,132 @@
+use std::{collections::HashMap, fmt::Display};
+
+use axum::http::StatusCode;
+
+use crate::base::{
+    imp::{db::DB, SharedTable},
+    AppError, BasableError,
+};
+
+/// The function that reduces the rows of a graph's bucket or category to its value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum AggregateFunction {
+    #[default]
+    Count,
+    CountDistinct,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    /// Continuous percentile, as a fraction between 0 and 1.
+    Percentile(f64),
+}
+
+impl TryFrom<&String> for AggregateFunction {
+    type Error = String;
+
+    /// Parses the function names, and percentiles written as `median` or `p90`.
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        let function = match value.as_str() {
+            "count" => AggregateFunction::Count,
+            "count_distinct" => AggregateFunction::CountDistinct,
+            "sum" => AggregateFunction::Sum,
+            "avg" => AggregateFunction::Avg,
+            "min" => AggregateFunction::Min,
+            "max" => AggregateFunction::Max,
+            "median" => AggregateFunction::Percentile(0.5),
+            _ => {
+                let percentile = value
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .filter(|p| *p > 0.0 && *p < 100.0);
+
+                return percentile
+                    .map(|p| AggregateFunction::Percentile(p / 100.0))
+                    .ok_or_else(|| "error parsing aggregate function".to_string());
+            }
+        };
+
+        Ok(function)
+    }
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregateFunction::Count => write!(f, "count"),
+            AggregateFunction::CountDistinct => write!(f, "count_distinct"),
+            AggregateFunction::Sum => write!(f, "sum"),
+            AggregateFunction::Avg => write!(f, "avg"),
+            AggregateFunction::Min => write!(f, "min"),
+            AggregateFunction::Max => write!(f, "max"),
+            AggregateFunction::Percentile(p) => write!(f, "p{}", p * 100.0),
+        }
+    }
+}
+
+/// The value of a graph's buckets or categories. The default counts rows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct GraphAggregate {
+    pub function: AggregateFunction,
+
+    /// The column the function is applied to. Only [`AggregateFunction::Count`] can do
+    /// without, and then counts rows.
+    pub column: Option<String>,
+}
+
+impl GraphAggregate {
+    /// Reads the `aggregate` and `value_column` query params.
+    pub fn from_query_params(params: &HashMap<String, String>) -> Result<Self, AppError> {
+        let err = |msg: String| AppError::new(StatusCode::EXPECTATION_FAILED, &msg);
+
+        let function = match params.get("aggregate") {
+            Some(function) => AggregateFunction::try_from(function).map_err(err)?,
+            None => AggregateFunction::Count,
+        };
+        let column = params.get("value_column").cloned();
+
+        if column.is_none() && function != AggregateFunction::Count {
+            return Err(err(format!("'{function}' needs a 'value_column'")));
+        }
+
+        Ok(GraphAggregate { function, column })
+    }
+
+    /// SQL expression of the aggregate. The column is resolved in `tbl`, and prefixed with
+    /// the table alias `alias` when given.
+    pub fn parse<D: DB + ?Sized>(
+        &self,
+        db: &D,
+        tbl: &SharedTable,
+        alias: Option<&str>,
+    ) -> Result<String, BasableError> {
+        let col = match &self.column {
+            Some(col) => {
+                let col = db.quote_ident(&tbl.resolve_columns(&[col])?[0]);
+                match alias {
+                    Some(alias) => format!("{alias}.{col}"),
+                    None => col,
+                }
+            }
+            None if self.function == AggregateFunction::Count => return Ok("COUNT(*)".to_string()),
+            None => {
+                return Err(BasableError::Query(format!(
+                    "'{}' needs a value column.",
+                    self.function
+                )))
+            }
+        };
+
+        let sql = match self.function {
+            AggregateFunction::Count => format!("COUNT({col})"),
+            AggregateFunction::CountDistinct => format!("COUNT(DISTINCT {col})"),
+            AggregateFunction::Sum => format!("SUM({col})"),
+            AggregateFunction::Avg => format!("AVG({col})"),
+            AggregateFunction::Min => format!("MIN({col})"),
+            AggregateFunction::Max => format!("MAX({col})"),
+            AggregateFunction::Percentile(p) => db.parse_percentile(p, &col)?,
+        };
+
+        Ok(sql)
+    }
+}
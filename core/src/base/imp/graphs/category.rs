@@ -1,36 +1,321 @@
-use crate::base::query::{BasableQuery, QueryOperation};
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::{
+    base::{
+        imp::{
+            db::DB,
+            graphs::{
+                aggregate::{AggregateFunction, GraphAggregate},
+                series::{SeriesOpts, SeriesSplit},
+            },
+        },
+        query::{
+            filter::{Filter, FilterChain, FilterCondition, FilterOperator},
+            BasableQuery, QueryOperation, QueryOrder,
+        },
+        AppError, BasableError,
+    },
+    globals::{BASABLE_CATEGORY_COL, BASABLE_CATEGORY_VALUE, BASABLE_SERIES_COL},
+};
+
+/// Number of categories of a graph when no limit is given.
+const DEFAULT_CATEGORY_LIMIT: usize = 20;
 
 pub enum CategoryGraphType {
+    /// Counts the rows of each value of the target column.
     Simple,
+
+    /// Counts the rows linked to each category through a join table, see [`JoinOptions`].
     ManyToMany,
-    Manual
+
+    /// Counts the rows in each of the categories defined by the user, see [`ManualCategory`].
+    Manual,
+}
+
+impl TryFrom<&String> for CategoryGraphType {
+    type Error = AppError;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "simple" => Ok(Self::Simple),
+            "many_to_many" => Ok(Self::ManyToMany),
+            "manual" => Ok(Self::Manual),
+            _ => Err(AppError::new(
+                StatusCode::EXPECTATION_FAILED,
+                "Invalid CategoryGraphType",
+            )),
+        }
+    }
+}
+
+/// Options of [`CategoryGraphType::ManyToMany`] graphs. The categories are the rows of
+/// [`CategoryGraphOpts::table`], and `join_table` has a row for each row linked to a category.
+pub struct JoinOptions {
+    /// The table that links rows to categories.
+    pub join_table: String,
+
+    /// Column of `join_table` that references the category.
+    pub join_col: String,
+
+    /// Column of [`CategoryGraphOpts::table`] referenced by `join_col`.
+    pub category_key: String,
+}
+
+/// A category of [`CategoryGraphType::Manual`] graphs. Rows whose target column matches
+/// `operator` are counted under `label`, such as
+/// `{ "label": "2010s", "op": "btw", "value": ["2010-01-01", "2019-12-31"] }` in JSON.
+#[derive(Deserialize)]
+pub struct ManualCategory {
+    pub label: String,
+
+    #[serde(flatten)]
+    pub operator: FilterOperator,
 }
 
 pub struct CategoryGraphOpts {
     pub table: String,
     pub graph_type: CategoryGraphType,
+
+    /// The column of the categories. For [`CategoryGraphType::ManyToMany`], this is the
+    /// column of the categories' names.
     pub target_col: String,
-    pub limit: usize
+    pub limit: usize,
+
+    /// Configure this option if you're using [`CategoryGraphType::ManyToMany`].
+    pub join: Option<JoinOptions>,
+
+    /// Categories of [`CategoryGraphType::Manual`] graphs. A row is counted in the first
+    /// category it matches, and rows that match no category are not counted.
+    pub categories: Option<Vec<ManualCategory>>,
+
+    /// The value of each category. For [`CategoryGraphType::ManyToMany`], the value column
+    /// is a column of the join table.
+    pub aggregate: GraphAggregate,
+
+    /// Splits the graph into series. Each series then has up to `limit` categories.
+    pub series: Option<SeriesOpts>,
 }
 
-impl From<CategoryGraphOpts> for BasableQuery {
-    fn from(value: CategoryGraphOpts) -> Self {
+impl CategoryGraphOpts {
+    pub fn from_query_params(params: HashMap<String, String>) -> Result<Self, AppError> {
+        let err = |msg: &str| AppError::new(StatusCode::EXPECTATION_FAILED, msg);
+
+        let (Some(table), Some(target_col)) = (params.get("table"), params.get("column")) else {
+            return Err(err("Missing query parameters"));
+        };
+
+        let graph_type = match params.get("graph_type") {
+            Some(graph_type) => graph_type.try_into()?,
+            None => CategoryGraphType::Simple,
+        };
+
+        let limit = match params.get("limit") {
+            Some(l) => l.parse::<usize>().map_err(|e| err(&e.to_string()))?,
+            None => DEFAULT_CATEGORY_LIMIT,
+        };
+
+        let join = match (
+            params.get("join_table"),
+            params.get("join_column"),
+            params.get("category_key"),
+        ) {
+            (None, None, None) => None,
+            (Some(join_table), Some(join_col), Some(category_key)) => Some(JoinOptions {
+                join_table: join_table.to_string(),
+                join_col: join_col.to_string(),
+                category_key: category_key.to_string(),
+            }),
+            _ => {
+                return Err(err(
+                    "'join_table', 'join_column' and 'category_key' must be provided together",
+                ))
+            }
+        };
+
+        let categories = match params.get("categories") {
+            Some(categories) => {
+                Some(serde_json::from_str(categories).map_err(|e| err(&e.to_string()))?)
+            }
+            None => None,
+        };
+
+        match (&graph_type, &join, &categories) {
+            (CategoryGraphType::ManyToMany, None, _) => {
+                return Err(err("missing 'join_table' parameter"))
+            }
+            (CategoryGraphType::Manual, _, None) => {
+                return Err(err("missing 'categories' parameter"))
+            }
+            _ => {}
+        }
+
+        Ok(CategoryGraphOpts {
+            table: table.to_string(),
+            graph_type,
+            target_col: target_col.to_string(),
+            limit,
+            join,
+            categories,
+            aggregate: GraphAggregate::from_query_params(&params)?,
+            series: SeriesOpts::from_query_params(&params)?,
+        })
+    }
+
+    /// How the results are split into series.
+    pub fn split(&self) -> SeriesSplit {
+        SeriesSplit::new(self.series.as_ref(), Some(self.limit))
+    }
+
+    /// Builds the [`BasableQuery`] for this analysis. `db` resolves the tables and columns.
+    /// Categories are selected as [`BASABLE_CATEGORY_COL`], and counts as
+    /// [`BASABLE_CATEGORY_VALUE`].
+    pub fn into_query<D: DB + ?Sized>(self, db: &D) -> Result<BasableQuery, BasableError> {
         let CategoryGraphOpts {
             table,
             graph_type,
             target_col,
             limit,
-        } = value;
-        
-        let select_columns = vec!["COUNT(*) as COUNT".to_string(), target_col.clone()];
-        let operation = QueryOperation::SelectData(Some(select_columns));
+            join,
+            categories,
+            aggregate,
+            series,
+        } = self;
 
-        BasableQuery {
-            table,
-            operation,
-            group_by: Some(vec![target_col]),
-            limit: Some(limit),
-            ..Default::default()
+        let tbl = db.resolve_table(&table)?;
+        let target_col = db.quote_ident(&tbl.resolve_columns(&[&target_col])?[0]);
+        let table = db.quote_table(tbl.name());
+
+        // Categories without a value, such as categories without linked rows, come last.
+        let order_by = |value: &str| Some(QueryOrder::DESC(format!("{value} IS NULL, {value}")));
+
+        // Series are read from the graph's table, and the categories of each series are limited
+        // by [`SeriesSplit`] instead of the query.
+        let alias = matches!(graph_type, CategoryGraphType::ManyToMany).then_some("x");
+        let series = series.map(|s| s.parse(db, tbl, alias)).transpose()?;
+        let series_column = series
+            .as_ref()
+            .map(|s| format!("{s} AS {BASABLE_SERIES_COL}"));
+        let limit = series.is_none().then_some(limit);
+
+        match graph_type {
+            CategoryGraphType::Simple => {
+                let value = aggregate.parse(db, tbl, None)?;
+                let mut select_columns = vec![
+                    format!("{value} AS {BASABLE_CATEGORY_VALUE}"),
+                    format!("{target_col} AS {BASABLE_CATEGORY_COL}"),
+                ];
+                select_columns.extend(series_column);
+                let operation = QueryOperation::SelectData(Some(select_columns));
+
+                let mut group_by = vec![target_col];
+                group_by.extend(series);
+
+                Ok(BasableQuery {
+                    table,
+                    operation,
+                    group_by: Some(group_by),
+                    order_by: order_by(&value),
+                    limit,
+                    ..Default::default()
+                })
+            }
+
+            CategoryGraphType::ManyToMany => {
+                let JoinOptions {
+                    join_table,
+                    join_col,
+                    category_key,
+                } = join.ok_or_else(|| {
+                    BasableError::Query("You must provide join table options.".to_string())
+                })?;
+
+                let category_key = db.quote_ident(&tbl.resolve_columns(&[&category_key])?[0]);
+
+                let jtbl = db.resolve_table(&join_table)?;
+                let join_col = db.quote_ident(&jtbl.resolve_columns(&[&join_col])?[0]);
+                let join_table = db.quote_table(jtbl.name());
+
+                // Categories without linked rows are counted too, with a count of 0.
+                let value = match aggregate {
+                    GraphAggregate {
+                        function: AggregateFunction::Count,
+                        column: None,
+                    } => format!("COUNT(y.{join_col})"),
+                    _ => aggregate.parse(db, jtbl, Some("y"))?,
+                };
+                let mut select_columns = vec![
+                    format!("{value} AS {BASABLE_CATEGORY_VALUE}"),
+                    format!("x.{target_col} AS {BASABLE_CATEGORY_COL}"),
+                ];
+                select_columns.extend(series_column);
+                let operation = QueryOperation::SelectData(Some(select_columns));
+                let left_join = format!("{join_table} y ON x.{category_key} = y.{join_col}");
+
+                let mut group_by = vec![format!("x.{target_col}")];
+                group_by.extend(series);
+
+                Ok(BasableQuery {
+                    table: format!("{table} x"),
+                    operation,
+                    left_join: Some(left_join),
+                    group_by: Some(group_by),
+                    order_by: order_by(&value),
+                    limit,
+                    ..Default::default()
+                })
+            }
+
+            CategoryGraphType::Manual => {
+                let categories = categories.filter(|c| !c.is_empty()).ok_or_else(|| {
+                    BasableError::Query("You must provide at least one category.".to_string())
+                })?;
+
+                // Each row gets the label of the first category it matches.
+                let value = aggregate.parse(db, tbl, None)?;
+                let mut params = Vec::new();
+                let mut matches = Vec::new();
+                let cases: Vec<String> = categories
+                    .into_iter()
+                    .map(|c| {
+                        let filter = Filter::Condition(FilterCondition {
+                            column: target_col.clone(),
+                            operator: c.operator,
+                        });
+                        let condition = db.parse_filter(&filter, &mut params);
+                        let label = db.bind(&c.label, &mut params);
+                        matches.push(filter);
+
+                        format!("WHEN {condition} THEN {label}")
+                    })
+                    .collect();
+
+                let mut select_columns = vec![
+                    format!("{value} AS {BASABLE_CATEGORY_VALUE}"),
+                    format!("CASE {} END AS {BASABLE_CATEGORY_COL}", cases.join(" ")),
+                ];
+                select_columns.extend(series_column);
+                let operation = QueryOperation::SelectData(Some(select_columns));
+
+                let mut group_by = vec![BASABLE_CATEGORY_COL.to_string()];
+                group_by.extend(series);
+
+                let mut filters = FilterChain::new();
+                filters.add_one(Filter::Or(matches));
+
+                Ok(BasableQuery {
+                    table,
+                    operation,
+                    params,
+                    filters,
+                    group_by: Some(group_by),
+                    order_by: order_by(&value),
+                    limit,
+                    ..Default::default()
+                })
+            }
         }
     }
-}
\ No newline at end of file
+}
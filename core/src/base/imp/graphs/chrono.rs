@@ -1,51 +1,115 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use ::chrono::{
+    DateTime, Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+};
+use axum::http::StatusCode;
 
-use crate::{base::query::{
-    filter::{Filter, FilterChain, FilterCondition, FilterOperator},
-    BasableQuery, QueryOperation, QueryOrder,
-}, globals::{BASABLE_CHRONO_XCOL, BASABLE_CHRONO_YCOL}};
+use crate::{
+    base::{
+        imp::{
+            db::DB,
+            graphs::{
+                aggregate::GraphAggregate,
+                series::{SeriesOpts, SeriesSplit},
+                AnalysisResult, AnalysisResults, AnalysisValue,
+            },
+        },
+        query::{
+            filter::{Filter, FilterChain, FilterCondition, FilterOperator},
+            BasableQuery, QueryOperation, QueryOrder,
+        },
+        AppError, BasableError,
+    },
+    globals::{BASABLE_CHRONO_XCOL, BASABLE_CHRONO_YCOL, BASABLE_SERIES_COL},
+};
 
-#[derive(Clone, EnumIter)]
+/// The buckets of a chrono graph. Period buckets are keyed on the whole period, and labelled
+/// with its start: a `YYYY-MM-DD` date, or a `YYYY-MM-DD HH:MM:SS` timestamp for buckets
+/// shorter than a day.
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) enum ChronoAnalysisBasis {
+    Hourly,
     Daily,
+    /// ISO weeks, starting on Monday.
+    Weekly,
     Monthly,
+    Quarterly,
     Yearly,
+    /// Day of the week, from 1 (Monday) to 7 (Sunday), regardless of the date.
+    DayOfWeek,
+    /// Hour of the day, from 0 to 23, regardless of the date.
+    HourOfDay,
+    /// Buckets of N minutes, counted from the Unix epoch.
+    Minutes(u32),
+    /// Buckets of N days, counted from the Unix epoch.
+    Days(u32),
 }
 
-impl From<ChronoAnalysisBasis> for String {
-    fn from(value: ChronoAnalysisBasis) -> Self {
-        let basis = match value {
-            ChronoAnalysisBasis::Daily => "Date",
-            ChronoAnalysisBasis::Monthly => "Month",
-            ChronoAnalysisBasis::Yearly => "Year",
-        };
+impl ChronoAnalysisBasis {
+    /// Whether buckets are labelled with a timestamp rather than a date.
+    pub fn has_time(&self) -> bool {
+        matches!(
+            self,
+            ChronoAnalysisBasis::Hourly | ChronoAnalysisBasis::Minutes(_)
+        )
+    }
 
-        basis.to_string()
+    /// Whether buckets are a part of the day or week rather than a period.
+    pub fn is_cyclic(&self) -> bool {
+        matches!(
+            self,
+            ChronoAnalysisBasis::DayOfWeek | ChronoAnalysisBasis::HourOfDay
+        )
     }
 }
 
 impl TryFrom<String> for ChronoAnalysisBasis {
     type Error = String;
 
+    /// Parses bases named after their [`Display`] form, such as `Month` or `Minutes:15`.
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        for b in ChronoAnalysisBasis::iter() {
-            let test = String::from(b.clone());
-            if value == test {
-                return Ok(b);
+        let basis = match value.as_str() {
+            "Hour" => ChronoAnalysisBasis::Hourly,
+            "Date" => ChronoAnalysisBasis::Daily,
+            "Week" => ChronoAnalysisBasis::Weekly,
+            "Month" => ChronoAnalysisBasis::Monthly,
+            "Quarter" => ChronoAnalysisBasis::Quarterly,
+            "Year" => ChronoAnalysisBasis::Yearly,
+            "DayOfWeek" => ChronoAnalysisBasis::DayOfWeek,
+            "HourOfDay" => ChronoAnalysisBasis::HourOfDay,
+            _ => {
+                let interval = value.split_once(':').and_then(|(unit, n)| {
+                    let n = n.parse::<u32>().ok().filter(|n| *n > 0)?;
+                    match unit {
+                        "Minutes" => Some(ChronoAnalysisBasis::Minutes(n)),
+                        "Days" => Some(ChronoAnalysisBasis::Days(n)),
+                        _ => None,
+                    }
+                });
+
+                return interval.ok_or_else(|| "error parsing analysis basis".to_string());
             }
-        }
+        };
 
-        Err("error parsing analysis basis".to_string())
+        Ok(basis)
     }
 }
 
 impl Display for ChronoAnalysisBasis {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let basis: String = self.clone().into();
-        write!(f, "{}", basis)
+        match self {
+            ChronoAnalysisBasis::Hourly => write!(f, "Hour"),
+            ChronoAnalysisBasis::Daily => write!(f, "Date"),
+            ChronoAnalysisBasis::Weekly => write!(f, "Week"),
+            ChronoAnalysisBasis::Monthly => write!(f, "Month"),
+            ChronoAnalysisBasis::Quarterly => write!(f, "Quarter"),
+            ChronoAnalysisBasis::Yearly => write!(f, "Year"),
+            ChronoAnalysisBasis::DayOfWeek => write!(f, "DayOfWeek"),
+            ChronoAnalysisBasis::HourOfDay => write!(f, "HourOfDay"),
+            ChronoAnalysisBasis::Minutes(n) => write!(f, "Minutes:{n}"),
+            ChronoAnalysisBasis::Days(n) => write!(f, "Days:{n}"),
+        }
     }
 }
 
@@ -66,7 +130,7 @@ impl TryFrom<String> for ChronoAnalysisRange {
     fn try_from(value: String) -> Result<Self, Self::Error> {
         let spl: Vec<&str> = value.split("range").collect();
         if spl.len() == 2 {
-            let start = spl.get(0).unwrap();
+            let start = spl.first().unwrap();
             let end = spl.get(1).unwrap();
 
             let range = ChronoAnalysisRange(start.trim().to_string(), end.trim().to_string());
@@ -77,33 +141,337 @@ impl TryFrom<String> for ChronoAnalysisRange {
     }
 }
 
+/// How [`ChronoAnalysisOpts::gaps`] fills the buckets without rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ChronoGapFill {
+    Zero,
+    Null,
+}
+
+impl TryFrom<&String> for ChronoGapFill {
+    type Error = String;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "zero" => Ok(ChronoGapFill::Zero),
+            "null" => Ok(ChronoGapFill::Null),
+            _ => Err("error parsing gap fill".to_string()),
+        }
+    }
+}
+
+/// The time zone chrono graphs are bucketed in.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ChronoTimeZone {
+    /// Offset from UTC, in minutes.
+    Offset(i32),
+    /// A time zone of the IANA database, such as `Europe/Paris`.
+    Named(String),
+}
+
+impl TryFrom<String> for ChronoTimeZone {
+    type Error = String;
+
+    /// Parses `UTC`, offsets such as `+02:00` or `-05:30`, and IANA time zone names.
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let err = || "error parsing time zone".to_string();
+
+        if value == "UTC" || value == "Z" {
+            return Ok(ChronoTimeZone::Offset(0));
+        }
+
+        if let Some(offset) = value.strip_prefix(['+', '-']) {
+            let (hours, minutes) = offset.split_once(':').ok_or_else(err)?;
+            if hours.len() != 2 || minutes.len() != 2 {
+                return Err(err());
+            }
+
+            let hours = hours.parse::<i32>().map_err(|_| err())?;
+            let minutes = minutes.parse::<i32>().map_err(|_| err())?;
+            if hours > 14 || minutes > 59 {
+                return Err(err());
+            }
+
+            let offset = hours * 60 + minutes;
+            let sign = if value.starts_with('-') { -1 } else { 1 };
+            return Ok(ChronoTimeZone::Offset(sign * offset));
+        }
+
+        // Names are inlined in SQL, so only the characters of IANA names are accepted.
+        let is_name = value.len() <= 64
+            && value.starts_with(|c: char| c.is_ascii_alphabetic())
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "/_+-".contains(c));
+
+        match is_name {
+            true => Ok(ChronoTimeZone::Named(value)),
+            false => Err(err()),
+        }
+    }
+}
+
+impl Display for ChronoTimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChronoTimeZone::Offset(offset) => {
+                let sign = if *offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+            ChronoTimeZone::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Number of buckets [`ChronoGaps`] may fill, so that a small basis over a long range does
+/// not produce a huge graph.
+const MAX_CHRONO_BUCKETS: usize = 10_000;
+
+/// The buckets of a chrono graph, used to fill the buckets without rows. See
+/// [`ChronoAnalysisOpts::gaps`].
+pub(crate) struct ChronoGaps(Option<(Vec<AnalysisValue>, ChronoGapFill)>);
+
+impl ChronoGaps {
+    /// Adds the missing buckets to `results`, which are labelled as described by
+    /// [`ChronoAnalysisBasis`].
+    pub fn fill(&self, results: AnalysisResults) -> AnalysisResults {
+        let Some((buckets, fill)) = &self.0 else {
+            return results;
+        };
+
+        let mut results: HashMap<String, AnalysisResult> =
+            results.into_iter().map(|r| (r.0.to_string(), r)).collect();
+
+        buckets
+            .iter()
+            .cloned()
+            .map(|x| match results.remove(&x.to_string()) {
+                Some(result) => result,
+                None => {
+                    let y = match fill {
+                        ChronoGapFill::Zero => AnalysisValue::UInt(0),
+                        ChronoGapFill::Null => AnalysisValue::NULL,
+                    };
+                    AnalysisResult::new(x, y)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reads a bound of a [`ChronoAnalysisRange`], as a date or a timestamp.
+fn parse_range_bound(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
 pub(crate) struct ChronoAnalysisOpts {
     pub table: String,
     pub chrono_col: String,
     pub basis: ChronoAnalysisBasis,
     pub range: ChronoAnalysisRange,
+
+    /// Fills the buckets of `range` without rows when set, instead of leaving them out.
+    pub gap_fill: Option<ChronoGapFill>,
+
+    /// Time zone `chrono_col` is converted to before it is bucketed. The range is in this
+    /// time zone too. Values are bucketed as the data source reads them when unset.
+    pub time_zone: Option<ChronoTimeZone>,
+
+    /// The value of each bucket.
+    pub aggregate: GraphAggregate,
+
+    /// Splits the graph into series, each with its buckets.
+    pub series: Option<SeriesOpts>,
 }
 
-impl From<ChronoAnalysisOpts> for BasableQuery {
-    fn from(value: ChronoAnalysisOpts) -> Self {
+impl ChronoAnalysisOpts {
+    pub fn from_query_params(params: HashMap<String, String>) -> Result<Self, AppError> {
+        let err = |msg: String| AppError::new(StatusCode::EXPECTATION_FAILED, &msg);
+
+        let (Some(table), Some(column), Some(basis), Some(range)) = (
+            params.get("table"),
+            params.get("column"),
+            params.get("basis"),
+            params.get("range"),
+        ) else {
+            return Err(err("Missing query parameters".to_string()));
+        };
+
+        let gap_fill = params
+            .get("fill")
+            .map(ChronoGapFill::try_from)
+            .transpose()
+            .map_err(err)?;
+
+        let time_zone = params
+            .get("time_zone")
+            .map(|tz| ChronoTimeZone::try_from(tz.to_owned()))
+            .transpose()
+            .map_err(err)?;
+
+        Ok(ChronoAnalysisOpts {
+            table: table.to_owned(),
+            chrono_col: column.to_owned(),
+            basis: basis.to_owned().try_into().map_err(err)?,
+            range: range.to_owned().try_into().map_err(err)?,
+            gap_fill,
+            time_zone,
+            aggregate: GraphAggregate::from_query_params(&params)?,
+            series: SeriesOpts::from_query_params(&params)?,
+        })
+    }
+
+    /// How the results are split into series.
+    pub fn split(&self) -> SeriesSplit {
+        SeriesSplit::new(self.series.as_ref(), None)
+    }
+
+    /// The buckets of the analysis range, when [`ChronoAnalysisOpts::gap_fill`] is set. The
+    /// range must then be made of dates or timestamps.
+    pub fn gaps(&self) -> Result<ChronoGaps, BasableError> {
+        let Some(fill) = self.gap_fill else {
+            return Ok(ChronoGaps(None));
+        };
+
+        let buckets = match self.basis {
+            ChronoAnalysisBasis::DayOfWeek => (1..=7).map(AnalysisValue::UInt).collect(),
+            ChronoAnalysisBasis::HourOfDay => (0..24).map(AnalysisValue::UInt).collect(),
+            _ => self.period_buckets()?,
+        };
+
+        Ok(ChronoGaps(Some((buckets, fill))))
+    }
+
+    fn period_buckets(&self) -> Result<Vec<AnalysisValue>, BasableError> {
+        let bound = |value: &str| {
+            parse_range_bound(value).ok_or_else(|| {
+                BasableError::Input(format!("'{value}' is not a date, so gaps can't be filled."))
+            })
+        };
+        let start = bound(self.range.start())?;
+        let end = bound(self.range.end())?;
+        if start > end {
+            return Err(BasableError::Input(
+                "The range must start before it ends.".to_string(),
+            ));
+        }
+
+        let epoch = DateTime::UNIX_EPOCH.date_naive();
+        let minutes = |n: u32| TimeDelta::minutes(n.into());
+        let days = |n: u32| TimeDelta::days(n.into());
+
+        // Start of the first bucket.
+        let date = start.date();
+        let first = match self.basis {
+            ChronoAnalysisBasis::Hourly => date.and_hms_opt(start.hour(), 0, 0),
+            ChronoAnalysisBasis::Minutes(n) => {
+                let length = i64::from(n) * 60;
+                let seconds = start.and_utc().timestamp().div_euclid(length) * length;
+                DateTime::from_timestamp(seconds, 0).map(|d| d.naive_utc())
+            }
+            _ => {
+                let first_day = match self.basis {
+                    ChronoAnalysisBasis::Weekly => {
+                        Some(date - days(date.weekday().num_days_from_monday()))
+                    }
+                    ChronoAnalysisBasis::Monthly => date.with_day(1),
+                    ChronoAnalysisBasis::Quarterly => {
+                        NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1)
+                    }
+                    ChronoAnalysisBasis::Yearly => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+                    ChronoAnalysisBasis::Days(n) => {
+                        let length = i64::from(n);
+                        let offset = (date - epoch).num_days().div_euclid(length) * length;
+                        Some(epoch + TimeDelta::days(offset))
+                    }
+                    _ => Some(date),
+                };
+
+                first_day.map(|d| d.and_time(NaiveTime::MIN))
+            }
+        };
+
+        let mut buckets = Vec::new();
+        let mut bucket = first;
+        while let Some(current) = bucket.filter(|b| *b <= end) {
+            if buckets.len() == MAX_CHRONO_BUCKETS {
+                return Err(BasableError::Input(format!(
+                    "Graphs can't fill more than {MAX_CHRONO_BUCKETS} buckets, use a longer basis."
+                )));
+            }
+
+            let label = match self.basis.has_time() {
+                true => current.format("%Y-%m-%d %H:%M:%S"),
+                false => current.format("%Y-%m-%d"),
+            };
+            buckets.push(AnalysisValue::Text(label.to_string()));
+
+            bucket = match self.basis {
+                ChronoAnalysisBasis::Hourly => current.checked_add_signed(TimeDelta::hours(1)),
+                ChronoAnalysisBasis::Weekly => current.checked_add_signed(days(7)),
+                ChronoAnalysisBasis::Monthly => current.checked_add_months(Months::new(1)),
+                ChronoAnalysisBasis::Quarterly => current.checked_add_months(Months::new(3)),
+                ChronoAnalysisBasis::Yearly => current.checked_add_months(Months::new(12)),
+                ChronoAnalysisBasis::Minutes(n) => current.checked_add_signed(minutes(n)),
+                ChronoAnalysisBasis::Days(n) => current.checked_add_signed(days(n)),
+                _ => current.checked_add_signed(days(1)),
+            };
+        }
+
+        Ok(buckets)
+    }
+
+    /// Builds the [`BasableQuery`] for this analysis. `db` resolves the table and column, and
+    /// provides the SQL expression used to group `chrono_col` by the analysis
+    /// [`ChronoAnalysisBasis`].
+    pub fn into_query<D: DB + ?Sized>(self, db: &D) -> Result<BasableQuery, BasableError> {
         let ChronoAnalysisOpts {
             table,
             chrono_col,
             basis,
             range,
-        } = value;
+            time_zone,
+            aggregate,
+            series,
+            ..
+        } = self;
 
+        let tbl = db.resolve_table(&table)?;
+        let mut chrono_col = db.quote_ident(&tbl.resolve_columns(&[&chrono_col])?[0]);
+        let table = db.quote_table(tbl.name());
+        let value = aggregate.parse(db, tbl, None)?;
+
+        if let Some(time_zone) = time_zone {
+            chrono_col = db.parse_time_zone(&chrono_col, &time_zone)?;
+        }
+
+        let basis_expr = db.parse_chrono_basis(&basis, &chrono_col);
 
         // create query operation type
-        let selection_columns = Some(vec![
-            format!("{basis}({chrono_col}) AS {BASABLE_CHRONO_XCOL}"),
-            format!("COUNT(*) AS {BASABLE_CHRONO_YCOL}"),
-        ]);
+        let mut selection_columns = vec![
+            format!("{basis_expr} AS {BASABLE_CHRONO_XCOL}"),
+            format!("{value} AS {BASABLE_CHRONO_YCOL}"),
+        ];
+        let mut group_columns = vec![basis_expr];
 
-        let operation = QueryOperation::SelectData(selection_columns);
+        if let Some(series) = series {
+            let series_expr = series.parse(db, tbl, None)?;
+            selection_columns.push(format!("{series_expr} AS {BASABLE_SERIES_COL}"));
+            group_columns.push(series_expr);
+        }
+
+        let operation = QueryOperation::SelectData(Some(selection_columns));
 
         // create query filters
-        let filter = Filter::BASE(FilterCondition {
+        let filter = Filter::Condition(FilterCondition {
             column: chrono_col.clone(),
             operator: FilterOperator::Btw(range.start().to_string(), range.end().to_string()),
         });
@@ -111,19 +479,17 @@ impl From<ChronoAnalysisOpts> for BasableQuery {
         let mut filters = FilterChain::new();
         filters.add_one(filter);
 
-        // creating grouping
-        let group_columns = vec![format!("{basis}({chrono_col})")];
         let group_by = Some(group_columns);
 
         let order_by = Some(QueryOrder::ASC(BASABLE_CHRONO_XCOL.to_string()));
 
-        BasableQuery {
+        Ok(BasableQuery {
             table,
             filters,
             operation,
             group_by,
             order_by,
             ..Default::default()
-        }
+        })
     }
 }
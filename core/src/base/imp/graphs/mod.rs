@@ -1,22 +1,32 @@
-use std::fmt::{Debug, Display};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+};
 
 use category::CategoryGraphOpts;
 use chrono::ChronoAnalysisOpts;
-use mysql::Value as MysqlValue;
 use serde::{ser::SerializeTuple, Serialize};
 use time::Date;
 use trend::TrendGraphOpts;
 
-use crate::base::AppError;
-
-use super::db::DBError;
+use crate::base::{
+    data::{
+        row::BasableRow,
+        value::{BasableValue, FromBasableValue},
+    },
+    AppError, BasableError,
+};
 
+pub(crate) mod aggregate;
 pub(crate) mod category;
 pub(crate) mod chrono;
+pub(crate) mod series;
 pub(crate) mod trend;
 
 pub(crate) type AnalysisResults = Vec<AnalysisResult>;
 
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) enum AnalysisValue {
     NULL,
     UInt(usize),
@@ -25,6 +35,8 @@ pub(crate) enum AnalysisValue {
     Date(Date),
     Float(f32),
     Double(f64),
+    /// Exact numeric value, kept as text so that no precision is lost.
+    Decimal(String),
 }
 
 impl Serialize for AnalysisValue {
@@ -42,6 +54,7 @@ impl Serialize for AnalysisValue {
             AnalysisValue::Date(date) => s.serialize_element(&date.to_string())?,
             AnalysisValue::Float(float) => s.serialize_element(float)?,
             AnalysisValue::Double(double) => s.serialize_element(double)?,
+            AnalysisValue::Decimal(decimal) => s.serialize_element(decimal)?,
         }
 
         s.end()
@@ -58,26 +71,42 @@ impl Display for AnalysisValue {
             AnalysisValue::Date(value) => value.to_string(),
             AnalysisValue::Float(value) => value.to_string(),
             AnalysisValue::Double(value) => value.to_string(),
+            AnalysisValue::Decimal(value) => value.to_string(),
         };
 
         write!(f, "{}", value)
     }
 }
 
-impl From<MysqlValue> for AnalysisValue {
-    fn from(value: MysqlValue) -> Self {
+impl From<BasableValue> for AnalysisValue {
+    fn from(value: BasableValue) -> Self {
         match value {
-            MysqlValue::NULL => AnalysisValue::NULL,
-            MysqlValue::Bytes(v) => AnalysisValue::Text(String::from_utf8(v).unwrap()),
-            MysqlValue::UInt(v) => AnalysisValue::UInt(usize::try_from(v).unwrap()),
-            MysqlValue::Int(v) => AnalysisValue::Int(isize::try_from(v).unwrap()),
-            MysqlValue::Float(v) => AnalysisValue::Float(v),
-            MysqlValue::Double(v) => AnalysisValue::Double(v),
-            _ => todo!(),
+            BasableValue::Null => AnalysisValue::NULL,
+            BasableValue::Bool(v) => AnalysisValue::UInt(v.into()),
+            BasableValue::UInt(v) => AnalysisValue::UInt(usize::try_from(v).unwrap()),
+            BasableValue::Int(v) => AnalysisValue::Int(isize::try_from(v).unwrap()),
+            BasableValue::Float(v) => AnalysisValue::Float(v),
+            BasableValue::Double(v) => AnalysisValue::Double(v),
+            BasableValue::Decimal(v) => AnalysisValue::Decimal(v),
+            BasableValue::Text(v) => AnalysisValue::Text(v),
+            BasableValue::Date(_, _, _, 0, 0, 0, 0) => match Date::from_value(&value) {
+                Some(date) => AnalysisValue::Date(date),
+                None => AnalysisValue::Text(value.to_string()),
+            },
+            v => AnalysisValue::Text(v.to_string()),
         }
     }
 }
 
+impl AnalysisValue {
+    /// Reads the value of `col` from `row`. A missing column is read as [`AnalysisValue::NULL`].
+    pub fn from_row(row: &BasableRow, col: &str) -> Self {
+        row.value(col)
+            .cloned()
+            .map_or(AnalysisValue::NULL, AnalysisValue::from)
+    }
+}
+
 #[derive(Serialize)]
 pub(crate) struct AnalysisResult(AnalysisValue, AnalysisValue);
 impl AnalysisResult {
@@ -92,31 +121,68 @@ impl Debug for AnalysisResult {
     }
 }
 
+/// The results of a graph, split by series when the graph has a
+/// [`SeriesOpts`](series::SeriesOpts).
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum AnalysisGraph {
+    Single(AnalysisResults),
+    Series(BTreeMap<String, AnalysisResults>),
+}
+
+impl AnalysisGraph {
+    /// Applies `f` to the results of each series.
+    pub fn map(self, mut f: impl FnMut(AnalysisResults) -> AnalysisResults) -> Self {
+        match self {
+            AnalysisGraph::Single(results) => AnalysisGraph::Single(f(results)),
+            AnalysisGraph::Series(series) => AnalysisGraph::Series(
+                series
+                    .into_iter()
+                    .map(|(name, results)| (name, f(results)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
 pub(crate) trait VisualizeDB {
-    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisResults, DBError>;
-    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisResults, DBError>;
-    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisResults, AppError>;
+    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisGraph, BasableError>;
+    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisGraph, BasableError>;
+    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisGraph, AppError>;
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use axum::http::StatusCode;
+
     use crate::{
         base::{
+            data::value::BasableValue,
             imp::graphs::{
                 category::{CategoryGraphOpts, CategoryGraphType},
                 chrono::{ChronoAnalysisBasis, ChronoAnalysisRange},
                 trend::CrossOptions,
             },
-            AppError,
+            AppError, BasableError,
         },
-        tests::common::create_test_db,
+        tests::common::{create_test_db, get_test_db_source},
     };
 
     use super::{
         trend::{TrendGraphOpts, TrendGraphOrder, TrendGraphType},
-        ChronoAnalysisOpts,
+        AnalysisGraph, AnalysisResults, AnalysisValue, ChronoAnalysisOpts,
     };
 
+    /// Results of a graph without series.
+    fn results(graph: AnalysisGraph) -> AnalysisResults {
+        match graph {
+            AnalysisGraph::Single(results) => results,
+            AnalysisGraph::Series(_) => panic!("unexpected series"),
+        }
+    }
+
     #[test]
     fn test_chrono_graph() -> Result<(), AppError> {
         let db = create_test_db()?;
@@ -125,10 +191,175 @@ mod tests {
             chrono_col: "release_date".to_string(),
             basis: ChronoAnalysisBasis::Monthly,
             range: ChronoAnalysisRange("2010-09-01".to_string(), "2010-11-30".to_string()),
+            gap_fill: None,
+            time_zone: None,
+            aggregate: Default::default(),
+            series: None,
         });
 
         assert!(graph.is_ok());
 
+        // Buckets and their counts, as strings.
+        let chrono = |params: &[(&str, &str)]| -> Result<Vec<(String, String)>, AppError> {
+            let defaults = [
+                ("table", "vgchartz"),
+                ("column", "release_date"),
+                ("range", "2010-01-01 range 2011-12-31"),
+            ];
+            let params = defaults
+                .iter()
+                .chain(params)
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let graph = db.chrono_graph(ChronoAnalysisOpts::from_query_params(params)?)?;
+
+            Ok(results(graph)
+                .iter()
+                .map(|r| (r.0.to_string(), r.1.to_string()))
+                .collect())
+        };
+        let graph = |basis: &str| chrono(&[("basis", basis)]);
+        let expected = |buckets: &[(&str, &str)]| -> Vec<(String, String)> {
+            buckets
+                .iter()
+                .map(|(x, y)| (x.to_string(), y.to_string()))
+                .collect()
+        };
+
+        // November 2010 and November 2011 are different buckets.
+        assert_eq!(
+            graph("Month")?,
+            expected(&[
+                ("2010-05-01", "1"),
+                ("2010-09-01", "2"),
+                ("2010-10-01", "1"),
+                ("2010-11-01", "2"),
+                ("2011-11-01", "2"),
+            ])
+        );
+        assert_eq!(
+            graph("Quarter")?,
+            expected(&[
+                ("2010-04-01", "1"),
+                ("2010-07-01", "2"),
+                ("2010-10-01", "3"),
+                ("2011-10-01", "2"),
+            ])
+        );
+        assert_eq!(
+            graph("Year")?,
+            expected(&[("2010-01-01", "6"), ("2011-01-01", "2")])
+        );
+        assert_eq!(
+            graph("Week")?[6..],
+            expected(&[("2011-11-07", "1"), ("2011-11-14", "1")])
+        );
+        assert_eq!(
+            graph("Hour")?[0],
+            expected(&[("2010-05-18 00:00:00", "1")])[0]
+        );
+        assert_eq!(
+            graph("Minutes:15")?[0],
+            expected(&[("2010-05-18 00:00:00", "1")])[0]
+        );
+        assert_eq!(
+            graph("Days:30")?,
+            expected(&[
+                ("2010-05-01", "1"),
+                ("2010-08-29", "1"),
+                ("2010-09-28", "2"),
+                ("2010-10-28", "2"),
+                ("2011-10-23", "2"),
+            ])
+        );
+        assert_eq!(
+            graph("DayOfWeek")?,
+            expected(&[("2", "5"), ("3", "1"), ("5", "2")])
+        );
+        assert_eq!(graph("HourOfDay")?, expected(&[("0", "8")]));
+
+        for invalid in ["Fortnight", "Minutes:0", "Days:", "Weeks:2"] {
+            assert!(graph(invalid).is_err());
+        }
+
+        let sales = chrono(&[
+            ("basis", "Year"),
+            ("aggregate", "sum"),
+            ("value_column", "total_sales"),
+        ])?;
+        let sales: Vec<f64> = sales
+            .iter()
+            .map(|(_, v)| (v.parse::<f64>().unwrap() * 100.0).round() / 100.0)
+            .collect();
+        assert_eq!(sales, [50.33, 8.84]);
+
+        let decimal = BasableValue::Decimal("12345678901234567.89".to_string());
+        assert_eq!(
+            AnalysisValue::from(decimal).to_string(),
+            "12345678901234567.89"
+        );
+
+        let range = ("range", "2010-09-01 range 2011-01-31");
+        assert_eq!(
+            chrono(&[("basis", "Month"), ("fill", "zero"), range])?,
+            expected(&[
+                ("2010-09-01", "2"),
+                ("2010-10-01", "1"),
+                ("2010-11-01", "2"),
+                ("2010-12-01", "0"),
+                ("2011-01-01", "0"),
+            ])
+        );
+        let filled = chrono(&[("basis", "Week"), ("fill", "null"), range])?;
+        assert_eq!(filled.len(), 23);
+        assert_eq!(filled[0], expected(&[("2010-08-30", "null")])[0]);
+        assert_eq!(filled[2], expected(&[("2010-09-13", "1")])[0]);
+
+        let filled = chrono(&[("basis", "DayOfWeek"), ("fill", "zero")])?;
+        assert_eq!(filled.len(), 7);
+        assert_eq!(filled[0], expected(&[("1", "0")])[0]);
+
+        // Dates are read as midnight, which is the previous day at UTC-8.
+        let range = ("range", "2010-05-01 range 2010-05-31");
+        assert_eq!(
+            chrono(&[("basis", "Date"), ("time_zone", "-08:00"), range])?,
+            expected(&[("2010-05-17", "1")])
+        );
+        assert_eq!(
+            chrono(&[("basis", "Date"), ("time_zone", "UTC"), range])?,
+            expected(&[("2010-05-18", "1")])
+        );
+
+        // SQLite can't convert dates to named time zones, and MySQL needs its time zone tables.
+        let named = chrono(&[
+            ("basis", "Date"),
+            ("time_zone", "America/Los_Angeles"),
+            range,
+        ]);
+        match get_test_db_source().as_str() {
+            "sqlite" => assert_eq!(named.err().map(|err| err.0), Some(StatusCode::BAD_REQUEST)),
+            "postgres" => assert_eq!(named?, expected(&[("2010-05-17", "1")])),
+            _ => {}
+        }
+
+        for invalid in [
+            &[("basis", "Month"), ("fill", "zeros")][..],
+            &[("basis", "Month"), ("time_zone", "+25:00")],
+            &[("basis", "Month"), ("time_zone", "UTC'; --")],
+        ] {
+            assert!(chrono(invalid).is_err());
+        }
+
+        // Ranges that can't be filled are the user's error.
+        for (basis, range) in [
+            ("Minutes:1", "2010-01-01 range 2011-12-31"),
+            ("Month", "soon range later"),
+            ("Month", "2011-12-31 range 2010-01-01"),
+        ] {
+            let graph = chrono(&[("basis", basis), ("fill", "zero"), ("range", range)]);
+            assert_eq!(graph.err().map(|err| err.0), Some(StatusCode::BAD_REQUEST));
+        }
+
         Ok(())
     }
 
@@ -146,6 +377,7 @@ mod tests {
                 foreign_table: "encounters".to_string(),
                 target_col: "Id".to_string(),
             }),
+            series: None,
         };
 
         let graph = db.trend_graph(opts);
@@ -155,19 +387,282 @@ mod tests {
     }
 
     #[test]
-    fn test_category_graph() -> Result<(), AppError> {
+    fn test_series_graph() -> Result<(), AppError> {
         let db = create_test_db()?;
+        let params = |params: &[(&str, &str)]| -> HashMap<String, String> {
+            params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        // Results of each series, as strings.
+        let series = |graph: AnalysisGraph| -> BTreeMap<String, Vec<(String, String)>> {
+            let AnalysisGraph::Series(series) = graph else {
+                panic!("missing series");
+            };
+
+            series
+                .into_iter()
+                .map(|(name, results)| {
+                    let results = results
+                        .iter()
+                        .map(|r| (r.0.to_string(), r.1.to_string()))
+                        .collect();
+                    (name, results)
+                })
+                .collect()
+        };
+        let expected = |results: &[(&str, &str)]| -> Vec<(String, String)> {
+            results
+                .iter()
+                .map(|(x, y)| (x.to_string(), y.to_string()))
+                .collect()
+        };
+
+        // PS3 and X360 have the most games, and the PC game is in the "Other" series.
+        let chrono = db.chrono_graph(ChronoAnalysisOpts::from_query_params(params(&[
+            ("table", "vgchartz"),
+            ("column", "release_date"),
+            ("basis", "Year"),
+            ("range", "2010-01-01 range 2011-12-31"),
+            ("fill", "zero"),
+            ("series_by", "console"),
+            ("series_limit", "2"),
+        ]))?)?;
+        let chrono = series(chrono);
+        assert_eq!(chrono.keys().collect::<Vec<_>>(), ["Other", "PS3", "X360"]);
+        assert_eq!(
+            chrono["Other"],
+            expected(&[("2010-01-01", "0"), ("2011-01-01", "1")])
+        );
+        assert_eq!(
+            chrono["PS3"],
+            expected(&[("2010-01-01", "4"), ("2011-01-01", "0")])
+        );
+        assert_eq!(
+            chrono["X360"],
+            expected(&[("2010-01-01", "2"), ("2011-01-01", "1")])
+        );
+
+        // Each series has up to `limit` categories.
+        let category = db.category_graph(CategoryGraphOpts::from_query_params(params(&[
+            ("table", "vgchartz"),
+            ("column", "console"),
+            ("series_by", "publisher"),
+            ("series_limit", "1"),
+            ("limit", "1"),
+        ]))?)?;
+        let category = series(category);
+        assert_eq!(category["Rockstar Games"], expected(&[("2", "PS3")]));
+        assert_eq!(category["Other"].len(), 1);
+        assert_eq!(category["Other"][0].0, "3");
+
+        let trend = db.trend_graph(TrendGraphOpts::from_query_params(params(&[
+            ("table", "patients"),
+            ("analysis_type", "cross"),
+            ("xcol", "FIRST"),
+            ("ycol", "PATIENT"),
+            ("foreign_table", "encounters"),
+            ("target_column", "Id"),
+            ("order", "ASC"),
+            ("series_by", "GENDER"),
+        ]))?)?;
+        let trend = series(trend);
+        assert_eq!(trend["F"], expected(&[("Alva", "1")]));
+        assert_eq!(trend["M"], expected(&[("Jimmie", "1"), ("Jacinto", "3")]));
+
+        let trend = db.trend_graph(TrendGraphOpts::from_query_params(params(&[
+            ("table", "vgchartz"),
+            ("analysis_type", "intra"),
+            ("xcol", "title"),
+            ("ycol", "critic_score"),
+            ("limit", "1"),
+            ("series_by", "console"),
+        ]))?)?;
+        let trend = series(trend);
+        assert_eq!(trend["PS3"], expected(&[("Red Dead Redemption", "9.5")]));
+        assert_eq!(trend["X360"], expected(&[("Grand Theft Auto IV", "9.9")]));
+
+        for invalid in [
+            &[("series_limit", "2")][..],
+            &[("series_by", "console"), ("series_limit", "0")],
+        ] {
+            let mut graph_params = vec![("table", "vgchartz"), ("column", "console")];
+            graph_params.extend_from_slice(invalid);
+            assert!(CategoryGraphOpts::from_query_params(params(&graph_params)).is_err());
+        }
+
+        let unknown = CategoryGraphOpts::from_query_params(params(&[
+            ("table", "vgchartz"),
+            ("column", "console"),
+            ("series_by", "platform"),
+        ]))?;
+        let graph = db.category_graph(unknown);
+        assert_eq!(graph.err().map(|err| err.0), Some(StatusCode::BAD_REQUEST));
+
+        Ok(())
+    }
 
-        let opts = CategoryGraphOpts {
+    #[test]
+    fn test_graph_unknown_identifier() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let graph = db.chrono_graph(ChronoAnalysisOpts {
             table: "vgchartz".to_string(),
+            chrono_col: "release_date) OR (1 = 1".to_string(),
+            basis: ChronoAnalysisBasis::Monthly,
+            range: ChronoAnalysisRange("2010-09-01".to_string(), "2010-11-30".to_string()),
+            gap_fill: None,
+            time_zone: None,
+            aggregate: Default::default(),
+            series: None,
+        });
+
+        assert!(matches!(graph, Err(BasableError::Identifier(_))));
+
+        let graph = db.category_graph(CategoryGraphOpts {
+            table: "unknown_table".to_string(),
             graph_type: CategoryGraphType::Simple,
             target_col: "publisher".to_string(),
             limit: 20,
+            join: None,
+            categories: None,
+            aggregate: Default::default(),
+            series: None,
+        });
+
+        assert_eq!(graph.err().map(|err| err.0), Some(StatusCode::BAD_REQUEST));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_category_graph() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        // Categories and their counts, as strings.
+        let graph = |params: &[(&str, &str)]| -> Result<Vec<(String, String)>, AppError> {
+            let params = params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let graph = db.category_graph(CategoryGraphOpts::from_query_params(params)?)?;
+
+            Ok(results(graph)
+                .iter()
+                .map(|r| (r.1.to_string(), r.0.to_string()))
+                .collect())
         };
 
-        let graph = db.category_graph(opts);
+        let simple = graph(&[("table", "vgchartz"), ("column", "publisher")])?;
+        assert_eq!(simple[0], ("Rockstar Games".to_string(), "3".to_string()));
 
-        assert!(graph.is_ok());
+        let many_to_many = graph(&[
+            ("table", "patients"),
+            ("column", "FIRST"),
+            ("graph_type", "many_to_many"),
+            ("join_table", "encounters"),
+            ("join_column", "PATIENT"),
+            ("category_key", "Id"),
+            ("limit", "2"),
+        ])?;
+        assert_eq!(many_to_many.len(), 2);
+        assert_eq!(many_to_many[0], ("Jacinto".to_string(), "3".to_string()));
+
+        let manual = graph(&[
+            ("table", "vgchartz"),
+            ("column", "console"),
+            ("graph_type", "manual"),
+            (
+                "categories",
+                r#"[
+                    { "label": "Xbox", "op": "eq", "value": "X360" },
+                    { "label": "PlayStation", "op": "in", "value": ["PS2", "PS3"] }
+                ]"#,
+            ),
+        ])?;
+        let expected =
+            [("PlayStation", "5"), ("Xbox", "4")].map(|(l, c)| (l.to_string(), c.to_string()));
+        assert_eq!(manual, expected);
+
+        // Values are compared as numbers, since Postgres sums REAL columns as REAL.
+        let values = |graph: &[(String, String)]| -> Vec<(String, f64)> {
+            graph
+                .iter()
+                .map(|(c, v)| {
+                    (
+                        c.clone(),
+                        (v.parse::<f64>().unwrap() * 100.0).round() / 100.0,
+                    )
+                })
+                .collect()
+        };
+
+        let sales = graph(&[
+            ("table", "vgchartz"),
+            ("column", "publisher"),
+            ("aggregate", "sum"),
+            ("value_column", "total_sales"),
+            ("limit", "2"),
+        ])?;
+        let expected = [("Rockstar Games", 38.01), ("Activision", 14.74)];
+        assert_eq!(values(&sales), expected.map(|(c, v)| (c.to_string(), v)));
+
+        // Patients without encounters have no cost, and come last.
+        let costs = graph(&[
+            ("table", "patients"),
+            ("column", "FIRST"),
+            ("graph_type", "many_to_many"),
+            ("join_table", "encounters"),
+            ("join_column", "PATIENT"),
+            ("category_key", "Id"),
+            ("aggregate", "max"),
+            ("value_column", "TOTAL_CLAIM_COST"),
+        ])?;
+        let expected = [("Jimmie", 1254.03), ("Alva", 142.58), ("Jacinto", 129.16)];
+        assert_eq!(
+            values(&costs[..3]),
+            expected.map(|(c, v)| (c.to_string(), v))
+        );
+        assert_eq!(costs[3], ("Gregorio".to_string(), "null".to_string()));
+
+        let publishers = graph(&[
+            ("table", "vgchartz"),
+            ("column", "console"),
+            ("aggregate", "count_distinct"),
+            ("value_column", "publisher"),
+        ])?;
+        assert_eq!(publishers[2], ("PC".to_string(), "1".to_string()));
+
+        // Only Postgres has percentiles.
+        let median = graph(&[
+            ("table", "encounters"),
+            ("column", "ENCOUNTERCLASS"),
+            ("aggregate", "median"),
+            ("value_column", "TOTAL_CLAIM_COST"),
+            ("limit", "1"),
+        ]);
+        match get_test_db_source().as_str() {
+            "postgres" => assert_eq!(values(&median?), [("emergency".to_string(), 1254.03)]),
+            _ => {
+                let AppError(code, msg) = median.unwrap_err();
+                assert_eq!(code, StatusCode::BAD_REQUEST);
+                assert_eq!(msg, "Percentiles are not supported by this data source.");
+            }
+        }
+
+        for invalid in [
+            &[("graph_type", "pie")][..],
+            &[("aggregate", "sum")],
+            &[("aggregate", "p100"), ("value_column", "LAST")],
+            &[("aggregate", "total"), ("value_column", "LAST")],
+            &[("graph_type", "manual")],
+            &[("graph_type", "manual"), ("categories", "[]")],
+            &[("graph_type", "many_to_many")],
+            &[("join_table", "encounters")],
+        ] {
+            let mut params = vec![("table", "patients"), ("column", "FIRST")];
+            params.extend_from_slice(invalid);
+            assert!(graph(&params).is_err());
+        }
 
         Ok(())
     }
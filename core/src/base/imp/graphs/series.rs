@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::http::StatusCode;
+
+use crate::{
+    base::{
+        data::row::BasableRow,
+        imp::{db::DB, SharedTable},
+        AppError, BasableError,
+    },
+    globals::BASABLE_SERIES_COL,
+};
+
+use super::{AnalysisGraph, AnalysisResult, AnalysisResults};
+
+/// Number of series of a graph when no limit is given.
+const DEFAULT_SERIES_LIMIT: usize = 10;
+
+/// The series of the rows that are not in the top series.
+const OTHER_SERIES: &str = "Other";
+
+/// Splits a graph into one series for each value of a column.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SeriesOpts {
+    /// The column of the series, in the graph's table.
+    pub column: String,
+
+    /// Number of series. Only the values of `column` with the most rows in the table get a
+    /// series, and the other rows are in the [`OTHER_SERIES`] series.
+    pub limit: usize,
+}
+
+impl SeriesOpts {
+    /// Reads the `series_by` and `series_limit` query params.
+    pub fn from_query_params(params: &HashMap<String, String>) -> Result<Option<Self>, AppError> {
+        let err = |msg: &str| AppError::new(StatusCode::EXPECTATION_FAILED, msg);
+
+        let Some(column) = params.get("series_by") else {
+            return match params.contains_key("series_limit") {
+                true => Err(err("missing 'series_by' parameter")),
+                false => Ok(None),
+            };
+        };
+
+        let limit = match params.get("series_limit") {
+            Some(l) => l.parse::<usize>().map_err(|e| err(&e.to_string()))?,
+            None => DEFAULT_SERIES_LIMIT,
+        };
+        if limit == 0 {
+            return Err(err("'series_limit' must be at least 1"));
+        }
+
+        Ok(Some(SeriesOpts {
+            column: column.to_string(),
+            limit,
+        }))
+    }
+
+    /// SQL expression of the series of a row. The column is resolved in `tbl`, and prefixed
+    /// with the table alias `alias` when given.
+    pub fn parse<D: DB + ?Sized>(
+        &self,
+        db: &D,
+        tbl: &SharedTable,
+        alias: Option<&str>,
+    ) -> Result<String, BasableError> {
+        let col = db.quote_ident(&tbl.resolve_columns(&[&self.column])?[0]);
+        let table = db.quote_table(tbl.name());
+        let limit = self.limit;
+
+        let series = match alias {
+            Some(alias) => format!("{alias}.{col}"),
+            None => col.clone(),
+        };
+
+        // The top series are selected from a derived table, since MySQL doesn't accept LIMIT
+        // in IN subqueries.
+        let top = format!(
+            "SELECT {col} AS {BASABLE_SERIES_COL} FROM {table} WHERE {col} IS NOT NULL \
+            GROUP BY {col} ORDER BY COUNT(*) DESC, {col} LIMIT {limit}"
+        );
+
+        Ok(format!(
+            "CASE WHEN {series} IN (SELECT {BASABLE_SERIES_COL} FROM ({top}) top_series) \
+            THEN {} ELSE '{OTHER_SERIES}' END",
+            db.parse_text_cast(&series)
+        ))
+    }
+}
+
+/// How the rows of a graph are split into series. See [`SeriesOpts`].
+pub(crate) struct SeriesSplit {
+    series: bool,
+
+    /// Number of results of each series.
+    limit: Option<usize>,
+}
+
+impl SeriesSplit {
+    /// Splits graphs of `series`, keeping the first `limit` results of each series. Graphs
+    /// without series are not split, and their queries are limited instead.
+    pub fn new(series: Option<&SeriesOpts>, limit: Option<usize>) -> Self {
+        SeriesSplit {
+            series: series.is_some(),
+            limit,
+        }
+    }
+
+    /// Builds the graph of `rows`, reading each row's result with `result`.
+    pub fn graph(
+        &self,
+        rows: &[BasableRow],
+        result: impl Fn(&BasableRow) -> AnalysisResult,
+    ) -> AnalysisGraph {
+        if !self.series {
+            return AnalysisGraph::Single(rows.iter().map(result).collect());
+        }
+
+        let mut series: BTreeMap<String, AnalysisResults> = BTreeMap::new();
+        for row in rows {
+            let name = row
+                .value(BASABLE_SERIES_COL)
+                .map_or_else(|| OTHER_SERIES.to_string(), |v| v.to_string());
+            let results = series.entry(name).or_default();
+
+            if self.limit.is_none_or(|limit| results.len() < limit) {
+                results.push(result(row));
+            }
+        }
+
+        AnalysisGraph::Series(series)
+    }
+}
@@ -4,12 +4,19 @@ use axum::http::StatusCode;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use crate::base::{
-    query::{
-        filter::{Filter, FilterChain, FilterCondition, FilterOperator},
-        BasableQuery, QueryOperation, QueryOrder,
+use crate::{
+    base::{
+        imp::{
+            db::DB,
+            graphs::series::{SeriesOpts, SeriesSplit},
+        },
+        query::{
+            filter::{Filter, FilterChain, FilterCondition, FilterOperator},
+            BasableQuery, QueryOperation, QueryOrder,
+        },
+        AppError, BasableError,
     },
-    AppError,
+    globals::BASABLE_SERIES_COL,
 };
 
 #[derive(Clone)]
@@ -36,6 +43,7 @@ impl TryFrom<&String> for TrendGraphType {
 }
 
 #[derive(EnumIter)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TrendGraphOrder {
     DESC,
     ASC,
@@ -99,6 +107,9 @@ pub struct TrendGraphOpts {
 
     /// Configure this option if you're using [`TrendAnalysisType::CrossModel`].
     pub cross: Option<CrossOptions>,
+
+    /// Splits the graph into series. Each series then has up to `limit` results.
+    pub series: Option<SeriesOpts>,
 }
 
 impl TrendGraphOpts {
@@ -165,9 +176,7 @@ impl TrendGraphOpts {
                 }
 
                 // if insufficient parameters are supplied for cross analysis, return error
-                if let Err(err) = cross_err {
-                    return Err(err);
-                }
+                cross_err?;
 
                 let opts = TrendGraphOpts {
                     table: String::from(table),
@@ -177,6 +186,7 @@ impl TrendGraphOpts {
                     order,
                     limit,
                     cross,
+                    series: SeriesOpts::from_query_params(&params)?,
                 };
 
                 Ok(opts)
@@ -187,12 +197,14 @@ impl TrendGraphOpts {
             }
         }
     }
-}
 
-impl TryFrom<TrendGraphOpts> for BasableQuery {
-    type Error = AppError;
+    /// How the results are split into series.
+    pub fn split(&self) -> SeriesSplit {
+        SeriesSplit::new(self.series.as_ref(), self.limit)
+    }
 
-    fn try_from(value: TrendGraphOpts) -> Result<Self, Self::Error> {
+    /// Builds the [`BasableQuery`] for this analysis. `db` resolves the tables and columns.
+    pub fn into_query<D: DB + ?Sized>(self, db: &D) -> Result<BasableQuery, BasableError> {
         let TrendGraphOpts {
             table,
             graph_type: analysis_type,
@@ -201,11 +213,29 @@ impl TryFrom<TrendGraphOpts> for BasableQuery {
             order,
             limit,
             cross,
-        } = value;
+            series,
+        } = self;
+
+        let tbl = db.resolve_table(&table)?;
+        let table = db.quote_table(tbl.name());
+
+        // The results of each series are limited by [`SeriesSplit`] instead of the query.
+        let alias = matches!(analysis_type, TrendGraphType::CrossModel).then_some("x");
+        let series = series.map(|s| s.parse(db, tbl, alias)).transpose()?;
+        let series_column = series
+            .as_ref()
+            .map(|s| format!("{s} AS {BASABLE_SERIES_COL}"));
+        let limit = limit.filter(|_| series.is_none());
 
         match analysis_type {
             TrendGraphType::IntraModel => {
-                let operation = QueryOperation::SelectData(Some(vec![xcol, ycol.clone()]));
+                let cols = tbl.resolve_columns(&[&xcol, &ycol])?;
+                let xcol = db.quote_ident(&cols[0]);
+                let ycol = db.quote_ident(&cols[1]);
+
+                let mut select_columns = vec![xcol, ycol.clone()];
+                select_columns.extend(series_column);
+                let operation = QueryOperation::SelectData(Some(select_columns));
 
                 let order = match order {
                     Some(order) => match order {
@@ -235,18 +265,30 @@ impl TryFrom<TrendGraphOpts> for BasableQuery {
                         target_col,
                     } = cross;
 
-                    let select_columns = vec![
+                    let cols = tbl.resolve_columns(&[&xcol, &target_col])?;
+                    let xcol = db.quote_ident(&cols[0]);
+                    let target_col = db.quote_ident(&cols[1]);
+
+                    let ftbl = db.resolve_table(&foreign_table)?;
+                    let ycol = db.quote_ident(&ftbl.resolve_columns(&[&ycol])?[0]);
+                    let foreign_table = db.quote_table(ftbl.name());
+
+                    let mut select_columns = vec![
                         format!("x.{xcol} AS {xcol}"),
                         format!("COUNT(y.{ycol}) AS {ycol}"),
                     ];
+                    select_columns.extend(series_column);
 
                     let operation = QueryOperation::SelectData(Some(select_columns));
                     let left_join = format!("{foreign_table} y ON x.{target_col} = y.{ycol}");
 
-                    let mut having = FilterChain::new();
-                    having.add_one(Filter::BASE(FilterCondition {
-                        column: ycol.clone(),
-                        operator: FilterOperator::Gt("0".to_string()),
+                    // Rows without a linked row are left out. Comparing the count with a
+                    // bound value would compare it with text, which SQLite always orders after
+                    // numbers.
+                    let mut filters = FilterChain::new();
+                    filters.add_one(Filter::Condition(FilterCondition {
+                        column: format!("y.{ycol}"),
+                        operator: FilterOperator::NotNull,
                     }));
 
                     let order = match order {
@@ -259,12 +301,15 @@ impl TryFrom<TrendGraphOpts> for BasableQuery {
 
                     let order_by = Some(order);
 
+                    let mut group_by = vec![xcol];
+                    group_by.extend(series);
+
                     let q = BasableQuery {
                         operation,
-                        having,
+                        filters,
                         table: format!("{table} x"),
                         left_join: Some(left_join),
-                        group_by: Some(vec![xcol]),
+                        group_by: Some(group_by),
                         order_by,
                         limit,
                         ..Default::default()
@@ -272,14 +317,9 @@ impl TryFrom<TrendGraphOpts> for BasableQuery {
 
                     Ok(q)
                 }
-                None => {
-                    let err = AppError::new(
-                        StatusCode::EXPECTATION_FAILED,
-                        "You must provide cross model options.",
-                    );
-
-                    Err(err)
-                }
+                None => Err(BasableError::Query(
+                    "You must provide cross model options.".to_string(),
+                )),
             },
         }
     }
@@ -1,16 +1,24 @@
 use std::collections::HashMap;
 
-use crate::{base::{column::ColumnList, data::table::{DataQueryFilter, DataQueryResult, TableConfig, UpdateDataOptions}}, imp::database::mysql::table::MySqlTable};
-
-use super::ConnectorType;
-
-pub(crate) type TableError = <MySqlTable as Table>::Error;
-pub(crate) type TableColumn = <MySqlTable as Table>::ColumnValue;
-
-pub(crate) trait Table: TableCRUD + Sync + Send {
-    type Error;
-    type Row;
-    type ColumnValue;
+use crate::base::{
+    column::ColumnList,
+    data::{
+        row::BasableRow,
+        table::{DataCursor, DataPage, DataQueryFilter, TableConfig},
+        value::BasableValue,
+    },
+    query::{
+        filter::{Filter, FilterChain, FilterCondition, FilterOperator},
+        find_ident, BasableQuery, QueryOperation, QueryOrder, SqlQuery,
+    },
+    BasableError,
+};
+
+use super::{connector::QueryExecutor, db::QuerySqlParser, ConnectorType};
+
+/// A table of a data source. Tables build their own queries, so they are [`QuerySqlParser`]s
+/// of the SQL dialect of their data source.
+pub(crate) trait Table: QuerySqlParser + Sync + Send {
 
     /// Create a new [`Table`] and assign the given [`ConnectorType`].
     ///
@@ -37,33 +45,413 @@ pub(crate) trait Table: TableCRUD + Sync + Send {
     fn connector(&self) -> &ConnectorType;
 
     /// Retrieve available columns for the table and build a [`ColumnList`].
-    fn query_columns(&self) -> Result<ColumnList, <Self as Table>::Error>;
+    fn query_columns(&self) -> Result<ColumnList, BasableError>;
+
+    /// Resolves `names` to the names of the table's columns, see [`find_ident`]. Unknown
+    /// columns are a [`BasableError::Identifier`]. The columns are queried on each call, so
+    /// names resolved several times for a request should be resolved with
+    /// [`Table::resolve_in`].
+    fn resolve_columns(&self, names: &[&str]) -> Result<Vec<String>, BasableError> {
+        self.resolve_in(&self.query_columns()?, names)
+    }
+
+    /// Resolves `names` like [`Table::resolve_columns`], against `cols`, the columns of the
+    /// table from [`Table::query_columns`].
+    fn resolve_in(&self, cols: &ColumnList, names: &[&str]) -> Result<Vec<String>, BasableError> {
+        names
+            .iter()
+            .map(|name| {
+                find_ident(cols.iter().map(|c| c.name.as_str()), name)
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        BasableError::Identifier(format!(
+                            "Unknown column '{name}' in table '{}'.",
+                            self.name()
+                        ))
+                    })
+            })
+            .collect()
+    }
 
     /// Create table's initial [`TableConfig`] if possible. Caller is responsible for
     /// saving the configuration in persistent DB.
-    fn init_config(&self) -> Option<TableConfig>;
-}
+    ///
+    /// The `pk` of the config is a column that is unique on its own, so that rows can be
+    /// changed one by one. It's `None` if the table only has keys of several columns.
+    fn init_config(&self) -> Option<TableConfig> {
+        let cols = self.query_columns().ok()?;
+
+        // Prefer the primary key. Otherwise, use the first unique column.
+        let pk = cols
+            .iter()
+            .find(|c| c.primary)
+            .or_else(|| cols.iter().find(|c| c.unique))
+            .map(|c| c.name.clone());
+
+        Some(TableConfig {
+            pk,
+            table_id: self.name().to_string(),
+            ..TableConfig::default()
+        })
+    }
+
+    /// Retrieve a page of data from the table based on query `filter`, with the number of
+    /// rows that match the filter.
+    fn query_data(&self, filter: DataQueryFilter) -> Result<DataPage, BasableError> {
+        let DataQueryFilter {
+            limit,
+            offset,
+            columns,
+            exclude,
+            filters,
+            order_by,
+            cursor_key,
+            cursor,
+        } = filter;
+
+        // Names are resolved against the columns queried once for the whole query.
+        let table_cols = self.query_columns()?;
+        let resolve = |name: &str| -> Result<String, BasableError> {
+            Ok(self.resolve_in(&table_cols, &[name])?.pop().unwrap())
+        };
+
+        let mut cols: Vec<String> = match columns {
+            Some(names) => {
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                self.resolve_in(&table_cols, &names)?
+            }
+            None => table_cols.iter().map(|c| c.name.clone()).collect(),
+        };
+        if let Some(exclude) = exclude {
+            let exclude: Vec<&str> = exclude.iter().map(String::as_str).collect();
+            let exclude = self.resolve_in(&table_cols, &exclude)?;
+            cols.retain(|c| !exclude.contains(c));
+        }
+        if cols.is_empty() {
+            return Err(BasableError::Identifier(
+                "At least one column must be queried.".to_string(),
+            ));
+        }
 
-pub(crate) trait TableCRUD {
-    /// Inserts a new data into the table.
-    fn insert_data(&self, input: HashMap<String, String>) -> Result<(), TableError>;
+        let mut conditions = FilterChain::new();
+        for filter in filters.all() {
+            conditions.add_one(filter.map_columns(&mut |column| {
+                Ok::<_, BasableError>(self.quote_ident(&resolve(column)?))
+            })?);
+        }
+
+        // Sort column, and whether rows are in descending order.
+        let order = match order_by {
+            Some(QueryOrder::ASC(col)) => Some((resolve(&col)?, false)),
+            Some(QueryOrder::DESC(col)) => Some((resolve(&col)?, true)),
+            None => None,
+        };
+
+        // Pages of a cursor must have the same rows, so its filters must be the same.
+        let filters_hash = DataCursor::hash_filters(&conditions);
+        if cursor.as_ref().is_some_and(|c| c.filters != filters_hash) {
+            return Err(BasableError::Input(
+                "The filters of a cursor can't be changed.".to_string(),
+            ));
+        }
 
-    /// Retrieve data from table based on query `filter`.
-    fn query_data(
+        // Pages have cursors when rows are ordered by the cursor key. Rows with the same key
+        // would be skipped, so keys of cursors must be the cursor key or a unique column.
+        let cursor_key = match cursor_key {
+            Some(key) => Some(resolve(&key)?),
+            None => None,
+        };
+        let key = match &cursor {
+            Some(c) => {
+                let key = resolve(&c.key)?;
+                let unique = table_cols
+                    .iter()
+                    .any(|col| col.name == key && (col.primary || col.unique));
+                if !unique && cursor_key.as_ref() != Some(&key) {
+                    return Err(BasableError::Input(format!(
+                        "Column '{key}' is not unique, so it can't be the key of a cursor."
+                    )));
+                }
+                Some(key)
+            }
+            None => cursor_key,
+        };
+        let mut query_order = None;
+        let keyset = match (key, &cursor, order) {
+            (Some(key), Some(c), _) => Some((key, c.desc)),
+            (Some(key), None, None) => Some((key, false)),
+            (Some(key), None, Some((col, desc))) if col == key => Some((key, desc)),
+            (_, _, order) => {
+                query_order = order.map(|(col, desc)| match desc {
+                    true => QueryOrder::DESC(self.quote_ident(&col)),
+                    false => QueryOrder::ASC(self.quote_ident(&col)),
+                });
+                None
+            }
+        };
+
+        let table = self.quote_table(self.name());
+        let conn = self.connector();
+
+        let count = BasableQuery {
+            table: table.clone(),
+            operation: QueryOperation::SelectData(Some(vec!["COUNT(*) AS total".to_string()])),
+            filters: conditions.clone(),
+            ..Default::default()
+        };
+        let SqlQuery { sql, params } = self.generate_sql(count)?;
+        let total = conn
+            .exec_query_params(&sql, &params)?
+            .first()
+            .and_then(|r| r.get("total"))
+            .unwrap_or(0);
+
+        let mut select_cols = cols.clone();
+        let mut query_limit = limit;
+        let prev = cursor.as_ref().is_some_and(|c| c.prev);
+
+        if let Some((key, desc)) = &keyset {
+            let quoted_key = self.quote_ident(key);
+
+            // Previous pages are found by going backward from the cursor.
+            if let Some(c) = &cursor {
+                let operator = match c.desc != c.prev {
+                    true => FilterOperator::Lt(c.value.clone()),
+                    false => FilterOperator::Gt(c.value.clone()),
+                };
+                conditions.add_one(Filter::Condition(FilterCondition {
+                    column: quoted_key.clone(),
+                    operator,
+                }));
+            }
+            query_order = match *desc != prev {
+                true => Some(QueryOrder::DESC(quoted_key)),
+                false => Some(QueryOrder::ASC(quoted_key)),
+            };
+
+            // One more row tells whether there are rows after the page.
+            query_limit += 1;
+            if !select_cols.contains(key) {
+                select_cols.push(key.clone());
+            }
+        }
+
+        let query = BasableQuery {
+            table,
+            operation: QueryOperation::SelectData(Some(
+                select_cols.iter().map(|c| self.quote_ident(c)).collect(),
+            )),
+            filters: conditions,
+            limit: Some(query_limit),
+            offset: Some(offset),
+            order_by: query_order,
+            ..Default::default()
+        };
+        let SqlQuery { sql, params } = self.generate_sql(query)?;
+        let mut result = conn.exec_query_params(&sql, &params)?;
+
+        let mut next_cursor = None;
+        let mut prev_cursor = None;
+
+        if let Some((key, desc)) = &keyset {
+            let more = result.len() > limit;
+            result.truncate(limit);
+            if prev {
+                result.reverse();
+            }
+
+            let cursor_at = |row: Option<&BasableRow>, prev| {
+                row.and_then(|r| r.value(key))
+                    .filter(|v| **v != BasableValue::Null)
+                    .map(|v| {
+                        DataCursor {
+                            key: key.clone(),
+                            value: v.to_string(),
+                            desc: *desc,
+                            prev,
+                            filters: filters_hash.clone(),
+                        }
+                        .encode()
+                    })
+            };
+
+            // A page found going backward has rows after it, and one found going forward has
+            // rows before it, unless it's the first page.
+            let (has_next, has_prev) = match prev {
+                true => (true, more),
+                false => (more, cursor.is_some() || offset > 0),
+            };
+            if has_next {
+                next_cursor = cursor_at(result.last(), false);
+            }
+            if has_prev {
+                prev_cursor = cursor_at(result.first(), true);
+            }
+        }
+
+        let rows = result
+            .iter()
+            .map(|r| {
+                cols.iter()
+                    .map(|c| {
+                        let v = r.value(c).cloned().unwrap_or(BasableValue::Null);
+                        (c.clone(), v)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(DataPage {
+            total,
+            limit,
+            offset,
+            rows,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    /// [`BasableQuery`] that inserts `row`.
+    fn insert_query(
         &self,
-        filter: DataQueryFilter,
-    ) -> DataQueryResult<TableColumn, TableError>;
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<BasableQuery, BasableError> {
+        let cols = self.query_columns()?;
+
+        Ok(BasableQuery {
+            table: self.quote_table(self.name()),
+            operation: QueryOperation::Insert(row_values(self, &cols, row)?),
+            ..Default::default()
+        })
+    }
+
+    /// [`BasableQuery`] that sets the columns of the rows whose column `col` has `value` to
+    /// the values in `row`.
+    fn update_query(
+        &self,
+        col: &str,
+        value: &str,
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<BasableQuery, BasableError> {
+        let cols = self.query_columns()?;
+
+        Ok(BasableQuery {
+            table: self.quote_table(self.name()),
+            operation: QueryOperation::Update(row_values(self, &cols, row)?),
+            filters: key_filter(self, &cols, col, value)?,
+            ..Default::default()
+        })
+    }
+
+    /// [`BasableQuery`] that deletes the rows whose column `col` has `value`.
+    fn delete_query(&self, col: &str, value: &str) -> Result<BasableQuery, BasableError> {
+        let cols = self.query_columns()?;
+
+        Ok(BasableQuery {
+            table: self.quote_table(self.name()),
+            operation: QueryOperation::Delete,
+            filters: key_filter(self, &cols, col, value)?,
+            ..Default::default()
+        })
+    }
+
+    /// [`BasableQuery`] that inserts `row`, or updates the row with the same value of
+    /// column `key`. `row` must have a value of `key`.
+    fn upsert_query(
+        &self,
+        key: &str,
+        row: &HashMap<String, BasableValue>,
+    ) -> Result<BasableQuery, BasableError> {
+        let cols = self.query_columns()?;
+        let col = self.resolve_in(&cols, &[key])?.pop().unwrap();
+        let key = self.quote_ident(&col);
+        let row = row_values(self, &cols, row)?;
+        if !row.iter().any(|(c, _)| *c == key) {
+            return Err(BasableError::Input(format!(
+                "Upserted rows must have a value of '{col}'."
+            )));
+        }
+
+        Ok(BasableQuery {
+            table: self.quote_table(self.name()),
+            operation: QueryOperation::Upsert {
+                row,
+                keys: vec![key],
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Runs `query`, which returns no rows, with `exec`, such as a transaction of
+    /// [`Connector::transaction`](super::connector::Connector::transaction).
+    fn exec_query_with(
+        &self,
+        exec: &dyn QueryExecutor,
+        query: BasableQuery,
+    ) -> Result<(), BasableError> {
+        let SqlQuery { sql, params } = self.generate_sql(query)?;
+        exec.exec_query_params(&sql, &params)?;
+
+        Ok(())
+    }
+}
+
+/// Columns of `row`, resolved against the table's `cols` and quoted, with their values.
+fn row_values<T: Table + ?Sized>(
+    table: &T,
+    cols: &ColumnList,
+    row: &HashMap<String, BasableValue>,
+) -> Result<Vec<(String, BasableValue)>, BasableError> {
+    let (names, values): (Vec<&str>, Vec<BasableValue>) =
+        row.iter().map(|(k, v)| (k.as_str(), v.clone())).unzip();
+    let cols = table.resolve_in(cols, &names)?;
+
+    Ok(cols
+        .iter()
+        .map(|c| table.quote_ident(c))
+        .zip(values)
+        .collect())
+}
 
-    fn update_data(&self, input: UpdateDataOptions) -> Result<(), TableError>;
+/// Filters of the rows of `table` whose column `col` has `value`. `col` is resolved
+/// against the table's `cols`.
+fn key_filter<T: Table + ?Sized>(
+    table: &T,
+    cols: &ColumnList,
+    col: &str,
+    value: &str,
+) -> Result<FilterChain, BasableError> {
+    let col = table.resolve_in(cols, &[col])?.pop().unwrap();
+
+    let mut filters = FilterChain::new();
+    filters.add_one(Filter::Condition(FilterCondition {
+        column: table.quote_ident(&col),
+        operator: FilterOperator::Eq(value.to_string()),
+    }));
+
+    Ok(filters)
+}
 
-    fn delete_data(&self, col: String, value: String) -> Result<(), TableError>;
+/// Values of `input` as text, for the rows written by the data routes.
+pub(crate) fn text_row(input: HashMap<String, String>) -> HashMap<String, BasableValue> {
+    input
+        .into_iter()
+        .map(|(k, v)| (k, BasableValue::Text(v)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use axum::http::StatusCode;
 
     use crate::{
-        base::{imp::table::DataQueryFilter, AppError},
+        base::{
+            imp::table::{text_row, DataCursor, DataQueryFilter},
+            query::filter::FilterChain,
+            AppError, BasableError,
+        },
         tests::common::{create_test_db, get_test_db_table},
     };
 
@@ -96,6 +484,252 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_table_query_data_filter() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table = db.get_table(&get_test_db_table()).unwrap();
+
+        let params = |params: &[(&str, &str)]| {
+            let params: Vec<(String, String)> = params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            DataQueryFilter::from_query_params(&params)
+        };
+
+        let filter = params(&[
+            ("filter", "basable_filter:eq(Publisher,Rockstar Games)"),
+            (
+                "filter",
+                "basable_filter:or(not(lt(critic_score,9.4)),null(critic_score))",
+            ),
+            ("columns", "title,critic_score"),
+            ("sort", "critic_score"),
+            ("order", "desc"),
+            ("limit", "2"),
+            ("page", "1"),
+        ])?;
+        let page = table.query_data(filter)?;
+        assert_eq!(page.total, 3);
+        assert_eq!(page.rows.len(), 2);
+        assert_eq!(page.rows[0].len(), 2);
+        assert_eq!(page.rows[0]["title"].to_string(), "Grand Theft Auto IV");
+
+        let filter = params(&[
+            ("filter", "basable_filter:eq(publisher,Rockstar Games)"),
+            ("exclude", "id"),
+            ("sort", "critic_score"),
+            ("offset", "2"),
+        ])?;
+        let page = table.query_data(filter)?;
+        assert_eq!((page.total, page.offset), (3, 2));
+        assert_eq!(page.rows.len(), 1);
+        assert_eq!(page.rows[0]["title"].to_string(), "Grand Theft Auto IV");
+        assert!(!page.rows[0].contains_key("id"));
+
+        // Regular expressions work on every data source.
+        let regex = |op: &str| {
+            let filter = format!("basable_filter:{op}(title,^Grand Theft)");
+            params(&[
+                ("filter", "basable_filter:eq(publisher,Rockstar Games)"),
+                ("filter", &filter),
+                ("sort", "title"),
+            ])
+        };
+        let page = table.query_data(regex("regex")?)?;
+        assert_eq!(page.total, 2);
+        assert_eq!(page.rows[0]["title"].to_string(), "Grand Theft Auto IV");
+        let page = table.query_data(regex("nregex")?)?;
+        assert_eq!(page.total, 1);
+        assert_eq!(page.rows[0]["title"].to_string(), "Red Dead Redemption");
+
+        let unknown = params(&[("filter", "basable_filter:null(1 = 1 OR id)")])?;
+        assert!(matches!(
+            table.query_data(unknown),
+            Err(BasableError::Identifier(_))
+        ));
+
+        for invalid in [
+            &[("filter", "basable_filter:BASE:publisher:null:")][..],
+            &[("page", "1"), ("offset", "10")],
+            &[("page", "0")],
+            &[("limit", "ten")],
+            &[("order", "desc")],
+            &[("sort", "title"), ("order", "up")],
+        ] {
+            assert!(params(invalid).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_query_data_cursor() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table = db.get_table(&get_test_db_table()).unwrap();
+
+        // Returns the ids of a page, and its cursors.
+        let page = |params: &[(&str, &str)]| -> Result<_, AppError> {
+            let mut params: Vec<(String, String)> = params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            params.push((
+                "filter".to_string(),
+                "basable_filter:or(eq(publisher,Rockstar Games),eq(publisher,Bethesda Softworks))"
+                    .to_string(),
+            ));
+            params.push(("limit".to_string(), "2".to_string()));
+
+            let mut filter = DataQueryFilter::from_query_params(&params)?;
+            filter.cursor_key = Some("id".to_string());
+            let page = table.query_data(filter)?;
+            assert_eq!(page.total, 5);
+
+            let ids: Vec<String> = page.rows.iter().map(|r| r["id"].to_string()).collect();
+            Ok((ids, page.next_cursor, page.prev_cursor))
+        };
+
+        let (first, next, prev) = page(&[])?;
+        assert!(prev.is_none());
+        let (second, next, prev) = page(&[("cursor", &next.unwrap())])?;
+        assert!(prev.is_some());
+        let (third, next, prev) = page(&[("cursor", &next.unwrap())])?;
+        assert_eq!(third.len(), 1);
+        assert!(next.is_none());
+
+        let mut ids: Vec<String> = [first.clone(), second.clone(), third].concat();
+        let mut sorted = ids.clone();
+        sorted.sort_by_key(|id| id.parse::<i64>().unwrap());
+        assert_eq!(ids, sorted);
+        ids.dedup();
+        assert_eq!(ids.len(), 5);
+
+        // Going back from the last page
+        let (back, _, prev) = page(&[("cursor", &prev.unwrap())])?;
+        assert_eq!(back, second);
+        let (back, next, prev) = page(&[("cursor", &prev.unwrap())])?;
+        assert_eq!(back, first);
+        assert!(prev.is_none());
+        assert!(next.is_some());
+
+        // Descending order is kept in the cursors.
+        let (desc, next, _) = page(&[("sort", "id"), ("order", "desc")])?;
+        let (desc_next, _, _) = page(&[("cursor", &next.unwrap())])?;
+        sorted.reverse();
+        assert_eq!([desc, desc_next].concat(), sorted[..4]);
+
+        // Rows sorted by another column have no cursors.
+        let (_, next, _) = page(&[("sort", "title")])?;
+        assert!(next.is_none());
+
+        assert!(page(&[("cursor", "invalid")]).is_err());
+
+        // Cursors can't be used with other filters, or with keys that aren't unique.
+        let (_, next, _) = page(&[])?;
+        let next = next.unwrap();
+        let filter = "basable_filter:not(null(id))";
+        let AppError(code, msg) = page(&[("cursor", &next), ("filter", filter)]).unwrap_err();
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert_eq!(msg, "The filters of a cursor can't be changed.");
+
+        let mut forged = DataCursor::try_from(next.as_str()).unwrap();
+        forged.key = "title".to_string();
+        let AppError(code, msg) = page(&[("cursor", &forged.encode())]).unwrap_err();
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert!(msg.contains("is not unique"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_query_data_cursor_composite_key() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let conn = db.connector();
+        conn.exec_query("DROP TABLE IF EXISTS basable_composite")?;
+        conn.exec_query(
+            "CREATE TABLE basable_composite (a INTEGER NOT NULL, b INTEGER NOT NULL, PRIMARY KEY (a, b))",
+        )?;
+
+        // Columns of a primary key of several columns aren't unique on their own.
+        let db = create_test_db()?;
+        let table = db.get_table("basable_composite").unwrap();
+        let cols = table.query_columns()?;
+        assert!(cols.iter().all(|c| !c.primary && !c.unique));
+
+        let cursor = DataCursor {
+            key: "a".to_string(),
+            value: "1".to_string(),
+            desc: false,
+            prev: false,
+            filters: DataCursor::hash_filters(&FilterChain::new()),
+        };
+        let filter = DataQueryFilter {
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        let page = table.query_data(filter);
+
+        conn.exec_query("DROP TABLE basable_composite")?;
+        assert!(matches!(page, Err(BasableError::Input(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_init_config() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table = db.get_table(&get_test_db_table()).unwrap();
+        assert_eq!(table.init_config().unwrap().pk.as_deref(), Some("id"));
+
+        let conn = db.connector();
+        conn.exec_query("DROP TABLE IF EXISTS basable_composite_config")?;
+        conn.exec_query(
+            "CREATE TABLE basable_composite_config (a INTEGER NOT NULL, b INTEGER NOT NULL, PRIMARY KEY (a, b))",
+        )?;
+
+        // Rows of tables without a key of one column can't be found by a `pk`.
+        let db = create_test_db()?;
+        let table = db.get_table("basable_composite_config").unwrap();
+        let config = table.init_config();
+
+        conn.exec_query("DROP TABLE basable_composite_config")?;
+        assert!(config.unwrap().pk.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_unknown_column() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table_name = get_test_db_table();
+
+        if let Some(table) = db.get_table(&table_name) {
+            let deleted = table.delete_query("1 = 1 OR id", "1");
+            assert!(matches!(deleted, Err(BasableError::Identifier(_))));
+
+            let cols = table.query_columns()?;
+            assert_eq!(table.resolve_in(&cols, &["ID", "title"])?, ["id", "title"]);
+            let unknown = table.resolve_in(&cols, &["title", "1 = 1 OR id"]);
+            assert!(matches!(unknown, Err(BasableError::Identifier(_))));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_upsert_without_key() -> Result<(), AppError> {
+        let db = create_test_db()?;
+        let table = db.get_table(&get_test_db_table()).unwrap();
+
+        let input = HashMap::from([("title".to_string(), "Upsert".to_string())]);
+        let err = table.upsert_query("id", &text_row(input)).err().unwrap();
+        assert!(matches!(err, BasableError::Input(_)));
+        assert_eq!(AppError::from(err).0, StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -103,7 +737,7 @@ mod interactive_tests {
     use std::{collections::HashMap, io::stdin};
 
     use crate::{
-        base::{imp::table::UpdateDataOptions, AppError},
+        base::{data::table::UpdateDataOptions, imp::table::text_row, AppError},
         tests::common::{create_test_db, get_test_db_table},
     };
 
@@ -141,7 +775,7 @@ mod interactive_tests {
                 test_data.insert(spl[0].to_string(), spl[1].to_string());
             }
 
-            let insert_data = table.insert_data(test_data);
+            let insert_data = db.insert_row(db.connector(), table.name(), &text_row(test_data));
 
             assert!(insert_data.is_ok());
         }
@@ -187,7 +821,9 @@ mod interactive_tests {
                 .insert(spl[0].to_string(), spl[1].to_string());
 
             // update the table
-            let update_data = table.update_data(test_data);
+            let UpdateDataOptions { key, value, input } = test_data;
+            let row = text_row(input);
+            let update_data = db.update_row(db.connector(), table.name(), &key, &value, &row);
 
             assert!(update_data.is_ok());
         }
@@ -0,0 +1,193 @@
+use rusqlite::{params, types::Type, OptionalExtension, Row};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::base::{
+    user::{ApiKeyAccess, ApiKeyScope},
+    BasableError,
+};
+
+use super::LocalDB;
+
+const SELECT_KEY: &str = "SELECT id, name, prefix, access, connections, created_at, last_used_at,
+    revoked_at FROM api_keys";
+
+/// An API key of a user, without the key itself. `prefix` is the start of the key, so that
+/// users can tell their keys apart.
+#[derive(Serialize)]
+pub(crate) struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub access: ApiKeyAccess,
+    pub connections: Vec<String>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// Repository of API keys. Keys are kept as hashes, and revoked keys are kept so that users
+/// can see when they were last used.
+pub(crate) struct ApiKeys<'a>(pub(super) &'a LocalDB);
+
+impl ApiKeys<'_> {
+    /// Saves a key of the user, with hash `key_hash`, that gives `access` to `connections`.
+    pub fn create(
+        &self,
+        user_id: &str,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        access: ApiKeyAccess,
+        connections: &[String],
+    ) -> Result<ApiKey, BasableError> {
+        let id = Uuid::new_v4().to_string();
+        let conn = self.0.pool()?;
+
+        conn.execute(
+            "INSERT INTO api_keys (id, user_id, name, prefix, key_hash, access, connections)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                user_id,
+                name,
+                prefix,
+                key_hash,
+                access.as_str(),
+                serde_json::to_string(connections).unwrap()
+            ],
+        )?;
+
+        let key = conn.query_row(
+            &format!("{SELECT_KEY} WHERE id = ?1"),
+            params![id],
+            Self::from_row,
+        )?;
+
+        Ok(key)
+    }
+
+    /// Keys of the user, including revoked keys.
+    pub fn list(&self, user_id: &str) -> Result<Vec<ApiKey>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(&format!(
+            "{SELECT_KEY} WHERE user_id = ?1 ORDER BY created_at, name"
+        ))?;
+
+        let keys = stmt
+            .query_map(params![user_id], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(keys)
+    }
+
+    /// Id of the owner and scope of the key with hash `key_hash`, unless it is revoked.
+    /// The key's last-used timestamp is updated.
+    pub fn authenticate(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<(String, ApiKeyScope)>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let key = conn
+            .query_row(
+                "UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP
+                WHERE key_hash = ?1 AND revoked_at IS NULL
+                RETURNING user_id, access, connections",
+                params![key_hash],
+                |r| {
+                    let scope = ApiKeyScope {
+                        access: access(r, 1)?,
+                        connections: connections(r, 2)?,
+                    };
+                    Ok((r.get(0)?, scope))
+                },
+            )
+            .optional()?;
+
+        Ok(key)
+    }
+
+    /// Revokes key `id` of the user. Returns `false` if the user has no such key, or if it
+    /// was already revoked.
+    pub fn revoke(&self, id: &str, user_id: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let revoked = conn.execute(
+            "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = ?1 AND user_id = ?2 AND revoked_at IS NULL",
+            params![id, user_id],
+        )?;
+
+        Ok(revoked > 0)
+    }
+
+    fn from_row(r: &Row) -> Result<ApiKey, rusqlite::Error> {
+        Ok(ApiKey {
+            id: r.get(0)?,
+            name: r.get(1)?,
+            prefix: r.get(2)?,
+            access: access(r, 3)?,
+            connections: connections(r, 4)?,
+            created_at: r.get(5)?,
+            last_used_at: r.get(6)?,
+            revoked_at: r.get(7)?,
+        })
+    }
+}
+
+fn access(r: &Row, idx: usize) -> Result<ApiKeyAccess, rusqlite::Error> {
+    let access: String = r.get(idx)?;
+    ApiKeyAccess::try_from(access.as_str())
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, err.into()))
+}
+
+/// Connections of a key are kept as a JSON array of ids.
+fn connections(r: &Row, idx: usize) -> Result<Vec<String>, rusqlite::Error> {
+    let connections: String = r.get(idx)?;
+    serde_json::from_str(&connections)
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, err.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{local::LocalDB, user::ApiKeyAccess, BasableError};
+
+    #[test]
+    fn test_api_keys() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let keys = db.api_keys();
+        let connections = vec!["conn".to_string()];
+        let key = keys.create(
+            "user",
+            "CI",
+            "bsk_abcd",
+            "hash",
+            ApiKeyAccess::Read,
+            &connections,
+        )?;
+        assert!(key.last_used_at.is_none());
+
+        let (user_id, scope) = keys.authenticate("hash")?.unwrap();
+        assert_eq!(user_id, "user");
+        assert_eq!(scope.access, ApiKeyAccess::Read);
+        assert_eq!(scope.connections, connections);
+        assert!(keys.authenticate("other_hash")?.is_none());
+
+        let listed = keys.list("user")?;
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_used_at.is_some());
+        assert!(keys.list("other_user")?.is_empty());
+
+        // Revoked keys are listed, but can't be used.
+        assert!(!keys.revoke(&key.id, "other_user")?);
+        assert!(keys.revoke(&key.id, "user")?);
+        assert!(!keys.revoke(&key.id, "user")?);
+        assert!(keys.authenticate("hash")?.is_none());
+        assert!(keys.list("user")?[0].revoked_at.is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use rusqlite::{
+    params, params_from_iter,
+    types::{Type, Value},
+    OptionalExtension, Row,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::base::BasableError;
+
+use super::LocalDB;
+
+const SELECT_CHANGE: &str =
+    "SELECT id, user_id, conn_id, table_name, operation, pk, created_at FROM audit_changes";
+
+/// Kind of change recorded in the audit log.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AuditOperation {
+    Insert,
+    Update,
+    Delete,
+    /// A change to the [`TableConfig`](crate::base::data::table::TableConfig) of a table.
+    Configure,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Insert => "insert",
+            AuditOperation::Update => "update",
+            AuditOperation::Delete => "delete",
+            AuditOperation::Configure => "configure",
+        }
+    }
+}
+
+impl TryFrom<&str> for AuditOperation {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "insert" => Ok(AuditOperation::Insert),
+            "update" => Ok(AuditOperation::Update),
+            "delete" => Ok(AuditOperation::Delete),
+            "configure" => Ok(AuditOperation::Configure),
+            _ => Err(format!("Unknown audit operation '{value}'.")),
+        }
+    }
+}
+
+/// A row touched by a change, as it was before and after the change. `before` is `None` for
+/// inserted rows, and `after` is `None` for deleted rows.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub(crate) struct AuditRow {
+    /// Value of the table's primary key in the row, if the table has one.
+    pub pk_value: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// A change made by a user to a table, with the rows it touched.
+#[derive(Serialize)]
+pub(crate) struct AuditChange {
+    pub id: i64,
+    pub user_id: String,
+    pub conn_id: String,
+    pub table_name: String,
+    pub operation: AuditOperation,
+
+    /// Primary key column of the table when the change was made.
+    pub pk: Option<String>,
+    pub created_at: String,
+    pub rows: Vec<AuditRow>,
+}
+
+/// Filters of [`Audit::query`]. Dates are compared with `created_at`, in the
+/// `YYYY-MM-DD HH:MM:SS` format (UTC), so a date without a time can be used.
+#[derive(Deserialize, Default)]
+pub(crate) struct AuditFilter {
+    pub table: Option<String>,
+    pub user_id: Option<String>,
+    pub operation: Option<AuditOperation>,
+    pub pk_value: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+
+    /// Page of results, starting from 1.
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// A page of the audit log. `total` is the number of changes matching the filters.
+#[derive(Serialize)]
+pub(crate) struct AuditPage {
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+    pub changes: Vec<AuditChange>,
+}
+
+/// Number of changes per page of the audit log, when the page size is not given.
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Repository of the audit log. Each change is kept with the rows it touched, newest first.
+pub(crate) struct Audit<'a>(pub(super) &'a LocalDB);
+
+impl Audit<'_> {
+    /// Records a change of `operation` by the user to table `table_name` of connection
+    /// `conn_id`. Returns the id of the change.
+    pub fn record(
+        &self,
+        user_id: &str,
+        conn_id: &str,
+        table_name: &str,
+        operation: AuditOperation,
+        pk: Option<&str>,
+        rows: &[AuditRow],
+    ) -> Result<i64, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO audit_changes (user_id, conn_id, table_name, operation, pk)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![user_id, conn_id, table_name, operation.as_str(), pk],
+        )?;
+        let id = tx.last_insert_rowid();
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO audit_rows (change_id, pk_value, before, after)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?;
+
+            for row in rows {
+                let before = row.before.as_ref().map(|v| v.to_string());
+                let after = row.after.as_ref().map(|v| v.to_string());
+                stmt.execute(params![id, row.pk_value, before, after])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Change `id` of connection `conn_id`.
+    pub fn get(&self, conn_id: &str, id: i64) -> Result<Option<AuditChange>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let change = conn
+            .query_row(
+                &format!("{SELECT_CHANGE} WHERE conn_id = ?1 AND id = ?2"),
+                params![conn_id, id],
+                Self::from_row,
+            )
+            .optional()?;
+        drop(conn);
+
+        match change {
+            Some(mut change) => {
+                change.rows = self.rows(change.id)?;
+                Ok(Some(change))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Changes to connection `conn_id` that match `filter`, newest first.
+    pub fn query(&self, conn_id: &str, filter: &AuditFilter) -> Result<AuditPage, BasableError> {
+        let mut conditions = vec!["conn_id = ?"];
+        let mut values = vec![Value::Text(conn_id.to_string())];
+
+        let mut add = |condition, value: Option<&str>| {
+            if let Some(v) = value {
+                conditions.push(condition);
+                values.push(Value::Text(v.to_string()));
+            }
+        };
+        add("table_name = ?", filter.table.as_deref());
+        add("user_id = ?", filter.user_id.as_deref());
+        add(
+            "operation = ?",
+            filter.operation.as_ref().map(|o| o.as_str()),
+        );
+        add(
+            "id IN (SELECT change_id FROM audit_rows WHERE pk_value = ?)",
+            filter.pk_value.as_deref(),
+        );
+        add("created_at >= ?", filter.from.as_deref());
+        add("created_at <= ?", filter.to.as_deref());
+
+        let page = filter.page.unwrap_or(1).max(1);
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let conditions = conditions.join(" AND ");
+
+        let conn = self.0.pool()?;
+        let total: usize = conn.query_row(
+            &format!("SELECT COUNT(*) FROM audit_changes WHERE {conditions}"),
+            params_from_iter(&values),
+            |r| r.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{SELECT_CHANGE} WHERE {conditions} ORDER BY id DESC LIMIT {limit} OFFSET {}",
+            (page - 1) * limit
+        ))?;
+        let mut changes = stmt
+            .query_map(params_from_iter(&values), Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        drop(conn);
+
+        for change in changes.iter_mut() {
+            change.rows = self.rows(change.id)?;
+        }
+
+        Ok(AuditPage {
+            total,
+            page,
+            limit,
+            changes,
+        })
+    }
+
+    fn rows(&self, change_id: i64) -> Result<Vec<AuditRow>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(
+            "SELECT pk_value, before, after FROM audit_rows WHERE change_id = ?1 ORDER BY rowid",
+        )?;
+
+        let rows = stmt
+            .query_map(params![change_id], |r| {
+                Ok(AuditRow {
+                    pk_value: r.get(0)?,
+                    before: json(r, 1)?,
+                    after: json(r, 2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    fn from_row(r: &Row) -> Result<AuditChange, rusqlite::Error> {
+        let operation: String = r.get(4)?;
+
+        Ok(AuditChange {
+            id: r.get(0)?,
+            user_id: r.get(1)?,
+            conn_id: r.get(2)?,
+            table_name: r.get(3)?,
+            operation: AuditOperation::try_from(operation.as_str()).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(4, Type::Text, err.into())
+            })?,
+            pk: r.get(5)?,
+            created_at: r.get(6)?,
+            rows: Vec::new(),
+        })
+    }
+}
+
+/// Values of rows are kept as JSON.
+fn json(r: &Row, idx: usize) -> Result<Option<serde_json::Value>, rusqlite::Error> {
+    let value: Option<String> = r.get(idx)?;
+
+    value
+        .map(|v| serde_json::from_str(&v))
+        .transpose()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, err.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::base::{local::LocalDB, BasableError};
+
+    use super::{AuditFilter, AuditOperation, AuditRow};
+
+    #[test]
+    fn test_audit() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let audit = db.audit();
+        let row = |pk: &str, before, after| AuditRow {
+            pk_value: Some(pk.to_string()),
+            before,
+            after,
+        };
+
+        let inserted = audit.record(
+            "user",
+            "conn",
+            "sales",
+            AuditOperation::Insert,
+            Some("id"),
+            &[row("1", None, Some(json!({ "id": 1 })))],
+        )?;
+        audit.record(
+            "other_user",
+            "conn",
+            "sales",
+            AuditOperation::Update,
+            Some("id"),
+            &[
+                row(
+                    "1",
+                    Some(json!({ "id": 1 })),
+                    Some(json!({ "id": 1, "n": 2 })),
+                ),
+                row(
+                    "2",
+                    Some(json!({ "id": 2 })),
+                    Some(json!({ "id": 2, "n": 2 })),
+                ),
+            ],
+        )?;
+        audit.record(
+            "user",
+            "other_conn",
+            "sales",
+            AuditOperation::Delete,
+            None,
+            &[],
+        )?;
+
+        let change = audit.get("conn", inserted)?.unwrap();
+        assert_eq!(change.operation, AuditOperation::Insert);
+        assert_eq!(change.pk.as_deref(), Some("id"));
+        assert!(audit.get("other_conn", inserted)?.is_none());
+
+        let page = audit.query("conn", &AuditFilter::default())?;
+        assert_eq!(page.total, 2);
+        assert_eq!(page.changes[0].operation, AuditOperation::Update);
+        assert_eq!(page.changes[0].rows.len(), 2);
+
+        let filtered = |filter| audit.query("conn", &filter).map(|p| p.total);
+        assert_eq!(
+            filtered(AuditFilter {
+                user_id: Some("user".to_string()),
+                ..Default::default()
+            })?,
+            1
+        );
+        assert_eq!(
+            filtered(AuditFilter {
+                pk_value: Some("2".to_string()),
+                ..Default::default()
+            })?,
+            1
+        );
+        assert_eq!(
+            filtered(AuditFilter {
+                operation: Some(AuditOperation::Delete),
+                ..Default::default()
+            })?,
+            0
+        );
+        assert_eq!(
+            filtered(AuditFilter {
+                from: Some("2000-01-01".to_string()),
+                table: Some("sales".to_string()),
+                ..Default::default()
+            })?,
+            2
+        );
+
+        let page = audit.query(
+            "conn",
+            &AuditFilter {
+                page: Some(2),
+                limit: Some(1),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!((page.total, page.changes.len()), (2, 1));
+        assert_eq!(page.changes[0].id, inserted);
+        assert_eq!(page.changes[0].rows[0].after, Some(json!({ "id": 1 })));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::{
+    base::{
+        config::{ConnectionConfig, SavedConnection},
+        user::Role,
+        BasableError,
+    },
+    utils::crypto,
+};
+
+use super::LocalDB;
+
+/// Table of the roles users have on saved connections, as `(conn_id, user_id, rank)`. A user
+/// can have several roles on a connection, through teams. The lowest rank is the user's
+/// role, see [`from_rank`].
+const ROLES: &str = "
+    WITH roles (conn_id, user_id, rank) AS (
+        SELECT id, user_id, 0 FROM connections
+        UNION ALL
+        SELECT conn_id, user_id, CASE role WHEN 'editor' THEN 1 ELSE 2 END
+        FROM connection_members
+        UNION ALL
+        SELECT tc.conn_id, tm.user_id, CASE tm.role WHEN 'editor' THEN 1 ELSE 2 END
+        FROM team_connections tc JOIN team_members tm ON tm.team_id = tc.team_id
+    )";
+
+fn from_rank(rank: i64) -> Role {
+    match rank {
+        0 => Role::Owner,
+        1 => Role::Editor,
+        _ => Role::Viewer,
+    }
+}
+
+/// Repository of [`SavedConnection`]s. Passwords are stored encrypted, apart from the rest
+/// of the [`ConnectionConfig`].
+pub(crate) struct Connections<'a>(pub(super) &'a LocalDB);
+
+impl Connections<'_> {
+    /// Connections saved by the user or shared with the user, without their passwords.
+    pub fn list(&self, user_id: &str) -> Result<Vec<SavedConnection>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(&format!(
+            "{ROLES}
+            SELECT c.id, c.name, c.config, c.created_at, c.updated_at, MIN(r.rank)
+            FROM connections c JOIN roles r ON r.conn_id = c.id
+            WHERE r.user_id = ?1 GROUP BY c.id ORDER BY c.created_at, c.name"
+        ))?;
+
+        let connections = stmt
+            .query_map(params![user_id], |r| Ok(Self::from_row(r)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        connections.into_iter().collect()
+    }
+
+    /// Connection `id` of the user, with its decrypted password.
+    pub fn get(&self, user_id: &str, id: &str) -> Result<Option<SavedConnection>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let row = conn
+            .query_row(
+                "SELECT id, name, config, created_at, updated_at, 0, password FROM connections
+                WHERE user_id = ?1 AND id = ?2",
+                params![user_id, id],
+                |r| Ok((Self::from_row(r), r.get::<_, Option<String>>(6)?)),
+            )
+            .optional()?;
+
+        let Some((saved, password)) = row else {
+            return Ok(None);
+        };
+
+        let mut saved = saved?;
+        saved.config.password = password.map(|p| crypto::decrypt(&p)).transpose()?;
+
+        Ok(Some(saved))
+    }
+
+    /// Id of the user who saved connection `id`.
+    pub fn owner(&self, id: &str) -> Result<Option<String>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let owner = conn
+            .query_row(
+                "SELECT user_id FROM connections WHERE id = ?1",
+                params![id],
+                |r| r.get(0),
+            )
+            .optional()?;
+
+        Ok(owner)
+    }
+
+    /// Role of the user on saved connection `id`, if the user has one.
+    pub fn role(&self, id: &str, user_id: &str) -> Result<Option<Role>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let rank: Option<i64> = conn.query_row(
+            &format!("{ROLES} SELECT MIN(rank) FROM roles WHERE conn_id = ?1 AND user_id = ?2"),
+            params![id, user_id],
+            |r| r.get(0),
+        )?;
+
+        Ok(rank.map(from_rank))
+    }
+
+    /// Saves `config` as connection `id` of the user. The name of a connection that is
+    /// already saved is kept.
+    pub fn save(
+        &self,
+        user_id: &str,
+        id: &str,
+        name: &str,
+        config: &ConnectionConfig,
+    ) -> Result<(), BasableError> {
+        let password = config.password.as_deref().map(crypto::encrypt);
+        let config = serde_json::to_string(config).unwrap();
+        let conn = self.0.pool()?;
+
+        conn.execute(
+            "INSERT INTO connections (id, user_id, name, config, password)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (id) DO UPDATE SET
+                config = excluded.config,
+                password = excluded.password,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE user_id = excluded.user_id",
+            params![id, user_id, name, config, password],
+        )?;
+
+        Ok(())
+    }
+
+    /// Renames connection `id` of the user. Returns `false` if the user has no such
+    /// connection.
+    pub fn rename(&self, user_id: &str, id: &str, name: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let updated = conn.execute(
+            "UPDATE connections SET name = ?3, updated_at = CURRENT_TIMESTAMP
+            WHERE user_id = ?1 AND id = ?2",
+            params![user_id, id, name],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    /// Deletes connection `id` of the user, along with its members, its shares with teams
+    /// and the table configurations of its users. Returns `false` if the user has no such connection.
+    pub fn delete(&self, user_id: &str, id: &str) -> Result<bool, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        let deleted = tx.execute(
+            "DELETE FROM connections WHERE user_id = ?1 AND id = ?2",
+            params![user_id, id],
+        )?;
+        if deleted > 0 {
+            tx.execute("DELETE FROM table_configs WHERE conn_id = ?1", params![id])?;
+            tx.execute(
+                "DELETE FROM connection_members WHERE conn_id = ?1",
+                params![id],
+            )?;
+            tx.execute(
+                "DELETE FROM team_connections WHERE conn_id = ?1",
+                params![id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn from_row(r: &Row) -> Result<SavedConnection, BasableError> {
+        let config: String = r.get(2)?;
+        let config =
+            serde_json::from_str(&config).map_err(|err| BasableError::Driver(err.to_string()))?;
+
+        Ok(SavedConnection {
+            id: r.get(0)?,
+            name: r.get(1)?,
+            config,
+            created_at: r.get(3)?,
+            updated_at: r.get(4)?,
+            role: from_rank(r.get(5)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dotenv::dotenv;
+
+    use crate::base::{
+        config::ConnectionConfig, data::table::TableConfig, local::LocalDB, BasableError,
+    };
+
+    #[test]
+    fn test_connections() -> Result<(), BasableError> {
+        dotenv().ok();
+
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let config = ConnectionConfig {
+            db_name: Some("basable".to_string()),
+            password: Some("p@ssword".to_string()),
+            ..Default::default()
+        };
+
+        let connections = db.connections();
+        connections.save("user", "conn", "basable (mysql)", &config)?;
+
+        // Passwords are encrypted at rest and not listed.
+        let stored: String = db.pool()?.query_row(
+            "SELECT config || password FROM connections WHERE id = 'conn'",
+            [],
+            |r| r.get(0),
+        )?;
+        assert!(!stored.contains("p@ssword"));
+
+        let list = connections.list("user")?;
+        assert_eq!(list.len(), 1);
+        assert!(list[0].config.password.is_none());
+
+        let saved = connections.get("user", "conn")?.unwrap();
+        assert_eq!(saved.config.password.as_deref(), Some("p@ssword"));
+
+        // Connections belong to a user.
+        assert!(connections.get("other_user", "conn")?.is_none());
+        assert!(!connections.rename("other_user", "conn", "mine")?);
+        connections.save("other_user", "conn", "mine", &config)?;
+        assert_eq!(connections.list("other_user")?.len(), 0);
+
+        assert!(connections.rename("user", "conn", "Sales")?);
+        assert_eq!(connections.list("user")?[0].name, "Sales");
+
+        let table_config = TableConfig::default();
+        db.table_configs()
+            .save("user", "conn", "vgchartz", &table_config)?;
+
+        assert!(connections.delete("user", "conn")?);
+        assert!(connections.get("user", "conn")?.is_none());
+        assert!(db
+            .table_configs()
+            .get("user", "conn", "vgchartz")?
+            .is_none());
+        assert!(!connections.delete("user", "conn")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::base::{user::Role, BasableError};
+
+use super::LocalDB;
+
+/// A pending invitation of a registered user to a team.
+#[derive(Serialize)]
+pub(crate) struct Invitation {
+    pub id: String,
+    pub team_id: String,
+    pub team_name: String,
+    pub inviter_id: String,
+    pub role: Role,
+    pub created_at: String,
+}
+
+/// Repository of invitations to teams. A user has at most one invitation per team, and
+/// becomes a member of the team by accepting it.
+pub(crate) struct Invitations<'a>(pub(super) &'a LocalDB);
+
+impl Invitations<'_> {
+    /// Invites the user to team `team_id` with `role`, replacing any pending invitation of
+    /// the user to the team. Returns the invitation.
+    pub fn create(
+        &self,
+        team_id: &str,
+        inviter_id: &str,
+        invitee_id: &str,
+        role: Role,
+    ) -> Result<Invitation, BasableError> {
+        if role == Role::Owner {
+            let msg = "A team can only have one owner.".to_string();
+            return Err(BasableError::Query(msg));
+        }
+
+        let conn = self.0.pool()?;
+        conn.execute(
+            "INSERT INTO invitations (id, team_id, inviter_id, invitee_id, role)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (team_id, invitee_id)
+            DO UPDATE SET inviter_id = excluded.inviter_id, role = excluded.role",
+            params![
+                Uuid::new_v4().to_string(),
+                team_id,
+                inviter_id,
+                invitee_id,
+                role.as_str()
+            ],
+        )?;
+        drop(conn);
+
+        let mut invitations = self.query(
+            "i.team_id = ?1 AND i.invitee_id = ?2",
+            params![team_id, invitee_id],
+        )?;
+        invitations
+            .pop()
+            .ok_or_else(|| BasableError::Driver("Invitation was not saved.".to_string()))
+    }
+
+    /// Pending invitations of the user.
+    pub fn list(&self, invitee_id: &str) -> Result<Vec<Invitation>, BasableError> {
+        self.query("i.invitee_id = ?1", params![invitee_id])
+    }
+
+    /// Makes the user a member of the team of invitation `id`, with the invitation's role.
+    /// Returns the team's id, or `None` if the user has no such invitation.
+    pub fn accept(&self, id: &str, invitee_id: &str) -> Result<Option<String>, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        let invitation: Option<(String, String)> = tx
+            .query_row(
+                "DELETE FROM invitations WHERE id = ?1 AND invitee_id = ?2
+                RETURNING team_id, role",
+                params![id, invitee_id],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()?;
+
+        let Some((team_id, role)) = invitation else {
+            return Ok(None);
+        };
+
+        // The owner of a team keeps their membership.
+        tx.execute(
+            "INSERT INTO team_members (team_id, user_id, role) VALUES (?1, ?2, ?3)
+            ON CONFLICT (team_id, user_id) DO UPDATE SET role = excluded.role
+            WHERE user_id NOT IN (SELECT owner_id FROM teams WHERE id = excluded.team_id)",
+            params![team_id, invitee_id, role],
+        )?;
+        tx.commit()?;
+
+        Ok(Some(team_id))
+    }
+
+    /// Deletes invitation `id` of the user. Returns `false` if the user has no such
+    /// invitation.
+    pub fn decline(&self, id: &str, invitee_id: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let deleted = conn.execute(
+            "DELETE FROM invitations WHERE id = ?1 AND invitee_id = ?2",
+            params![id, invitee_id],
+        )?;
+
+        Ok(deleted > 0)
+    }
+
+    fn query(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Invitation>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT i.id, i.team_id, t.name, i.inviter_id, i.role, i.created_at
+            FROM invitations i JOIN teams t ON t.id = i.team_id
+            WHERE {condition} ORDER BY i.created_at, t.name"
+        ))?;
+
+        let rows = stmt
+            .query_map(params, |r| {
+                Ok((
+                    r.get(0)?,
+                    r.get(1)?,
+                    r.get(2)?,
+                    r.get(3)?,
+                    r.get::<_, String>(4)?,
+                    r.get(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, team_id, team_name, inviter_id, role, created_at)| {
+                Ok(Invitation {
+                    id,
+                    team_id,
+                    team_name,
+                    inviter_id,
+                    role: Role::try_from(role.as_str()).map_err(BasableError::Driver)?,
+                    created_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{local::LocalDB, user::Role, BasableError};
+
+    #[test]
+    fn test_invitations() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let team = db.teams().create("owner", "Analytics")?;
+        let invitations = db.invitations();
+
+        let invitation = invitations.create(&team.id, "owner", "analyst", Role::Viewer)?;
+        assert_eq!(invitation.team_name, "Analytics");
+        assert!(invitations
+            .create(&team.id, "owner", "analyst", Role::Owner)
+            .is_err());
+
+        // Inviting the user again replaces the pending invitation.
+        let updated = invitations.create(&team.id, "owner", "analyst", Role::Editor)?;
+        assert_eq!(updated.id, invitation.id);
+        let pending = invitations.list("analyst")?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].role, Role::Editor);
+
+        assert!(!invitations.decline(&invitation.id, "someone")?);
+        assert!(invitations.decline(&invitation.id, "analyst")?);
+        assert!(invitations.list("analyst")?.is_empty());
+        assert!(invitations.accept(&invitation.id, "analyst")?.is_none());
+
+        Ok(())
+    }
+}
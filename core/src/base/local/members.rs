@@ -0,0 +1,121 @@
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::base::{user::Role, BasableError};
+
+use super::LocalDB;
+
+/// A registered user with a role on a connection they don't own.
+#[derive(Serialize)]
+pub(crate) struct Member {
+    pub user_id: String,
+    pub email: String,
+    pub username: Option<String>,
+    pub role: Role,
+}
+
+/// Repository of the roles users have on saved connections they don't own. Owners are not
+/// members, see [`Connections::owner`](super::connections::Connections::owner). The role of
+/// a user on a connection is read with [`Connections::role`](super::connections::Connections::role).
+pub(crate) struct Members<'a>(pub(super) &'a LocalDB);
+
+impl Members<'_> {
+    /// Gives the user `role` on connection `conn_id`, replacing their current role.
+    /// [`Role::Owner`] can't be given.
+    pub fn set(&self, conn_id: &str, user_id: &str, role: Role) -> Result<(), BasableError> {
+        if role == Role::Owner {
+            let msg = "A connection can only have one owner.".to_string();
+            return Err(BasableError::Query(msg));
+        }
+
+        let conn = self.0.pool()?;
+        conn.execute(
+            "INSERT INTO connection_members (conn_id, user_id, role) VALUES (?1, ?2, ?3)
+            ON CONFLICT (conn_id, user_id) DO UPDATE SET role = excluded.role",
+            params![conn_id, user_id, role.as_str()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes the user from connection `conn_id`. Returns `false` if the user is not a
+    /// member.
+    pub fn remove(&self, conn_id: &str, user_id: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let removed = conn.execute(
+            "DELETE FROM connection_members WHERE conn_id = ?1 AND user_id = ?2",
+            params![conn_id, user_id],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    pub fn list(&self, conn_id: &str) -> Result<Vec<Member>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(
+            "SELECT m.user_id, u.email, u.username, m.role FROM connection_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.conn_id = ?1 ORDER BY m.created_at, u.email",
+        )?;
+
+        let rows = stmt
+            .query_map(params![conn_id], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, String>(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(user_id, email, username, role)| {
+                Ok(Member {
+                    user_id,
+                    email,
+                    username,
+                    role: Role::try_from(role.as_str()).map_err(BasableError::Driver)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{
+        local::LocalDB,
+        user::{Account, Role},
+        BasableError,
+    };
+
+    #[test]
+    fn test_members() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        db.users().create(&Account {
+            id: "member".to_string(),
+            email: "member@example.com".to_string(),
+            username: None,
+            password_hash: "hash".to_string(),
+        })?;
+
+        let members = db.members();
+        assert!(db.connections().role("conn", "member")?.is_none());
+
+        members.set("conn", "member", Role::Viewer)?;
+        assert_eq!(db.connections().role("conn", "member")?, Some(Role::Viewer));
+        members.set("conn", "member", Role::Editor)?;
+        assert_eq!(db.connections().role("conn", "member")?, Some(Role::Editor));
+        assert!(members.set("conn", "member", Role::Owner).is_err());
+
+        let list = members.list("conn")?;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].email, "member@example.com");
+        assert!(members.list("other_conn")?.is_empty());
+
+        assert!(members.remove("conn", "member")?);
+        assert!(db.connections().role("conn", "member")?.is_none());
+        assert!(!members.remove("conn", "member")?);
+
+        Ok(())
+    }
+}
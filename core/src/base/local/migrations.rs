@@ -0,0 +1,195 @@
+use rusqlite::Connection;
+
+use crate::base::BasableError;
+
+/// Schema migrations of the local store, in the order they are applied. Migrations must
+/// never be edited or removed once released. Changes to the schema are new migrations.
+const MIGRATIONS: &[&str] = &[
+    // 1: table configurations
+    "
+    CREATE TABLE table_configs (
+        user_id TEXT NOT NULL,
+        conn_id TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        config TEXT NOT NULL,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, conn_id, table_name)
+    );
+    ",
+    // 2: saved connections
+    "
+    CREATE TABLE connections (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        config TEXT NOT NULL,
+        password TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX connections_user_id ON connections (user_id);
+    ",
+    // 3: user accounts and sessions
+    "
+    CREATE TABLE users (
+        id TEXT PRIMARY KEY,
+        email TEXT NOT NULL UNIQUE,
+        username TEXT UNIQUE COLLATE NOCASE,
+        password_hash TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE sessions (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,
+        is_guest INTEGER NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX sessions_user_id ON sessions (user_id);
+    ",
+    // 4: refresh tokens and revoked access tokens. Sessions started before have no refresh
+    // token and their access tokens have no id, so they are dropped.
+    "
+    DELETE FROM sessions;
+    ALTER TABLE sessions ADD COLUMN refresh_hash TEXT NOT NULL DEFAULT '';
+    ALTER TABLE sessions ADD COLUMN access_jti TEXT NOT NULL DEFAULT '';
+    ALTER TABLE sessions ADD COLUMN access_expires_at INTEGER NOT NULL DEFAULT 0;
+    CREATE UNIQUE INDEX sessions_refresh_hash ON sessions (refresh_hash);
+    CREATE TABLE revoked_tokens (
+        jti TEXT PRIMARY KEY,
+        expires_at INTEGER NOT NULL
+    );
+    ",
+    // 5: roles of users on connections they don't own
+    "
+    CREATE TABLE connection_members (
+        conn_id TEXT NOT NULL,
+        user_id TEXT NOT NULL,
+        role TEXT NOT NULL CHECK (role IN ('editor', 'viewer')),
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (conn_id, user_id)
+    );
+    CREATE INDEX connection_members_user_id ON connection_members (user_id);
+    ",
+    // 6: teams, their shared connections and invitations
+    "
+    CREATE TABLE teams (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        owner_id TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE team_members (
+        team_id TEXT NOT NULL REFERENCES teams (id) ON DELETE CASCADE,
+        user_id TEXT NOT NULL,
+        role TEXT NOT NULL CHECK (role IN ('editor', 'viewer')),
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (team_id, user_id)
+    );
+    CREATE INDEX team_members_user_id ON team_members (user_id);
+    CREATE TABLE team_connections (
+        team_id TEXT NOT NULL REFERENCES teams (id) ON DELETE CASCADE,
+        conn_id TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (team_id, conn_id)
+    );
+    CREATE INDEX team_connections_conn_id ON team_connections (conn_id);
+    CREATE TABLE invitations (
+        id TEXT PRIMARY KEY,
+        team_id TEXT NOT NULL REFERENCES teams (id) ON DELETE CASCADE,
+        inviter_id TEXT NOT NULL,
+        invitee_id TEXT NOT NULL,
+        role TEXT NOT NULL CHECK (role IN ('editor', 'viewer')),
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (team_id, invitee_id)
+    );
+    CREATE INDEX invitations_invitee_id ON invitations (invitee_id);
+    ",
+    // 7: API keys
+    "
+    CREATE TABLE api_keys (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        prefix TEXT NOT NULL,
+        key_hash TEXT NOT NULL UNIQUE,
+        access TEXT NOT NULL CHECK (access IN ('read', 'write')),
+        connections TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        last_used_at TEXT,
+        revoked_at TEXT
+    );
+    CREATE INDEX api_keys_user_id ON api_keys (user_id);
+    ",
+    // 8: audit log of changes to data and table configurations
+    "
+    CREATE TABLE audit_changes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id TEXT NOT NULL,
+        conn_id TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        operation TEXT NOT NULL CHECK (operation IN ('insert', 'update', 'delete', 'configure')),
+        pk TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX audit_changes_conn_id ON audit_changes (conn_id, table_name);
+    CREATE TABLE audit_rows (
+        change_id INTEGER NOT NULL REFERENCES audit_changes (id) ON DELETE CASCADE,
+        pk_value TEXT,
+        before TEXT,
+        after TEXT
+    );
+    CREATE INDEX audit_rows_change_id ON audit_rows (change_id);
+    ",
+];
+
+/// Applies the migrations that have not been applied to `conn` yet, in a single transaction.
+/// The number of applied migrations is the schema version, which is kept in SQLite's
+/// `user_version`.
+///
+/// A store whose schema is newer than this version of Basable is not modified.
+pub(crate) fn migrate(conn: &mut Connection) -> Result<(), BasableError> {
+    let tx = conn.transaction()?;
+    let version: usize = tx.pragma_query_value(None, "user_version", |r| r.get(0))?;
+
+    if version > MIGRATIONS.len() {
+        let msg = format!(
+            "The local database schema (version {version}) is newer than this version of Basable supports (version {}).",
+            MIGRATIONS.len()
+        );
+        return Err(BasableError::Connection(msg));
+    }
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::{migrate, MIGRATIONS};
+
+    #[test]
+    fn test_migrate() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        migrate(&mut conn).unwrap();
+        // Applied migrations are skipped.
+        migrate(&mut conn).unwrap();
+
+        let version: usize = conn
+            .pragma_query_value(None, "user_version", |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
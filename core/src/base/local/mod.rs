@@ -0,0 +1,191 @@
+use std::{env, fs, path::Path};
+
+use api_keys::ApiKeys;
+use audit::Audit;
+use connections::Connections;
+use invitations::Invitations;
+use members::Members;
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use sessions::Sessions;
+use table_configs::TableConfigs;
+use teams::Teams;
+use users::Users;
+
+use super::BasableError;
+
+pub(crate) mod api_keys;
+pub(crate) mod audit;
+pub(crate) mod connections;
+pub(crate) mod invitations;
+pub(crate) mod members;
+pub(crate) mod migrations;
+pub(crate) mod sessions;
+pub(crate) mod table_configs;
+pub(crate) mod teams;
+pub(crate) mod users;
+
+/// Path of the local store when `BASABLE_LOCAL_DB_PATH` is not set.
+pub(crate) const DEFAULT_LOCAL_DB_PATH: &str = "basable.sqlite3";
+
+/// Path of the local store, `BASABLE_LOCAL_DB_PATH` or [`DEFAULT_LOCAL_DB_PATH`].
+pub(crate) fn local_db_path() -> String {
+    env::var("BASABLE_LOCAL_DB_PATH").unwrap_or(DEFAULT_LOCAL_DB_PATH.to_string())
+}
+
+/// Settings applied to every connection of the store.
+const CONNECTION_PRAGMAS: &str = "
+    PRAGMA foreign_keys = ON;
+    PRAGMA busy_timeout = 5000;
+";
+
+/// Local SQLite store of Basable's own data, such as [`TableConfig`](crate::base::data::table::TableConfig)s.
+/// Each kind of data is read and written through its repository, for example
+/// [`LocalDB::table_configs`].
+#[derive(Clone)]
+pub(crate) struct LocalDB(pub Pool<SqliteConnectionManager>);
+
+impl LocalDB {
+    /// Opens the store at `path`, creating the file and its directory if they don't exist.
+    /// The store is journaled with WAL, so that reads and writes don't block each other.
+    pub fn open(path: &str) -> Result<Self, BasableError> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir).map_err(|err| BasableError::Connection(err.to_string()))?;
+        }
+
+        let manager = SqliteConnectionManager::file(path).with_init(|c| {
+            c.execute_batch(CONNECTION_PRAGMAS)?;
+            c.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+        });
+        let pool = Pool::new(manager)?;
+
+        Ok(LocalDB(pool))
+    }
+
+    /// Creates an in-memory store. Every in-memory SQLite connection is a separate database,
+    /// so the pool keeps a single connection open for the lifetime of the store.
+    pub fn memory() -> Result<Self, BasableError> {
+        let manager =
+            SqliteConnectionManager::memory().with_init(|c| c.execute_batch(CONNECTION_PRAGMAS));
+        let pool = Pool::builder()
+            .max_size(1)
+            .max_lifetime(None)
+            .idle_timeout(None)
+            .build(manager)?;
+
+        Ok(LocalDB(pool))
+    }
+
+    pub(crate) fn pool(&self) -> Result<PooledConnection<SqliteConnectionManager>, BasableError> {
+        Ok(self.0.get()?)
+    }
+
+    /// Checks the store for corruption. Basable should not start with a corrupted store,
+    /// since writing to it could lose more data.
+    pub fn check_integrity(&self) -> Result<(), BasableError> {
+        let conn = self.pool()?;
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let errors = stmt
+            .query_map([], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if errors != ["ok"] {
+            let msg = format!("The local database is corrupted: {}", errors.join("; "));
+            return Err(BasableError::Connection(msg));
+        }
+
+        Ok(())
+    }
+
+    /// Brings the store's schema up to date, see [`migrations::migrate`].
+    pub fn migrate(&self) -> Result<(), BasableError> {
+        let mut conn = self.pool()?;
+        migrations::migrate(&mut conn)?;
+
+        Ok(())
+    }
+
+    pub fn api_keys(&self) -> ApiKeys<'_> {
+        ApiKeys(self)
+    }
+
+    pub fn audit(&self) -> Audit<'_> {
+        Audit(self)
+    }
+
+    pub fn connections(&self) -> Connections<'_> {
+        Connections(self)
+    }
+
+    pub fn invitations(&self) -> Invitations<'_> {
+        Invitations(self)
+    }
+
+    pub fn members(&self) -> Members<'_> {
+        Members(self)
+    }
+
+    pub fn sessions(&self) -> Sessions<'_> {
+        Sessions(self)
+    }
+
+    pub fn table_configs(&self) -> TableConfigs<'_> {
+        TableConfigs(self)
+    }
+
+    pub fn teams(&self) -> Teams<'_> {
+        Teams(self)
+    }
+
+    pub fn users(&self) -> Users<'_> {
+        Users(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::base::{data::table::TableConfig, BasableError};
+
+    use super::LocalDB;
+
+    #[test]
+    fn test_open_local_db() -> Result<(), BasableError> {
+        let dir = env::temp_dir().join(format!("basable_local_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("basable.sqlite3");
+        let path = path.to_str().unwrap();
+
+        let config = TableConfig {
+            table_id: "vgchartz".to_string(),
+            pk: Some("id".to_string()),
+            ..Default::default()
+        };
+
+        {
+            let db = LocalDB::open(path)?;
+            db.check_integrity()?;
+            db.migrate()?;
+
+            let mode: String = db
+                .pool()?
+                .pragma_query_value(None, "journal_mode", |r| r.get(0))?;
+            assert_eq!(mode, "wal");
+
+            db.table_configs()
+                .save("user", "conn", "vgchartz", &config)?;
+        }
+
+        // Data is kept when the store is opened again.
+        let db = LocalDB::open(path)?;
+        db.check_integrity()?;
+        db.migrate()?;
+        let saved = db.table_configs().get("user", "conn", "vgchartz")?;
+        assert_eq!(saved.and_then(|c| c.pk).as_deref(), Some("id"));
+
+        drop(db);
+        fs::remove_dir_all(&dir).unwrap();
+
+        Ok(())
+    }
+}
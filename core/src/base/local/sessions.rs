@@ -0,0 +1,225 @@
+use rusqlite::{params, OptionalExtension, Transaction};
+use uuid::Uuid;
+
+use crate::base::BasableError;
+
+use super::LocalDB;
+
+/// A login session. The session's refresh token is kept as a hash, along with the id of the
+/// last access token issued for it.
+pub(crate) struct Session {
+    pub id: String,
+    pub user_id: String,
+    pub is_guest: bool,
+}
+
+/// An access token issued for a session. `expires_at` is a Unix timestamp.
+pub(crate) struct AccessToken<'a> {
+    pub jti: &'a str,
+    pub expires_at: i64,
+}
+
+/// Repository of login sessions and revoked access tokens. A session lasts until its refresh
+/// token expires or it is ended. Ending a session revokes its access token.
+pub(crate) struct Sessions<'a>(pub(super) &'a LocalDB);
+
+impl Sessions<'_> {
+    /// Starts a session of the user, whose refresh token expires at `expires_at`. Returns
+    /// the session's id.
+    pub fn create(
+        &self,
+        user_id: &str,
+        is_guest: bool,
+        access: &AccessToken,
+        refresh_hash: &str,
+        expires_at: i64,
+    ) -> Result<String, BasableError> {
+        let id = Uuid::new_v4().to_string();
+        let conn = self.0.pool()?;
+
+        conn.execute_batch(
+            "DELETE FROM sessions WHERE expires_at <= unixepoch();
+            DELETE FROM revoked_tokens WHERE expires_at <= unixepoch();",
+        )?;
+        conn.execute(
+            "INSERT INTO sessions
+            (id, user_id, is_guest, refresh_hash, access_jti, access_expires_at, expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                user_id,
+                is_guest,
+                refresh_hash,
+                access.jti,
+                access.expires_at,
+                expires_at
+            ],
+        )?;
+
+        Ok(id)
+    }
+
+    /// Replaces the tokens of the session whose refresh token hashes to `refresh_hash`, and
+    /// extends it until `expires_at`. Its previous access token is revoked. Returns `None`
+    /// if no active session has the refresh token.
+    pub fn refresh(
+        &self,
+        refresh_hash: &str,
+        access: &AccessToken,
+        new_refresh_hash: &str,
+        expires_at: i64,
+    ) -> Result<Option<Session>, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        let session = tx
+            .query_row(
+                "SELECT id, user_id, is_guest FROM sessions
+                WHERE refresh_hash = ?1 AND expires_at > unixepoch()",
+                params![refresh_hash],
+                |r| {
+                    Ok(Session {
+                        id: r.get(0)?,
+                        user_id: r.get(1)?,
+                        is_guest: r.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        let Some(session) = session else {
+            return Ok(None);
+        };
+
+        Self::revoke_access(&tx, "id = ?1", &session.id)?;
+        tx.execute(
+            "UPDATE sessions SET refresh_hash = ?2, access_jti = ?3, access_expires_at = ?4,
+            expires_at = ?5 WHERE id = ?1",
+            params![
+                session.id,
+                new_refresh_hash,
+                access.jti,
+                access.expires_at,
+                expires_at
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(Some(session))
+    }
+
+    /// Ends session `id` of the user. Returns `false` if the session doesn't exist.
+    pub fn end(&self, id: &str, user_id: &str) -> Result<bool, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        Self::revoke_access(&tx, "id = ?1", id)?;
+        let ended = tx.execute(
+            "DELETE FROM sessions WHERE id = ?1 AND user_id = ?2",
+            params![id, user_id],
+        )?;
+
+        if ended == 0 {
+            // Not a session of the user, so its access token is not revoked.
+            return Ok(false);
+        }
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Ends all sessions of the user. Returns the number of sessions ended.
+    pub fn end_all(&self, user_id: &str) -> Result<usize, BasableError> {
+        let mut conn = self.0.pool()?;
+        let tx = conn.transaction()?;
+
+        Self::revoke_access(&tx, "user_id = ?1", user_id)?;
+        let ended = tx.execute("DELETE FROM sessions WHERE user_id = ?1", params![user_id])?;
+
+        tx.commit()?;
+        Ok(ended)
+    }
+
+    /// Whether the access token with id `jti` is revoked.
+    pub fn is_revoked(&self, jti: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let revoked = conn
+            .query_row(
+                "SELECT 1 FROM revoked_tokens WHERE jti = ?1",
+                params![jti],
+                |_| Ok(()),
+            )
+            .optional()?;
+
+        Ok(revoked.is_some())
+    }
+
+    /// Revokes the current access tokens of the sessions matching `condition`.
+    fn revoke_access(tx: &Transaction, condition: &str, value: &str) -> Result<(), BasableError> {
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO revoked_tokens (jti, expires_at)
+                SELECT access_jti, access_expires_at FROM sessions WHERE {condition}"
+            ),
+            params![value],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::base::{local::LocalDB, BasableError};
+
+    use super::AccessToken;
+
+    #[test]
+    fn test_sessions() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let sessions = db.sessions();
+        let now = Utc::now().timestamp();
+        let access = |jti| AccessToken {
+            jti,
+            expires_at: now + 60,
+        };
+
+        let id = sessions.create("user", false, &access("a1"), "r1", now + 600)?;
+        sessions.create("user", false, &access("b1"), "s1", now + 600)?;
+        sessions.create("user", false, &access("c1"), "t1", now - 1)?;
+
+        // Refresh tokens are replaced, and the previous access token is revoked.
+        let session = sessions.refresh("r1", &access("a2"), "r2", now + 600)?;
+        assert_eq!(session.unwrap().id, id);
+        assert!(sessions.is_revoked("a1")?);
+        assert!(!sessions.is_revoked("a2")?);
+        assert!(sessions
+            .refresh("r1", &access("a3"), "r3", now + 600)?
+            .is_none());
+        // Expired sessions can't be refreshed.
+        assert!(sessions
+            .refresh("t1", &access("c2"), "t2", now + 600)?
+            .is_none());
+
+        assert!(!sessions.end(&id, "other_user")?);
+        assert!(!sessions.is_revoked("a2")?);
+        assert!(sessions.end(&id, "user")?);
+        assert!(sessions.is_revoked("a2")?);
+        assert!(sessions
+            .refresh("r2", &access("a3"), "r3", now + 600)?
+            .is_none());
+
+        assert_eq!(sessions.end_all("user")?, 2);
+        assert!(sessions.is_revoked("b1")?);
+        assert!(sessions
+            .refresh("s1", &access("b2"), "s2", now + 600)?
+            .is_none());
+
+        Ok(())
+    }
+}
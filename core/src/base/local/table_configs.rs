@@ -0,0 +1,112 @@
+use rusqlite::{params, OptionalExtension};
+
+use crate::base::{data::table::TableConfig, BasableError};
+
+use super::LocalDB;
+
+/// Repository of [`TableConfig`]s. A configuration is kept per user, connection and table.
+pub(crate) struct TableConfigs<'a>(pub(super) &'a LocalDB);
+
+impl TableConfigs<'_> {
+    pub fn get(
+        &self,
+        user_id: &str,
+        conn_id: &str,
+        table_name: &str,
+    ) -> Result<Option<TableConfig>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let config: Option<String> = conn
+            .query_row(
+                "SELECT config FROM table_configs
+                WHERE user_id = ?1 AND conn_id = ?2 AND table_name = ?3",
+                params![user_id, conn_id, table_name],
+                |r| r.get(0),
+            )
+            .optional()?;
+
+        config
+            .map(|c| serde_json::from_str(&c))
+            .transpose()
+            .map_err(|err| BasableError::Driver(err.to_string()))
+    }
+
+    /// Saves `config`, replacing the table's existing configuration.
+    pub fn save(
+        &self,
+        user_id: &str,
+        conn_id: &str,
+        table_name: &str,
+        config: &TableConfig,
+    ) -> Result<(), BasableError> {
+        let config = serde_json::to_string(config).unwrap();
+        let conn = self.0.pool()?;
+
+        conn.execute(
+            "INSERT INTO table_configs (user_id, conn_id, table_name, config)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (user_id, conn_id, table_name)
+            DO UPDATE SET config = excluded.config, updated_at = CURRENT_TIMESTAMP",
+            params![user_id, conn_id, table_name, config],
+        )?;
+
+        Ok(())
+    }
+
+    /// Saves `config` only if the table has no configuration yet. Returns `true` if `config`
+    /// was saved.
+    pub fn save_default(
+        &self,
+        user_id: &str,
+        conn_id: &str,
+        table_name: &str,
+        config: &TableConfig,
+    ) -> Result<bool, BasableError> {
+        let config = serde_json::to_string(config).unwrap();
+        let conn = self.0.pool()?;
+
+        let saved = conn.execute(
+            "INSERT OR IGNORE INTO table_configs (user_id, conn_id, table_name, config)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![user_id, conn_id, table_name, config],
+        )?;
+
+        Ok(saved > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{data::table::TableConfig, local::LocalDB, BasableError};
+
+    #[test]
+    fn test_table_configs() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let configs = db.table_configs();
+        assert!(configs.get("user", "conn", "vgchartz")?.is_none());
+
+        let mut config = TableConfig {
+            table_id: "vgchartz".to_string(),
+            pk: Some("id".to_string()),
+            ..Default::default()
+        };
+        assert!(configs.save_default("user", "conn", "vgchartz", &config)?);
+
+        // An existing configuration is not replaced by a default one.
+        config.pk = Some("title".to_string());
+        assert!(!configs.save_default("user", "conn", "vgchartz", &config)?);
+        let saved = configs.get("user", "conn", "vgchartz")?.unwrap();
+        assert_eq!(saved.pk.as_deref(), Some("id"));
+
+        configs.save("user", "conn", "vgchartz", &config)?;
+        let saved = configs.get("user", "conn", "vgchartz")?.unwrap();
+        assert_eq!(saved.pk.as_deref(), Some("title"));
+
+        // Configurations belong to a user.
+        assert!(configs.get("other_user", "conn", "vgchartz")?.is_none());
+
+        Ok(())
+    }
+}
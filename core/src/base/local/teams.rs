@@ -0,0 +1,248 @@
+use rusqlite::params;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::base::{user::Role, BasableError};
+
+use super::{members::Member, LocalDB};
+
+/// A team of registered users, with the role of the user it was read for. Members of a team
+/// have their team role on every connection shared with the team.
+#[derive(Serialize)]
+pub(crate) struct Team {
+    pub id: String,
+    pub name: String,
+    pub owner_id: String,
+    pub role: Role,
+}
+
+/// Repository of teams, their members and the connections shared with them. The owner of a
+/// team is also its member, with the `editor` role.
+pub(crate) struct Teams<'a>(pub(super) &'a LocalDB);
+
+impl Teams<'_> {
+    pub fn create(&self, owner_id: &str, name: &str) -> Result<Team, BasableError> {
+        let id = Uuid::new_v4().to_string();
+        let mut conn = self.0.pool()?;
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO teams (id, name, owner_id) VALUES (?1, ?2, ?3)",
+            params![id, name, owner_id],
+        )?;
+        tx.execute(
+            "INSERT INTO team_members (team_id, user_id, role) VALUES (?1, ?2, ?3)",
+            params![id, owner_id, Role::Editor.as_str()],
+        )?;
+        tx.commit()?;
+
+        Ok(Team {
+            id,
+            name: name.to_string(),
+            owner_id: owner_id.to_string(),
+            role: Role::Owner,
+        })
+    }
+
+    /// Team `id`, if the user is a member.
+    pub fn get(&self, id: &str, user_id: &str) -> Result<Option<Team>, BasableError> {
+        Ok(self
+            .query("t.id = ?1 AND m.user_id = ?2", params![id, user_id])?
+            .pop())
+    }
+
+    /// Teams the user is a member of.
+    pub fn list(&self, user_id: &str) -> Result<Vec<Team>, BasableError> {
+        self.query("m.user_id = ?1", params![user_id])
+    }
+
+    /// Deletes team `id` of the owner, along with its members, shares and invitations.
+    /// Returns `false` if the owner has no such team.
+    pub fn delete(&self, id: &str, owner_id: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let deleted = conn.execute(
+            "DELETE FROM teams WHERE id = ?1 AND owner_id = ?2",
+            params![id, owner_id],
+        )?;
+
+        Ok(deleted > 0)
+    }
+
+    pub fn members(&self, id: &str) -> Result<Vec<Member>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(
+            "SELECT m.user_id, u.email, u.username,
+            CASE WHEN t.owner_id = m.user_id THEN 'owner' ELSE m.role END
+            FROM team_members m
+            JOIN teams t ON t.id = m.team_id
+            JOIN users u ON u.id = m.user_id
+            WHERE m.team_id = ?1 ORDER BY m.created_at, u.email",
+        )?;
+
+        let rows = stmt
+            .query_map(params![id], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, String>(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(user_id, email, username, role)| {
+                Ok(Member {
+                    user_id,
+                    email,
+                    username,
+                    role: Role::try_from(role.as_str()).map_err(BasableError::Driver)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Removes the user from team `id`, and stops sharing the user's connections with the
+    /// team. Returns `false` if the user is not a member.
+    pub fn remove_member(&self, id: &str, user_id: &str) -> Result<bool, BasableError> {
+        let mut conn = self.0.pool()?;
+
+        let tx = conn.transaction()?;
+        let removed = tx.execute(
+            "DELETE FROM team_members WHERE team_id = ?1 AND user_id = ?2",
+            params![id, user_id],
+        )?;
+        tx.execute(
+            "DELETE FROM team_connections WHERE team_id = ?1
+            AND conn_id IN (SELECT id FROM connections WHERE user_id = ?2)",
+            params![id, user_id],
+        )?;
+        tx.commit()?;
+
+        Ok(removed > 0)
+    }
+
+    /// Shares connection `conn_id` with team `id`.
+    pub fn share(&self, id: &str, conn_id: &str) -> Result<(), BasableError> {
+        let conn = self.0.pool()?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO team_connections (team_id, conn_id) VALUES (?1, ?2)",
+            params![id, conn_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Stops sharing connection `conn_id` with team `id`. Returns `false` if it was not
+    /// shared.
+    pub fn unshare(&self, id: &str, conn_id: &str) -> Result<bool, BasableError> {
+        let conn = self.0.pool()?;
+
+        let removed = conn.execute(
+            "DELETE FROM team_connections WHERE team_id = ?1 AND conn_id = ?2",
+            params![id, conn_id],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    fn query(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Team>, BasableError> {
+        let conn = self.0.pool()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT t.id, t.name, t.owner_id,
+            CASE WHEN t.owner_id = m.user_id THEN 'owner' ELSE m.role END
+            FROM teams t JOIN team_members m ON m.team_id = t.id
+            WHERE {condition} ORDER BY t.created_at, t.name"
+        ))?;
+
+        let rows = stmt
+            .query_map(params, |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, String>(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, name, owner_id, role)| {
+                Ok(Team {
+                    id,
+                    name,
+                    owner_id,
+                    role: Role::try_from(role.as_str()).map_err(BasableError::Driver)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{
+        config::ConnectionConfig,
+        local::LocalDB,
+        user::{Account, Role},
+        BasableError,
+    };
+
+    #[test]
+    fn test_teams() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        for id in ["owner", "analyst"] {
+            db.users().create(&Account {
+                id: id.to_string(),
+                email: format!("{id}@example.com"),
+                username: None,
+                password_hash: "hash".to_string(),
+            })?;
+        }
+
+        let config = ConnectionConfig::default();
+        db.connections().save("owner", "conn", "Sales", &config)?;
+
+        let team = db.teams().create("owner", "Analytics")?;
+        assert_eq!(team.role, Role::Owner);
+        db.teams().share(&team.id, "conn")?;
+
+        // The connection is shared once the invitation is accepted.
+        let invitation = db
+            .invitations()
+            .create(&team.id, "owner", "analyst", Role::Viewer)?;
+        assert_eq!(db.invitations().list("analyst")?.len(), 1);
+        assert!(db.connections().role("conn", "analyst")?.is_none());
+        assert!(db.teams().get(&team.id, "analyst")?.is_none());
+
+        assert!(db.invitations().accept(&invitation.id, "owner")?.is_none());
+        let accepted = db.invitations().accept(&invitation.id, "analyst")?;
+        assert_eq!(accepted.as_deref(), Some(team.id.as_str()));
+        assert!(db.invitations().list("analyst")?.is_empty());
+
+        assert_eq!(db.teams().list("analyst")?[0].role, Role::Viewer);
+        assert_eq!(db.teams().members(&team.id)?.len(), 2);
+        assert_eq!(
+            db.connections().role("conn", "analyst")?,
+            Some(Role::Viewer)
+        );
+        let shared = db.connections().list("analyst")?;
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].role, Role::Viewer);
+
+        // The best of a user's roles applies.
+        db.members().set("conn", "analyst", Role::Editor)?;
+        assert_eq!(
+            db.connections().role("conn", "analyst")?,
+            Some(Role::Editor)
+        );
+        db.members().remove("conn", "analyst")?;
+
+        assert!(db.teams().remove_member(&team.id, "analyst")?);
+        assert!(db.connections().role("conn", "analyst")?.is_none());
+
+        assert!(!db.teams().delete(&team.id, "analyst")?);
+        assert!(db.teams().delete(&team.id, "owner")?);
+        assert!(db.teams().list("owner")?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::base::{user::Account, BasableError};
+
+use super::LocalDB;
+
+/// Repository of registered user [`Account`]s. Emails are stored in lowercase, and
+/// usernames are unique regardless of case.
+pub(crate) struct Users<'a>(pub(super) &'a LocalDB);
+
+impl Users<'_> {
+    pub fn create(&self, account: &Account) -> Result<(), BasableError> {
+        let conn = self.0.pool()?;
+
+        conn.execute(
+            "INSERT INTO users (id, email, username, password_hash) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                account.id,
+                account.email.to_lowercase(),
+                account.username,
+                account.password_hash
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Result<Option<Account>, BasableError> {
+        self.find("id = ?1", id)
+    }
+
+    pub fn find_by_email(&self, email: &str) -> Result<Option<Account>, BasableError> {
+        self.find("email = lower(?1)", email)
+    }
+
+    pub fn find_by_username(&self, username: &str) -> Result<Option<Account>, BasableError> {
+        self.find("username = ?1", username)
+    }
+
+    fn find(&self, condition: &str, value: &str) -> Result<Option<Account>, BasableError> {
+        let conn = self.0.pool()?;
+
+        let account = conn
+            .query_row(
+                &format!("SELECT id, email, username, password_hash FROM users WHERE {condition}"),
+                params![value],
+                Self::from_row,
+            )
+            .optional()?;
+
+        Ok(account)
+    }
+
+    fn from_row(r: &Row) -> Result<Account, rusqlite::Error> {
+        Ok(Account {
+            id: r.get(0)?,
+            email: r.get(1)?,
+            username: r.get(2)?,
+            password_hash: r.get(3)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::{local::LocalDB, user::Account, BasableError};
+
+    #[test]
+    fn test_users() -> Result<(), BasableError> {
+        let db = LocalDB::memory()?;
+        db.migrate()?;
+
+        let users = db.users();
+        users.create(&Account {
+            id: "id".to_string(),
+            email: "Ada@Example.com".to_string(),
+            username: Some("ada".to_string()),
+            password_hash: "hash".to_string(),
+        })?;
+
+        let account = users.find_by_email("ada@example.COM")?.unwrap();
+        assert_eq!(account.email, "ada@example.com");
+        assert_eq!(users.find_by_username("ADA")?.unwrap().id, "id");
+        assert_eq!(users.find_by_id("id")?.unwrap().email, "ada@example.com");
+        assert!(users.find_by_email("bob@example.com")?.is_none());
+
+        // Emails and usernames are unique.
+        let mut other = Account {
+            id: "other".to_string(),
+            username: None,
+            ..account
+        };
+        assert!(users.create(&other).is_err());
+        other.email = "bob@example.com".to_string();
+        other.username = Some("Ada".to_string());
+        assert!(users.create(&other).is_err());
+
+        Ok(())
+    }
+}
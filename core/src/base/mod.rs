@@ -10,28 +10,21 @@ use axum::{
     response::IntoResponse,
 };
 use foundation::Basable;
-use r2d2::{Pool, PooledConnection};
-use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::params;
+use imp::SharedDB;
+use local::LocalDB;
 use serde::Serialize;
+use user::{Role, User};
+use uuid::Uuid;
 
 pub(crate) mod column;
 pub(crate) mod config;
 pub(crate) mod foundation;
 pub(crate) mod imp;
+pub(crate) mod local;
 pub(crate) mod user;
 pub(crate) mod data;
 pub(crate) mod query;
 
-#[derive(Clone)]
-pub(crate) struct LocalDB(pub Pool<SqliteConnectionManager>);
-
-impl LocalDB {
-    fn pool(&self) -> PooledConnection<SqliteConnectionManager> {
-        self.0.get().unwrap()
-    }
-}
-
 #[derive(Clone)]
 pub(crate) struct AppState {
     pub instance: Arc<Mutex<Basable>>,
@@ -39,25 +32,93 @@ pub(crate) struct AppState {
 }
 
 impl AppState {
-    pub fn setup_local_db(&self) {
-        let pool = self.local_db.pool();
-        pool.execute(
-            "CREATE TABLE IF NOT EXISTS table_configs (id INTEGER)",
-            params![],
-        )
-        .unwrap();
+    pub fn new(local_db: LocalDB) -> Self {
+        Self {
+            instance: Default::default(),
+            local_db,
+        }
+    }
+
+    /// Prepares the local store for use. The store is checked for corruption before its
+    /// migrations are applied.
+    pub fn setup_local_db(&self) -> Result<(), BasableError> {
+        self.local_db.check_integrity()?;
+        self.local_db.migrate()
+    }
+
+    /// Get connection `conn_id` and the user's [`Role`] on it. A saved connection that is
+    /// not open, such as after a restart, is opened again. Members of a connection and of
+    /// the teams it is shared with share the owner's open connection.
+    ///
+    /// A user authenticated with an API key only gets the connections of the key, with the
+    /// key's access.
+    pub fn get_connection(
+        &self,
+        conn_id: &str,
+        user: &User,
+    ) -> Result<Option<(SharedDB, Role)>, AppError> {
+        let Some(key) = &user.api_key else {
+            return self.find_connection(conn_id, user);
+        };
+
+        if !key.connections.iter().any(|c| c == conn_id) {
+            return Ok(None);
+        }
+
+        let conn = self.find_connection(conn_id, user)?;
+        Ok(conn.map(|(db, role)| (db, key.access.limit(role))))
+    }
+
+    fn find_connection(
+        &self,
+        conn_id: &str,
+        user: &User,
+    ) -> Result<Option<(SharedDB, Role)>, AppError> {
+        let bsbl = self.instance.lock().unwrap();
+        if let Some(db) = bsbl.get_connection(conn_id, &user.id) {
+            return Ok(Some((db, Role::Owner)));
+        }
+        std::mem::drop(bsbl); // release Mutex lock while connecting
+
+        // Only connections of registered users are saved.
+        let (Ok(id), false) = (Uuid::parse_str(conn_id), user.is_guest) else {
+            return Ok(None);
+        };
+        let Some(owner) = self.local_db.connections().owner(conn_id)? else {
+            return Ok(None);
+        };
+
+        let Some(role) = self.local_db.connections().role(conn_id, &user.id)? else {
+            return Ok(None);
+        };
+
+        let bsbl = self.instance.lock().unwrap();
+        if let Some(db) = bsbl.get_connection(conn_id, &owner) {
+            return Ok(Some((db, role)));
+        }
+        std::mem::drop(bsbl);
+
+        let Some(saved) = self.local_db.connections().get(&owner, conn_id)? else {
+            return Ok(None);
+        };
+        let db = Basable::open_connection(&saved.config, owner.clone(), id)?;
+
+        // Another request may have opened the connection in the meantime.
+        let mut bsbl = self.instance.lock().unwrap();
+        match bsbl.get_connection(conn_id, &owner) {
+            Some(db) => Ok(Some((db, role))),
+            None => {
+                bsbl.add_connection(&db);
+                Ok(Some((db, role)))
+            }
+        }
     }
 }
 
+/// Creates [`AppState`] with an in-memory local store.
 impl Default for AppState {
     fn default() -> Self {
-        let manager = SqliteConnectionManager::memory();
-        let pool = r2d2::Pool::new(manager).unwrap();
-
-        Self {
-            instance: Default::default(),
-            local_db: LocalDB(pool),
-        }
+        Self::new(LocalDB::memory().unwrap())
     }
 }
 
@@ -87,7 +148,66 @@ impl Serialize for AppError {
     where
         S: serde::Serializer,
     {
-        Ok(serializer.collect_str(&self.1)?)
+        serializer.collect_str(&self.1)
+    }
+}
+
+/// Error returned by data source operations. Each data source implementation converts
+/// its driver errors into [`BasableError`].
+#[derive(Debug)]
+pub(crate) enum BasableError {
+    /// Error reported by the data source driver.
+    Driver(String),
+
+    /// The data source could not be reached or opened.
+    Connection(String),
+
+    /// A query could not be built from the given options.
+    Query(String),
+
+    /// The operation is not supported by the data source.
+    Unsupported(String),
+
+    /// A table or column name is not part of the data source's schema.
+    Identifier(String),
+
+    /// A value given by the user is not valid for the operation.
+    Input(String),
+}
+
+impl Display for BasableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BasableError::Driver(msg) => write!(f, "{msg}"),
+            BasableError::Connection(msg) => write!(f, "{msg}"),
+            BasableError::Query(msg) => write!(f, "{msg}"),
+            BasableError::Unsupported(msg) => write!(f, "{msg}"),
+            BasableError::Identifier(msg) => write!(f, "{msg}"),
+            BasableError::Input(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+/// Connection pools only fail while opening a new connection.
+impl From<r2d2::Error> for BasableError {
+    fn from(value: r2d2::Error) -> Self {
+        Self::Connection(value.to_string())
+    }
+}
+
+/// [`BasableError::Unsupported`], [`BasableError::Identifier`] and [`BasableError::Input`]
+/// resolve to `StatusCode::BAD_REQUEST`. All other variations resolve to
+/// `StatusCode::INTERNAL_SERVER_ERROR`.
+impl From<BasableError> for AppError {
+    fn from(value: BasableError) -> Self {
+        let code = match value {
+            BasableError::Unsupported(_) | BasableError::Identifier(_) | BasableError::Input(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self(code, value.to_string())
     }
 }
 
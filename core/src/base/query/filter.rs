@@ -1,65 +1,70 @@
-use std::fmt::Display;
+use std::{fmt::Display, iter::Peekable, str::Chars};
 
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use serde::{Deserialize, Serialize};
 
 use crate::globals::QUERY_FILTER_PREFIX;
 
-pub trait FilterValue: Display + Clone + Default {}
-
-#[derive(Clone, Default)]
+/// Operator of a [`FilterCondition`], with its values. In JSON, the operator is named by `op`
+/// and its values are in `value`, such as `{ "op": "btw", "value": ["2010", "2012"] }`.
+#[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "op", content = "value", rename_all = "lowercase")]
 pub enum FilterOperator {
     Eq(String),
+    #[serde(rename = "neq")]
     NotEq(String),
     Gt(String),
     Lt(String),
     Gte(String),
     Lte(String),
     Like(String),
+    #[serde(rename = "nlike")]
     NotLike(String),
+    #[serde(rename = "slike")]
     LikeSingle(String),
+    #[serde(rename = "nslike")]
     NotLikeSingle(String),
     Regex(String),
+    #[serde(rename = "nregex")]
     NotRegex(String),
     Btw(String, String),
+    #[serde(rename = "nbtw")]
     NotBtw(String, String),
+    #[serde(rename = "in")]
     Contains(Vec<String>),
+    #[serde(rename = "nin")]
     NotContains(Vec<String>),
 
     #[default]
     Null,
 
+    #[serde(rename = "nnull")]
     NotNull,
 }
 
+/// SQL form of the operator, with `?` in place of its values. Values are never part of the
+/// SQL; they are bound as query parameters by [`QuerySqlParser`](`crate::base::imp::db::QuerySqlParser`).
 impl Display for FilterOperator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let op = match self {
-            FilterOperator::Eq(v) => format!("= '{v}'"),
-            FilterOperator::NotEq(v) => format!("!= '{v}'"),
-            FilterOperator::Gt(v) => format!("> '{v}'"),
-            FilterOperator::Lt(v) => format!("< '{v}'"),
-            FilterOperator::Gte(v) => format!(">= '{v}'"),
-            FilterOperator::Lte(v) => format!("<= '{v}'"),
-            FilterOperator::Like(v) => format!("LIKE '{v}%'"),
-            FilterOperator::NotLike(v) => format!("NOT LIKE '{v}%'"),
-            FilterOperator::LikeSingle(v) => format!("LIKE '_{v}%'"),
-            FilterOperator::NotLikeSingle(v) => format!("NOT LIKE '_{v}%'"),
-            FilterOperator::Regex(v) => format!("REGEXP '{v}'"),
-            FilterOperator::NotRegex(v) => format!("NOT REGEXP '{v}'"),
-            FilterOperator::Btw(start, end) => format!("BETWEEN '{start}' AND '{end}'"),
-            FilterOperator::NotBtw(start, end) => format!("NOT BETWEEN '{start}' AND '{end}'"),
+            FilterOperator::Eq(_) => "= ?".to_string(),
+            FilterOperator::NotEq(_) => "!= ?".to_string(),
+            FilterOperator::Gt(_) => "> ?".to_string(),
+            FilterOperator::Lt(_) => "< ?".to_string(),
+            FilterOperator::Gte(_) => ">= ?".to_string(),
+            FilterOperator::Lte(_) => "<= ?".to_string(),
+            FilterOperator::Like(_) | FilterOperator::LikeSingle(_) => "LIKE ?".to_string(),
+            FilterOperator::NotLike(_) | FilterOperator::NotLikeSingle(_) => {
+                "NOT LIKE ?".to_string()
+            }
+            FilterOperator::Regex(_) => "REGEXP ?".to_string(),
+            FilterOperator::NotRegex(_) => "NOT REGEXP ?".to_string(),
+            FilterOperator::Btw(_, _) => "BETWEEN ? AND ?".to_string(),
+            FilterOperator::NotBtw(_, _) => "NOT BETWEEN ? AND ?".to_string(),
             FilterOperator::Contains(values) => {
-                let v: Vec<String> = values.iter().map(|v| v.to_string()).collect();
-                let v = v.join(", ");
-
-                format!("IN ({v})")
+                format!("IN ({})", vec!["?"; values.len()].join(", "))
             }
             FilterOperator::NotContains(values) => {
-                let v: Vec<String> = values.iter().map(|v| v.to_string()).collect();
-                let v = v.join(", ");
-
-                format!("NOT IN ({v})")
+                format!("NOT IN ({})", vec!["?"; values.len()].join(", "))
             }
             FilterOperator::Null => "IS NULL".to_string(),
             FilterOperator::NotNull => "IS NOT NULL".to_string(),
@@ -69,9 +74,118 @@ impl Display for FilterOperator {
     }
 }
 
-#[derive(Clone, Default)]
+impl FilterOperator {
+    /// Name of the operator in encoded [`Filter`]s, the same as in JSON.
+    fn name(&self) -> &'static str {
+        match self {
+            FilterOperator::Eq(_) => "eq",
+            FilterOperator::NotEq(_) => "neq",
+            FilterOperator::Gt(_) => "gt",
+            FilterOperator::Lt(_) => "lt",
+            FilterOperator::Gte(_) => "gte",
+            FilterOperator::Lte(_) => "lte",
+            FilterOperator::Like(_) => "like",
+            FilterOperator::NotLike(_) => "nlike",
+            FilterOperator::LikeSingle(_) => "slike",
+            FilterOperator::NotLikeSingle(_) => "nslike",
+            FilterOperator::Regex(_) => "regex",
+            FilterOperator::NotRegex(_) => "nregex",
+            FilterOperator::Btw(_, _) => "btw",
+            FilterOperator::NotBtw(_, _) => "nbtw",
+            FilterOperator::Contains(_) => "in",
+            FilterOperator::NotContains(_) => "nin",
+            FilterOperator::Null => "null",
+            FilterOperator::NotNull => "nnull",
+        }
+    }
+
+    fn values(&self) -> Vec<&String> {
+        match self {
+            FilterOperator::Eq(v)
+            | FilterOperator::NotEq(v)
+            | FilterOperator::Gt(v)
+            | FilterOperator::Lt(v)
+            | FilterOperator::Gte(v)
+            | FilterOperator::Lte(v)
+            | FilterOperator::Like(v)
+            | FilterOperator::NotLike(v)
+            | FilterOperator::LikeSingle(v)
+            | FilterOperator::NotLikeSingle(v)
+            | FilterOperator::Regex(v)
+            | FilterOperator::NotRegex(v) => vec![v],
+            FilterOperator::Btw(start, end) | FilterOperator::NotBtw(start, end) => {
+                vec![start, end]
+            }
+            FilterOperator::Contains(values) | FilterOperator::NotContains(values) => {
+                values.iter().collect()
+            }
+            FilterOperator::Null | FilterOperator::NotNull => Vec::new(),
+        }
+    }
+
+    /// Operator named `name`, with `values`.
+    fn from_values(name: &str, mut values: Vec<String>) -> Result<Self, String> {
+        let count = |n: usize| match values.len() == n {
+            true => Ok(()),
+            false => Err(format!("Filter operator '{name}' takes {n} value(s).")),
+        };
+
+        let operator = match name {
+            "btw" | "nbtw" => {
+                count(2)?;
+                let end = values.pop().unwrap();
+                let start = values.pop().unwrap();
+
+                match name {
+                    "btw" => FilterOperator::Btw(start, end),
+                    _ => FilterOperator::NotBtw(start, end),
+                }
+            }
+            "in" | "nin" => {
+                if values.is_empty() {
+                    return Err(format!("Filter operator '{name}' takes at least 1 value."));
+                }
+
+                match name {
+                    "in" => FilterOperator::Contains(values),
+                    _ => FilterOperator::NotContains(values),
+                }
+            }
+            "null" => count(0).map(|_| FilterOperator::Null)?,
+            "nnull" => count(0).map(|_| FilterOperator::NotNull)?,
+            _ => {
+                count(1)?;
+                let v = values.pop().unwrap();
+
+                match name {
+                    "eq" => FilterOperator::Eq(v),
+                    "neq" => FilterOperator::NotEq(v),
+                    "gt" => FilterOperator::Gt(v),
+                    "lt" => FilterOperator::Lt(v),
+                    "gte" => FilterOperator::Gte(v),
+                    "lte" => FilterOperator::Lte(v),
+                    "like" => FilterOperator::Like(v),
+                    "nlike" => FilterOperator::NotLike(v),
+                    "slike" => FilterOperator::LikeSingle(v),
+                    "nslike" => FilterOperator::NotLikeSingle(v),
+                    "regex" => FilterOperator::Regex(v),
+                    "nregex" => FilterOperator::NotRegex(v),
+                    _ => return Err(format!("Unknown filter operator '{name}'.")),
+                }
+            }
+        };
+
+        Ok(operator)
+    }
+}
+
+/// A condition on a column, such as `{ "column": "title", "op": "like", "value": "Halo" }`
+/// in JSON.
+#[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize)]
 pub struct FilterCondition {
     pub column: String,
+
+    #[serde(flatten)]
     pub operator: FilterOperator,
 }
 
@@ -81,22 +195,194 @@ impl Display for FilterCondition {
     }
 }
 
-#[derive(Clone, EnumIter)]
+/// A boolean expression of [`FilterCondition`]s. Groups of filters are joined with `AND` or
+/// `OR`, and can be nested.
+///
+/// In JSON, groups are objects with an `and`, `or` or `not` key, and conditions are objects
+/// of [`FilterCondition`]. For example, `a = 1 AND (b = 2 OR NOT c IS NULL)` is:
+///
+/// ```json
+/// { "and": [
+///     { "column": "a", "op": "eq", "value": "1" },
+///     { "or": [
+///         { "column": "b", "op": "eq", "value": "2" },
+///         { "not": { "column": "c", "op": "null" } }
+///     ] }
+/// ] }
+/// ```
+///
+/// In URLs, filters are written as `basable_filter:<expression>` (see [`Display`] for
+/// [`Filter`]), and the same filter is:
+/// `basable_filter:and(eq(a,1),or(eq(b,2),not(null(c))))`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Filter {
-    BASE(FilterCondition),
-    AND(FilterCondition),
-    OR(FilterCondition),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+
+    #[serde(untagged)]
+    Condition(FilterCondition),
+}
+
+impl Filter {
+    /// The same filter, with the columns of its conditions mapped by `f`. Fails with the
+    /// first error of `f`.
+    pub fn map_columns<E>(
+        &self,
+        f: &mut impl FnMut(&str) -> Result<String, E>,
+    ) -> Result<Filter, E> {
+        let map = |filters: &[Filter], f: &mut _| {
+            filters
+                .iter()
+                .map(|filter| filter.map_columns(f))
+                .collect::<Result<Vec<_>, E>>()
+        };
+
+        let filter = match self {
+            Filter::And(filters) => Filter::And(map(filters, f)?),
+            Filter::Or(filters) => Filter::Or(map(filters, f)?),
+            Filter::Not(filter) => Filter::Not(Box::new(filter.map_columns(f)?)),
+            Filter::Condition(c) => Filter::Condition(FilterCondition {
+                column: f(&c.column)?,
+                operator: c.operator.clone(),
+            }),
+        };
+
+        Ok(filter)
+    }
+}
+
+/// Characters that are escaped with `\` in encoded filters.
+const ESCAPED: [char; 4] = ['\\', ',', '(', ')'];
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if ESCAPED.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
 }
 
+/// Encoded form of the filter, used to pass filters in URLs. Groups are written as
+/// `and(<filter>,...)`, `or(<filter>,...)` and `not(<filter>)`, and conditions as
+/// `<op>(<column>,<value>,...)` with the operators and values of [`FilterOperator`]'s JSON.
+/// `\`, `,`, `(` and `)` in columns and values are escaped with `\`.
 impl Display for Filter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let filter = match self {
-            Filter::BASE(c) => c.to_string(),
-            Filter::AND(c) => format!("AND {c}"),
-            Filter::OR(c) => format!("OR {c}"),
+        fn encode(filter: &Filter) -> String {
+            let group = |name: &str, filters: &[Filter]| {
+                let filters: Vec<String> = filters.iter().map(encode).collect();
+                format!("{name}({})", filters.join(","))
+            };
+
+            match filter {
+                Filter::And(filters) => group("and", filters),
+                Filter::Or(filters) => group("or", filters),
+                Filter::Not(filter) => format!("not({})", encode(filter)),
+                Filter::Condition(c) => {
+                    let mut args = vec![escape(&c.column)];
+                    args.extend(c.operator.values().into_iter().map(|v| escape(v)));
+                    format!("{}({})", c.operator.name(), args.join(","))
+                }
+            }
+        }
+
+        write!(f, "{QUERY_FILTER_PREFIX}{}", encode(self))
+    }
+}
+
+/// Parser of encoded filters.
+struct FilterParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl FilterParser<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if_eq(&c).is_some()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(format!("Expected '{c}' in filter.")),
+        }
+    }
+
+    /// Name of a group or operator.
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+
+        name
+    }
+
+    /// An escaped column or value.
+    fn arg(&mut self) -> Result<String, String> {
+        let mut arg = String::new();
+        while let Some(c) = self.chars.next_if(|c| *c != ',' && *c != ')') {
+            match c {
+                '\\' => arg.push(self.chars.next().ok_or("Unterminated escape in filter.")?),
+                '(' => return Err("Unescaped '(' in filter.".to_string()),
+                c => arg.push(c),
+            }
+        }
+
+        Ok(arg)
+    }
+
+    fn filter(&mut self) -> Result<Filter, String> {
+        let name = self.name();
+        self.expect('(')?;
+
+        let filter = match name.as_str() {
+            "and" | "or" => {
+                let mut filters = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        filters.push(self.filter()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+
+                match name.as_str() {
+                    "and" => Filter::And(filters),
+                    _ => Filter::Or(filters),
+                }
+            }
+            "not" => {
+                let filter = self.filter()?;
+                self.expect(')')?;
+                Filter::Not(Box::new(filter))
+            }
+            _ => {
+                let column = self.arg()?;
+                let mut values = Vec::new();
+                while self.eat(',') {
+                    values.push(self.arg()?);
+                }
+                self.expect(')')?;
+
+                if column.is_empty() {
+                    return Err("Filter conditions must have a column.".to_string());
+                }
+                Filter::Condition(FilterCondition {
+                    column,
+                    operator: FilterOperator::from_values(&name, values)?,
+                })
+            }
         };
 
-        write!(f, "{QUERY_FILTER_PREFIX}{filter}")
+        Ok(filter)
     }
 }
 
@@ -104,17 +390,25 @@ impl TryFrom<String> for Filter {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        for filter in Filter::iter() {
-            if filter.to_string() == value {
-                return Ok(filter);
-            }
-        }
+        let encoded = value
+            .strip_prefix(QUERY_FILTER_PREFIX)
+            .ok_or_else(|| format!("Filters must start with '{QUERY_FILTER_PREFIX}'."))?;
 
-        Err("Error converting type to Filter".to_string())
+        let mut parser = FilterParser {
+            chars: encoded.chars().peekable(),
+        };
+        let filter = parser.filter()?;
+
+        match parser.chars.next() {
+            None => Ok(filter),
+            Some(c) => Err(format!("Unexpected '{c}' in filter.")),
+        }
     }
 }
 
-#[derive(Default)]
+/// Filters of a query, which rows must all match. Each filter is joined to the others
+/// with `AND`.
+#[derive(Clone, Default)]
 pub struct FilterChain(Vec<Filter>);
 impl FilterChain {
     pub fn new() -> Self {
@@ -125,10 +419,6 @@ impl FilterChain {
         self.0.push(filter);
     }
 
-    pub fn add_multiple(&mut self, filters: Vec<Filter>) {
-        filters.iter().for_each(|f| self.0.push(f.clone()));
-    }
-
     pub fn all(&self) -> &Vec<Filter> {
         &self.0
     }
@@ -144,19 +434,82 @@ impl FilterChain {
 
 impl Display for FilterChain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let values = &self.0;
+        let values: Vec<String> = self.0.iter().map(|f| f.to_string()).collect();
+        let values = values.join(",");
 
-        if !values.is_empty() {
-            let first = values.get(0).unwrap();
+        write!(f, "{values}")
+    }
+}
 
-            if !matches!(first, &Filter::BASE(_)) {
-                return Err(std::fmt::Error)
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
 
-        let values: Vec<String> = values.iter().map(|f| f.to_string()).collect();
-        let values = values.join(",");
+    use super::{Filter, FilterCondition, FilterOperator};
 
-        write!(f, "{values}")
+    fn condition(column: &str, operator: FilterOperator) -> Filter {
+        Filter::Condition(FilterCondition {
+            column: column.to_string(),
+            operator,
+        })
+    }
+
+    #[test]
+    fn test_encode_filter() {
+        let filter = Filter::And(vec![
+            condition(
+                "publisher",
+                FilterOperator::Eq("Rockstar Games (North), Inc.".to_string()),
+            ),
+            Filter::Or(vec![
+                condition(
+                    "release_date",
+                    FilterOperator::Btw("2010-01-01".to_string(), "2010-12-31".to_string()),
+                ),
+                condition(
+                    "console",
+                    FilterOperator::Contains(vec!["PS3".to_string(), "X\\360".to_string()]),
+                ),
+                Filter::Not(Box::new(condition("critic_score", FilterOperator::NotNull))),
+            ]),
+            Filter::Or(Vec::new()),
+        ]);
+
+        let encoded = filter.to_string();
+        assert_eq!(
+            encoded,
+            "basable_filter:and(eq(publisher,Rockstar Games \\(North\\)\\, Inc.),\
+            or(btw(release_date,2010-01-01,2010-12-31),in(console,PS3,X\\\\360),\
+            not(nnull(critic_score))),or())"
+        );
+        assert_eq!(Filter::try_from(encoded).unwrap(), filter);
+
+        let json = serde_json::to_value(&filter).unwrap();
+        assert_eq!(
+            json["and"][1]["or"][0],
+            json!({ "column": "release_date", "op": "btw", "value": ["2010-01-01", "2010-12-31"] })
+        );
+        assert_eq!(
+            json["and"][1]["or"][2],
+            json!({ "not": { "column": "critic_score", "op": "nnull" } })
+        );
+        assert_eq!(serde_json::from_value::<Filter>(json).unwrap(), filter);
+
+        for invalid in [
+            "publisher = 'Rockstar Games'",
+            "basable_filter:xor(eq(title,Halo))",
+            "basable_filter:equals(title,Halo)",
+            "basable_filter:btw(title,2010)",
+            "basable_filter:in(title)",
+            "basable_filter:null(title,Halo)",
+            "basable_filter:eq(,Halo)",
+            "basable_filter:eq(title,Halo (3))",
+            "basable_filter:not(eq(title,Halo),eq(title,Fable))",
+            "basable_filter:and(eq(title,Halo)",
+            "basable_filter:eq(title,Halo))",
+            "basable_filter:eq(title,Halo\\",
+        ] {
+            assert!(Filter::try_from(invalid.to_string()).is_err(), "{invalid}");
+        }
     }
 }
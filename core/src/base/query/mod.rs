@@ -2,10 +2,32 @@ use std::fmt::Display;
 
 use filter::FilterChain;
 
+use super::data::value::BasableValue;
+
 pub mod filter;
 
+/// Operation of a [`BasableQuery`]. Rows of values are pairs of columns and values; the
+/// values are bound as query parameters.
 pub enum QueryOperation {
     SelectData(Option<Vec<String>>),
+
+    /// Inserts a row.
+    Insert(Vec<(String, BasableValue)>),
+
+    /// Sets the columns of the rows matching the query's filters to the given values. Every
+    /// row is updated when the query has no filters.
+    Update(Vec<(String, BasableValue)>),
+
+    /// Deletes the rows matching the query's filters. Every row is deleted when the query
+    /// has no filters.
+    Delete,
+
+    /// Inserts `row`, or updates the row that has the same values in the `keys` columns.
+    /// `keys` must be unique in the table.
+    Upsert {
+        row: Vec<(String, BasableValue)>,
+        keys: Vec<String>,
+    },
 }
 
 impl Default for QueryOperation {
@@ -14,6 +36,7 @@ impl Default for QueryOperation {
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum QueryOrder {
     ASC(String),
     DESC(String),
@@ -30,14 +53,47 @@ impl Display for QueryOrder {
     }
 }
 
+/// A query to be converted to SQL by [`QuerySqlParser::generate_sql`]. Table and column names
+/// are written into the SQL as they are, so they must be resolved and quoted by the caller.
+/// Only `table` is used by [`QueryOperation::Insert`] and [`QueryOperation::Upsert`].
+///
+/// [`QuerySqlParser::generate_sql`]: crate::base::imp::db::QuerySqlParser::generate_sql
 #[derive(Default)]
 pub struct BasableQuery {
     pub table: String,
     pub operation: QueryOperation,
     pub filters: FilterChain,
     pub limit: Option<usize>,
+    pub offset: Option<usize>,
     pub order_by: Option<QueryOrder>,
     pub group_by: Option<Vec<String>>,
     pub left_join: Option<String>,
     pub having: FilterChain,
+
+    /// Parameters of the placeholders in the columns of [`QueryOperation::SelectData`], in
+    /// order. They are bound before the parameters of the filters.
+    pub params: Vec<BasableValue>,
+}
+
+/// SQL generated from a [`BasableQuery`]. `params` are bound to the placeholders of `sql`, in order.
+pub(crate) struct SqlQuery {
+    pub sql: String,
+    pub params: Vec<BasableValue>,
+}
+
+/// Quotes `name` as an SQL identifier with double quotes, as defined by the SQL standard.
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Finds `name` in `idents`. An exact match is preferred, otherwise names are compared case
+/// insensitively.
+pub(crate) fn find_ident<'a, I>(mut idents: I, name: &str) -> Option<&'a str>
+where
+    I: Iterator<Item = &'a str> + Clone,
+{
+    idents
+        .clone()
+        .find(|i| *i == name)
+        .or_else(|| idents.find(|i| i.eq_ignore_ascii_case(name)))
 }
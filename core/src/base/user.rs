@@ -1,26 +1,316 @@
-use std::str::from_utf8;
+use std::{env, str::from_utf8};
 
 use axum::http::{HeaderValue, StatusCode};
 use chrono::Utc;
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
-use time::format_description::well_known::iso8601::Config;
 
-use crate::{base::AppError, utils::get_env};
+use uuid::Uuid;
 
-use crate::base::data::table::TableConfig;
+use crate::{
+    base::AppError,
+    utils::{crypto, get_env},
+};
+
+use crate::base::{
+    config::ConnectionConfig,
+    data::table::TableConfig,
+    local::{api_keys::ApiKey, sessions::AccessToken, LocalDB},
+};
 
 pub(crate) struct User {
     pub id: String,
     pub is_guest: bool,
+
+    /// Session the user is authenticated with.
+    pub session_id: Option<String>,
+
+    /// API key the user is authenticated with, instead of a session.
+    pub api_key: Option<ApiKeyScope>,
+}
+
+/// Role of a user on a connection or in a team. The user who opened a connection owns it,
+/// and other users are given a role on it by the owner, or through a team it is shared with.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Role {
+    Owner,
+    /// Can read and change data.
+    Editor,
+    /// Can only read data.
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Editor => "editor",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    /// Whether the role allows changes to data and table configurations.
+    pub fn can_write(&self) -> bool {
+        !matches!(self, Role::Viewer)
+    }
+}
+
+impl TryFrom<&str> for Role {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "owner" => Ok(Role::Owner),
+            "editor" => Ok(Role::Editor),
+            "viewer" => Ok(Role::Viewer),
+            _ => Err(format!("Unknown role '{value}'.")),
+        }
+    }
 }
 
+/// Access an API key gives to its connections.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ApiKeyAccess {
+    Read,
+    Write,
+}
+
+impl ApiKeyAccess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyAccess::Read => "read",
+            ApiKeyAccess::Write => "write",
+        }
+    }
+
+    /// Role of a user authenticated with the key on a connection the user has `role` on.
+    /// A key never gives more access than the user has.
+    pub fn limit(&self, role: Role) -> Role {
+        match self {
+            ApiKeyAccess::Read => Role::Viewer,
+            ApiKeyAccess::Write => role,
+        }
+    }
+}
+
+impl TryFrom<&str> for ApiKeyAccess {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "read" => Ok(ApiKeyAccess::Read),
+            "write" => Ok(ApiKeyAccess::Write),
+            _ => Err(format!("Unknown API key access '{value}'.")),
+        }
+    }
+}
+
+/// What a user authenticated with an API key can use.
+#[derive(Clone, Debug)]
+pub(crate) struct ApiKeyScope {
+    pub access: ApiKeyAccess,
+
+    /// Ids of the only connections the key can be used with.
+    pub connections: Vec<String>,
+}
+
+/// A registered user. Guest users have no account.
+pub(crate) struct Account {
+    pub id: String,
+    pub email: String,
+    pub username: Option<String>,
+    pub password_hash: String,
+}
+
+/// Minimum length of account passwords.
+const MIN_PASSWORD_LEN: usize = 8;
+
 impl User {
-    pub fn save_connection(&self, config: Config){}
-    pub fn get_table_config(&self, conn_id: &str, table_name: &str) -> Option<TableConfig> {
-        None
+    /// Registers an account with id `id`. A guest user who signs up keeps their id, so that
+    /// their connections become the account's connections.
+    pub fn register(
+        db: &LocalDB,
+        id: String,
+        email: &str,
+        username: Option<&str>,
+        password: &str,
+    ) -> Result<User, AppError> {
+        let bad_request = |msg| Err(AppError::new(StatusCode::BAD_REQUEST, msg));
+        let conflict = |msg| Err(AppError::new(StatusCode::CONFLICT, msg));
+
+        let email = email.trim();
+        let username = username.map(str::trim).filter(|u| !u.is_empty());
+
+        if !email.contains('@') || email.chars().any(char::is_whitespace) {
+            return bad_request("Please provide a valid email.");
+        }
+        if username.is_some_and(|u| u.contains('@')) {
+            return bad_request("Username can't contain '@'.");
+        }
+        if password.chars().count() < MIN_PASSWORD_LEN {
+            let msg = format!("Password must have at least {MIN_PASSWORD_LEN} characters.");
+            return bad_request(&msg);
+        }
+
+        let users = db.users();
+        if users.find_by_email(email)?.is_some() {
+            return conflict("An account with this email already exists.");
+        }
+        if let Some(username) = username {
+            if users.find_by_username(username)?.is_some() {
+                return conflict("This username is taken.");
+            }
+        }
+
+        users.create(&Account {
+            id: id.clone(),
+            email: email.to_string(),
+            username: username.map(String::from),
+            password_hash: crypto::hash_password(password),
+        })?;
+
+        Ok(User {
+            id,
+            is_guest: false,
+            session_id: None,
+            api_key: None,
+        })
+    }
+
+    /// Get the registered user with `email` and `password`.
+    pub fn login(db: &LocalDB, email: &str, password: &str) -> Result<User, AppError> {
+        let account = db.users().find_by_email(email.trim())?;
+
+        // A password is verified even without an account, so that the time of the response
+        // doesn't tell whether `email` is registered.
+        let hash = account
+            .as_ref()
+            .map_or(crypto::DUMMY_PASSWORD_HASH, |a| a.password_hash.as_str());
+        let verified = crypto::verify_password(password, hash);
+
+        match account {
+            Some(account) if verified => Ok(User {
+                id: account.id,
+                is_guest: false,
+                session_id: None,
+                api_key: None,
+            }),
+            _ => Err(AppError::new(
+                StatusCode::UNAUTHORIZED,
+                "Invalid email or password.",
+            )),
+        }
+    }
+
+    /// Rejects users authenticated with an API key. A key only gives access to the data of
+    /// its connections, so managing the account, its sessions, connections and teams needs a
+    /// session.
+    pub fn with_session(&self) -> Result<(), AppError> {
+        match self.api_key {
+            Some(_) => Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                "This can't be done with an API key.",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Ends the session the user is authenticated with.
+    pub fn logout(&self, db: &LocalDB) -> Result<(), AppError> {
+        if let Some(session_id) = &self.session_id {
+            db.sessions().end(session_id, &self.id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends all sessions of the user, including the one the user is authenticated with.
+    pub fn logout_all(&self, db: &LocalDB) -> Result<(), AppError> {
+        db.sessions().end_all(&self.id)?;
+        Ok(())
+    }
+
+    /// Saves `config` of connection `conn_id`, so that the user can open the connection again
+    /// after a restart. Connections of guest users are not saved.
+    pub fn save_connection(
+        &self,
+        db: &LocalDB,
+        conn_id: &str,
+        config: &ConnectionConfig,
+    ) -> Result<(), AppError> {
+        if self.is_guest {
+            return Ok(());
+        }
+
+        db.connections()
+            .save(&self.id, conn_id, &config.default_name(), config)?;
+        Ok(())
+    }
+
+    /// Creates an API key of the user that gives `access` to `connections`, which must be
+    /// saved connections the user has a role on.
+    pub fn create_api_key(
+        &self,
+        db: &LocalDB,
+        name: &str,
+        access: ApiKeyAccess,
+        connections: &[String],
+    ) -> Result<NewApiKey, AppError> {
+        let bad_request = |msg| Err(AppError::new(StatusCode::BAD_REQUEST, msg));
+
+        let name = name.trim();
+        if name.is_empty() {
+            return bad_request("API key name can't be empty.");
+        }
+        if connections.is_empty() {
+            return bad_request("An API key needs at least one connection.");
+        }
+        for conn_id in connections {
+            if db.connections().role(conn_id, &self.id)?.is_none() {
+                let msg = format!("Can't find a saved connection with id '{conn_id}'.");
+                return Err(AppError(StatusCode::NOT_FOUND, msg));
+            }
+        }
+
+        let key = format!("{API_KEY_PREFIX}{}", crypto::random_token());
+        let prefix = &key[..API_KEY_PREFIX.len() + 6];
+        let details = db.api_keys().create(
+            &self.id,
+            name,
+            prefix,
+            &crypto::hash_token(&key),
+            access,
+            connections,
+        )?;
+
+        Ok(NewApiKey { key, details })
+    }
+
+    /// Get the user's [`TableConfig`] for table `table_name` of connection `conn_id`.
+    pub fn get_table_config(
+        &self,
+        db: &LocalDB,
+        conn_id: &str,
+        table_name: &str,
+    ) -> Result<Option<TableConfig>, AppError> {
+        let config = db.table_configs().get(&self.id, conn_id, table_name)?;
+        Ok(config)
+    }
+
+    /// Save the user's [`TableConfig`] for table `table_name` of connection `conn_id`.
+    pub fn update_table_config(
+        &self,
+        db: &LocalDB,
+        conn_id: &str,
+        table_name: &str,
+        config: TableConfig,
+    ) -> Result<(), AppError> {
+        db.table_configs()
+            .save(&self.id, conn_id, table_name, &config)?;
+        Ok(())
     }
-    pub fn update_table_config(&self, conn_id: &str, table_name: &str, config: TableConfig) {}
 }
 
 impl Default for User {
@@ -28,53 +318,158 @@ impl Default for User {
         Self {
             id: String::new(),
             is_guest: true,
+            session_id: None,
+            api_key: None,
         }
     }
 }
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Claims {
+    /// Id of the user, a UUID.
     sub: String,
+    /// Id of the user's session.
+    sid: String,
+    /// Id of the token, which is used to revoke it.
+    jti: String,
     is_guest: bool,
     exp: usize,
 }
 
+/// Tokens of a session. `exp` and `refresh_exp` are the lifetimes of the access token and
+/// the refresh token, in seconds.
 #[derive(Serialize)]
 pub(crate) struct JwtSession {
     pub token: String,
     pub exp: usize,
+    pub refresh_token: String,
+    pub refresh_exp: usize,
+}
+
+/// An API key, returned when it is created. `key` is not kept, so it can't be read again.
+#[derive(Serialize)]
+pub(crate) struct NewApiKey {
+    pub key: String,
+
+    #[serde(flatten)]
+    pub details: ApiKey,
 }
 
-pub(crate) fn create_jwt(user: User) -> Result<JwtSession, AppError> {
-    let exp_time = 7200;
+/// Prefix of API keys, which tells them apart from other tokens.
+const API_KEY_PREFIX: &str = "bsk_";
+
+/// Lifetime of access tokens in seconds. It is `BASABLE_ACCESS_TOKEN_LIFETIME` if set, and two
+/// hours otherwise.
+fn access_token_lifetime() -> i64 {
+    lifetime("BASABLE_ACCESS_TOKEN_LIFETIME", 2 * 60 * 60)
+}
 
-    // Token expiration is two hours
-    let exp = Utc::now()
-        .checked_add_signed(chrono::Duration::seconds(exp_time.clone()))
-        .expect("Invalid timestamp")
-        .timestamp() as usize;
+/// Lifetime of refresh tokens in seconds. It is `BASABLE_REFRESH_TOKEN_LIFETIME` if set, and
+/// 30 days otherwise. A session ends if it is not refreshed within this lifetime.
+fn refresh_token_lifetime() -> i64 {
+    lifetime("BASABLE_REFRESH_TOKEN_LIFETIME", 30 * 24 * 60 * 60)
+}
+
+fn lifetime(key: &str, default: i64) -> i64 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+/// Starts a session for `user` and returns its tokens. The session lasts until its refresh
+/// token expires, or the user logs out.
+pub(crate) fn create_jwt(db: &LocalDB, user: User) -> Result<JwtSession, AppError> {
+    let now = Utc::now().timestamp();
+    let (access_exp, refresh_exp) = (access_token_lifetime(), refresh_token_lifetime());
+
+    let jti = Uuid::new_v4().to_string();
+    let refresh_token = crypto::random_token();
+
+    let access = AccessToken {
+        jti: &jti,
+        expires_at: now + access_exp,
+    };
+    let sid = db.sessions().create(
+        &user.id,
+        user.is_guest,
+        &access,
+        &crypto::hash_token(&refresh_token),
+        now + refresh_exp,
+    )?;
 
     let claims = Claims {
         sub: user.id,
+        sid,
+        jti: jti.clone(),
         is_guest: user.is_guest,
-        exp,
+        exp: access.expires_at as usize,
     };
 
-    let header = Header::new(Algorithm::HS512);
+    Ok(JwtSession {
+        token: encode_jwt(&claims)?,
+        exp: access_exp as usize,
+        refresh_token,
+        refresh_exp: refresh_exp as usize,
+    })
+}
 
-    let secret = get_env("BASABLE_JWT_SECRET");
-    let secret = secret.as_bytes();
+/// Issues new tokens for the session of `refresh_token`. The refresh token can only be used
+/// once, and the session's previous access token is revoked.
+pub(crate) fn refresh_jwt(db: &LocalDB, refresh_token: &str) -> Result<JwtSession, AppError> {
+    let now = Utc::now().timestamp();
+    let (access_exp, refresh_exp) = (access_token_lifetime(), refresh_token_lifetime());
+
+    let jti = Uuid::new_v4().to_string();
+    let new_refresh_token = crypto::random_token();
 
-    let token = encode(&header, &claims, &EncodingKey::from_secret(secret))
-        .map_err(|e| AppError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let access = AccessToken {
+        jti: &jti,
+        expires_at: now + access_exp,
+    };
+    let session = db.sessions().refresh(
+        &crypto::hash_token(refresh_token),
+        &access,
+        &crypto::hash_token(&new_refresh_token),
+        now + refresh_exp,
+    )?;
+
+    let Some(session) = session else {
+        return Err(AppError::new(
+            StatusCode::UNAUTHORIZED,
+            "Invalid refresh token!",
+        ));
+    };
+
+    let claims = Claims {
+        sub: session.user_id,
+        sid: session.id,
+        jti: jti.clone(),
+        is_guest: session.is_guest,
+        exp: access.expires_at as usize,
+    };
 
     Ok(JwtSession {
-        token,
-        exp: exp_time as usize,
+        token: encode_jwt(&claims)?,
+        exp: access_exp as usize,
+        refresh_token: new_refresh_token,
+        refresh_exp: refresh_exp as usize,
     })
 }
 
-pub(crate) fn decode_jwt(header_value: &HeaderValue) -> Result<User, AppError> {
+fn encode_jwt(claims: &Claims) -> Result<String, AppError> {
+    let header = Header::new(Algorithm::HS512);
+
+    let secret = get_env("BASABLE_JWT_SECRET");
+    let secret = secret.as_bytes();
+
+    encode(&header, claims, &EncodingKey::from_secret(secret))
+        .map_err(|e| AppError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Get the [`User`] of the token in `header_value`. Revoked tokens are rejected.
+pub(crate) fn decode_jwt(db: &LocalDB, header_value: &HeaderValue) -> Result<User, AppError> {
     let token = extract_jwt(header_value)?;
 
     let secret = get_env("BASABLE_JWT_SECRET");
@@ -87,9 +482,53 @@ pub(crate) fn decode_jwt(header_value: &HeaderValue) -> Result<User, AppError> {
     )
     .map_err(|e| AppError(StatusCode::UNAUTHORIZED, e.to_string()))?;
 
-    let Claims { sub, is_guest, .. } = decoded.claims;
+    let Claims {
+        sub,
+        sid,
+        jti,
+        is_guest,
+        ..
+    } = decoded.claims;
+
+    // Tokens issued before user ids were UUIDs carry an IP address.
+    if Uuid::parse_str(&sub).is_err() {
+        return Err(AppError::new(StatusCode::UNAUTHORIZED, "Invalid token!"));
+    }
+
+    if db.sessions().is_revoked(&jti)? {
+        return Err(AppError::new(
+            StatusCode::UNAUTHORIZED,
+            "Token has been revoked.",
+        ));
+    }
 
-    Ok(User { id: sub, is_guest })
+    Ok(User {
+        id: sub,
+        is_guest,
+        session_id: Some(sid),
+        api_key: None,
+    })
+}
+
+/// Get the [`User`] of the API key in `header_value`, limited to the key's scope. Revoked
+/// keys are rejected.
+pub(crate) fn decode_api_key(db: &LocalDB, header_value: &HeaderValue) -> Result<User, AppError> {
+    let invalid = || AppError::new(StatusCode::UNAUTHORIZED, "Invalid API key.");
+
+    let key = header_value.to_str().map_err(|_| invalid())?;
+    if !key.starts_with(API_KEY_PREFIX) {
+        return Err(invalid());
+    }
+
+    match db.api_keys().authenticate(&crypto::hash_token(key))? {
+        Some((id, scope)) => Ok(User {
+            id,
+            is_guest: false,
+            session_id: None,
+            api_key: Some(scope),
+        }),
+        None => Err(invalid()),
+    }
 }
 
 fn extract_jwt(header_value: &HeaderValue) -> Result<String, AppError> {
@@ -1,3 +1,6 @@
 pub static QUERY_FILTER_PREFIX: &str = "basable_filter:";
 pub static BASABLE_CHRONO_XCOL: &str = "BASABLE_CHRONO_BASIS_VALUE";
-pub static BASABLE_CHRONO_YCOL: &str = "BASABLE_CHRONO_RESULT";
\ No newline at end of file
+pub static BASABLE_CHRONO_YCOL: &str = "BASABLE_CHRONO_RESULT";
+pub static BASABLE_CATEGORY_COL: &str = "BASABLE_CATEGORY";
+pub static BASABLE_CATEGORY_VALUE: &str = "BASABLE_CATEGORY_VALUE";
+pub static BASABLE_SERIES_COL: &str = "BASABLE_SERIES";
\ No newline at end of file
@@ -15,7 +15,13 @@ use axum::{
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
-use crate::base::AppState;
+use crate::{
+    base::{
+        local::{local_db_path, LocalDB},
+        AppState,
+    },
+    utils::crypto,
+};
 
 use super::routes::core_routes;
 
@@ -38,8 +44,15 @@ pub fn app() -> IntoMakeServiceWithConnectInfo<Router<()>, std::net::SocketAddr>
         .allow_origin("http://localhost:5173".parse::<HeaderValue>().unwrap())
         .allow_headers([ACCEPT, ACCESS_CONTROL_ALLOW_HEADERS, CONTENT_TYPE]);
 
-    let state = AppState::default();
-    state.setup_local_db();
+    crypto::check_encryption_key().expect("Invalid encryption key");
+
+    let path = local_db_path();
+    let local_db = LocalDB::open(&path).expect("Failed to open the local database");
+
+    let state = AppState::new(local_db);
+    state
+        .setup_local_db()
+        .expect("Failed to set up the local database");
     
     let routes = core_routes();
 
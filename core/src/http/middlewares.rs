@@ -7,13 +7,14 @@ use axum::{
 
 use crate::base::{
     imp::{SharedDB, SharedTable},
-    user::{decode_jwt, User},
+    user::{decode_api_key, decode_jwt, User},
     AppError, AppState,
 };
 
 /// Extracts information about the current [`User`] by inspecting the Authorization
 /// header. If Authorization is not provided, it checks for `B-Session-Id`, which should
-/// be provided for guest users. If none of this is found, the `User` is `None`.
+/// be provided for guest users, and then for an API key in `B-Api-Key`. If none of this
+/// is found, the request is rejected.
 pub(crate) struct AuthExtractor(pub User);
 
 #[async_trait]
@@ -24,20 +25,28 @@ where
 {
     type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let mut auth_header = parts.headers.get(AUTHORIZATION);
 
         // If Authorization header does not exist, use session-id to retrieve guest user.
-        if let None = auth_header {
+        if auth_header.is_none() {
             auth_header = parts.headers.get("B-Session-Id");
         }
 
-        match auth_header {
-            Some(hv) => match decode_jwt(hv) {
-                Ok(user) => Ok(AuthExtractor(user)),
-                Err(e) => Err(e),
-            },
-            None => {
+        let state = AppState::from_ref(state);
+
+        match (auth_header, parts.headers.get("B-Api-Key")) {
+            (Some(hv), _) => {
+                let user = decode_jwt(&state.local_db, hv)?;
+
+                Ok(AuthExtractor(user))
+            }
+            (None, Some(hv)) => {
+                let user = decode_api_key(&state.local_db, hv)?;
+
+                Ok(AuthExtractor(user))
+            }
+            (None, None) => {
                 let err = AppError::new(
                     StatusCode::UNAUTHORIZED,
                     "User authentication not provided.",
@@ -63,24 +72,27 @@ where
 
         let state = extract_app_state(parts, state).await;
 
-        let conn_id = match parts.headers.get("Connection-Id") {
-            Some(h) => Some(h.to_str().unwrap()),
-            None => None,
-        };
+        let conn_id = parts
+            .headers
+            .get("Connection-Id")
+            .map(|h| h.to_str().unwrap());
 
-        if let None = conn_id {
+        if conn_id.is_none() {
             return Err(AppError::new(
                 StatusCode::PRECONDITION_REQUIRED,
                 "Connection Id not provided",
             ));
         }
 
-        let bsbl = state.instance.lock().unwrap();
-        let db = bsbl.get_connection(conn_id.unwrap(), &user.id);
-        std::mem::drop(bsbl); // release Mutex lock
+        let db = state.get_connection(conn_id.unwrap(), &user)?;
 
         match db {
-            Some(db) => Ok(DbExtractor(db)),
+            // Requests that change data need a role with write access.
+            Some((_, role)) if !parts.method.is_safe() && !role.can_write() => Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                "You only have read access to this connection.",
+            )),
+            Some((db, _)) => Ok(DbExtractor(db)),
             None => Err(AppError::new(
                 StatusCode::PRECONDITION_FAILED,
                 "You do not have access to this connection.",
@@ -0,0 +1,233 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, post},
+    Json, Router,
+};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+
+use crate::{
+    base::{
+        local::api_keys::ApiKey,
+        user::{ApiKeyAccess, NewApiKey, User},
+        AppError, AppState,
+    },
+    http::middlewares::AuthExtractor,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct ApiKeyForm {
+    pub name: String,
+    pub access: ApiKeyAccess,
+
+    /// Ids of the saved connections the key can be used with.
+    pub connections: Vec<String>,
+}
+
+/// API keys are managed by registered users through a session, so that a key can't be used
+/// to create other keys.
+fn with_session(user: &User) -> Result<(), AppError> {
+    match (user.is_guest, &user.api_key) {
+        (true, _) => Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Guest users can't create API keys.",
+        )),
+        (_, Some(_)) => Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "API keys can't be managed with an API key.",
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[debug_handler]
+/// POST: /core/api-keys
+///
+/// Creates an API key of the current user. The key is only returned by this request.
+pub(crate) async fn create_api_key(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+    Json(form): Json<ApiKeyForm>,
+) -> Result<Json<NewApiKey>, AppError> {
+    with_session(&user)?;
+    let key = user.create_api_key(&state.local_db, &form.name, form.access, &form.connections)?;
+
+    Ok(Json(key))
+}
+
+#[debug_handler]
+/// GET: /core/api-keys
+///
+/// Lists the API keys of the current user, including revoked keys.
+pub(crate) async fn list_api_keys(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ApiKey>>, AppError> {
+    with_session(&user)?;
+    let keys = state.local_db.api_keys().list(&user.id)?;
+
+    Ok(Json(keys))
+}
+
+#[debug_handler]
+/// DELETE: /core/api-keys/:key_id
+///
+/// Revokes an API key of the current user. Requests with the key are rejected from then on.
+pub(crate) async fn revoke_api_key(
+    Path(key_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    with_session(&user)?;
+
+    match state.local_db.api_keys().revoke(&key_id, &user.id)? {
+        true => Ok("Operation successful".to_string()),
+        false => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find an active API key with the given id",
+        )),
+    }
+}
+
+/// Routes for the API keys of registered users
+pub(super) fn api_keys_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_api_key).get(list_api_keys))
+        .route("/:key_id", delete(revoke_api_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::{FromRequestParts, Path, State},
+        http::{Method, Request, StatusCode},
+        Json,
+    };
+
+    use crate::{
+        base::{user::ApiKeyAccess, AppError, AppState},
+        http::{
+            middlewares::{AuthExtractor, DbExtractor},
+            routes::connect,
+        },
+        tests::{
+            common::{create_test_config, create_test_state},
+            extractors::auth_extractor,
+        },
+    };
+
+    use super::{create_api_key, list_api_keys, revoke_api_key, ApiKeyForm};
+
+    /// Extracts the connection of a `method` request made with API key `key`.
+    async fn extract_db(
+        state: &AppState,
+        key: &str,
+        conn_id: &str,
+        method: Method,
+    ) -> Result<DbExtractor, AppError> {
+        let (mut parts, _) = Request::builder()
+            .method(method)
+            .header("B-Api-Key", key)
+            .header("Connection-Id", conn_id)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        DbExtractor::from_request_parts(&mut parts, state).await
+    }
+
+    #[tokio::test]
+    async fn test_api_keys() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let conn_id = details.id;
+
+        let form = |access, connections: &[&str]| {
+            Json(ApiKeyForm {
+                name: "CI".to_string(),
+                access,
+                connections: connections.iter().map(|c| c.to_string()).collect(),
+            })
+        };
+
+        let unknown = create_api_key(
+            auth_extractor(),
+            State(state.clone()),
+            form(ApiKeyAccess::Read, &["unknown"]),
+        )
+        .await;
+        assert!(matches!(unknown, Err(AppError(StatusCode::NOT_FOUND, _))));
+
+        let Json(key) = create_api_key(
+            auth_extractor(),
+            State(state.clone()),
+            form(ApiKeyAccess::Read, &[&conn_id]),
+        )
+        .await?;
+        assert!(key.key.starts_with(&key.details.prefix));
+
+        // Read keys can only be used to read their connections.
+        extract_db(&state, &key.key, &conn_id, Method::GET).await?;
+        let write = extract_db(&state, &key.key, &conn_id, Method::POST).await;
+        assert!(matches!(write, Err(AppError(StatusCode::FORBIDDEN, _))));
+        let Json(other) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let other_conn = extract_db(&state, &key.key, &other.id, Method::GET).await;
+        assert!(matches!(
+            other_conn,
+            Err(AppError(StatusCode::PRECONDITION_FAILED, _))
+        ));
+        let invalid = extract_db(&state, "bsk_invalid", &conn_id, Method::GET).await;
+        assert!(matches!(
+            invalid,
+            Err(AppError(StatusCode::UNAUTHORIZED, _))
+        ));
+
+        let Json(keys) = list_api_keys(auth_extractor(), State(state.clone())).await?;
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].last_used_at.is_some());
+
+        // Keys can't be managed with a key.
+        let (mut parts, _) = Request::builder()
+            .header("B-Api-Key", &key.key)
+            .body(())
+            .unwrap()
+            .into_parts();
+        let with_key = AuthExtractor::from_request_parts(&mut parts, &state).await?;
+        let listed = list_api_keys(with_key, State(state.clone())).await;
+        assert!(matches!(listed, Err(AppError(StatusCode::FORBIDDEN, _))));
+
+        let Json(write_key) = create_api_key(
+            auth_extractor(),
+            State(state.clone()),
+            form(ApiKeyAccess::Write, &[&conn_id]),
+        )
+        .await?;
+        extract_db(&state, &write_key.key, &conn_id, Method::POST).await?;
+
+        revoke_api_key(
+            Path(key.details.id.clone()),
+            auth_extractor(),
+            State(state.clone()),
+        )
+        .await?;
+        let revoked = extract_db(&state, &key.key, &conn_id, Method::GET).await;
+        assert!(matches!(
+            revoked,
+            Err(AppError(StatusCode::UNAUTHORIZED, _))
+        ));
+
+        Ok(())
+    }
+}
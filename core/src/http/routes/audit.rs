@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use axum_macros::debug_handler;
+
+use crate::{
+    base::{
+        local::audit::{AuditFilter, AuditPage},
+        AppError, AppState,
+    },
+    http::middlewares::{AuthExtractor, DbExtractor},
+};
+
+#[debug_handler]
+/// GET: /core/audit
+///
+/// Lists the changes made to the data and table configurations of the current connection,
+/// newest first. The changes can be filtered and paginated with [`AuditFilter`] query params.
+pub(crate) async fn query_audit_log(
+    Query(filter): Query<AuditFilter>,
+    AuthExtractor(_): AuthExtractor,
+    DbExtractor(db): DbExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<AuditPage>, AppError> {
+    let conn_id = db.id().to_string();
+    let page = state.local_db.audit().query(&conn_id, &filter)?;
+
+    Ok(Json(page))
+}
+
+/// Routes for the audit log of connections
+pub(super) fn audit_routes() -> Router<AppState> {
+    Router::new().route("/", get(query_audit_log))
+}
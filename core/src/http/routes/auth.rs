@@ -1,50 +1,379 @@
-use std::net::SocketAddr;
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+use uuid::Uuid;
 
-use axum::{
-    extract::{ConnectInfo, State},
-    routing::post,
-    Json, Router,
+use crate::{
+    base::{
+        foundation::Basable,
+        user::{create_jwt, refresh_jwt, JwtSession, User},
+        AppError, AppState,
+    },
+    http::middlewares::AuthExtractor,
 };
-use axum_macros::debug_handler;
 
-use crate::base::{foundation::Basable, user::JwtSession, AppError, AppState};
+#[derive(Deserialize)]
+pub(crate) struct SignupForm {
+    pub email: String,
+    pub username: Option<String>,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LoginForm {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RefreshForm {
+    pub refresh_token: String,
+}
 
 #[debug_handler]
 /// POST: /core/auth/guest
 ///
 /// Creates a Basable guest `User` and returns a `JwtSession`.
-async fn create_guest_user(
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    State(_): State<AppState>,
+async fn create_guest_user(State(state): State<AppState>) -> Result<Json<JwtSession>, AppError> {
+    let session = Basable::create_guest_user(&state.local_db)?;
+
+    Ok(Json(session))
+}
+
+#[debug_handler]
+/// POST: /core/auth/signup
+///
+/// Registers a new `User` account and returns a `JwtSession` for it.
+async fn signup(
+    State(state): State<AppState>,
+    Json(form): Json<SignupForm>,
+) -> Result<Json<JwtSession>, AppError> {
+    let user = User::register(
+        &state.local_db,
+        Uuid::new_v4().to_string(),
+        &form.email,
+        form.username.as_deref(),
+        &form.password,
+    )?;
+
+    let session = create_jwt(&state.local_db, user)?;
+    Ok(Json(session))
+}
+
+#[debug_handler]
+/// POST: /core/auth/login
+///
+/// Logs a registered `User` in with their email and password, and returns a `JwtSession`.
+async fn login(
+    State(state): State<AppState>,
+    Json(form): Json<LoginForm>,
+) -> Result<Json<JwtSession>, AppError> {
+    let user = User::login(&state.local_db, &form.email, &form.password)?;
+
+    let session = create_jwt(&state.local_db, user)?;
+    Ok(Json(session))
+}
+
+#[debug_handler]
+/// POST: /core/auth/refresh
+///
+/// Returns a new `JwtSession` for the session of a refresh token. The refresh token can only
+/// be used once.
+async fn refresh(
+    State(state): State<AppState>,
+    Json(form): Json<RefreshForm>,
 ) -> Result<Json<JwtSession>, AppError> {
-    let addr = addr.ip().to_string();
-    let session = Basable::create_guest_user(&addr)?;
+    let session = refresh_jwt(&state.local_db, &form.refresh_token)?;
 
     Ok(Json(session))
 }
 
+#[debug_handler]
+/// POST: /core/auth/logout
+///
+/// Ends the current session. Its tokens are not accepted afterwards.
+async fn logout(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    user.with_session()?;
+    user.logout(&state.local_db)?;
+
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// POST: /core/auth/revoke-all
+///
+/// Ends all sessions of the current user, on every device.
+async fn revoke_all(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    user.with_session()?;
+    user.logout_all(&state.local_db)?;
+
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// POST: /core/auth/upgrade
+///
+/// Registers an account for the current guest `User`. The account keeps the guest's id, and
+/// the guest's open connections are saved for it. The guest session is ended, and a
+/// `JwtSession` of the account is returned.
+async fn upgrade_guest(
+    AuthExtractor(guest): AuthExtractor,
+    State(state): State<AppState>,
+    Json(form): Json<SignupForm>,
+) -> Result<Json<JwtSession>, AppError> {
+    guest.with_session()?;
+    if !guest.is_guest {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "User is already registered.",
+        ));
+    }
+
+    let user = User::register(
+        &state.local_db,
+        guest.id.clone(),
+        &form.email,
+        form.username.as_deref(),
+        &form.password,
+    )?;
+
+    let connections = state.instance.lock().unwrap().user_connections(&user.id);
+    for db in connections {
+        let conn_id = db.id().to_string();
+        user.save_connection(&state.local_db, &conn_id, db.connector().config())?;
+    }
+
+    guest.logout(&state.local_db)?;
+
+    let session = create_jwt(&state.local_db, user)?;
+    Ok(Json(session))
+}
+
 /// Routes for user session management and authentication
 pub(super) fn auth_routes() -> Router<AppState> {
-    Router::new().route("/guest", post(create_guest_user))
+    Router::new()
+        .route("/guest", post(create_guest_user))
+        .route("/signup", post(signup))
+        .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/revoke-all", post(revoke_all))
+        .route("/upgrade", post(upgrade_guest))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use axum::{
+        extract::State,
+        http::{HeaderValue, StatusCode},
+        Json,
+    };
 
-    use axum::extract::{ConnectInfo, State};
+    use crate::{
+        base::{
+            foundation::Basable,
+            user::{decode_jwt, JwtSession, User},
+            AppError, AppState,
+        },
+        http::middlewares::AuthExtractor,
+        tests::{
+            common::{create_test_config, create_test_state},
+            extractors::api_key_extractor,
+        },
+    };
 
-    use crate::{base::AppError, http::routes::auth::create_guest_user, tests::common::create_test_state};
+    use super::{
+        create_guest_user, login, logout, refresh, revoke_all, signup, upgrade_guest, LoginForm,
+        RefreshForm, SignupForm,
+    };
+
+    fn session_user(state: &AppState, session: &JwtSession) -> Result<User, AppError> {
+        let bearer = format!("Bearer {}", session.token);
+        decode_jwt(&state.local_db, &HeaderValue::from_str(&bearer).unwrap())
+    }
+
+    fn signup_form(email: &str) -> SignupForm {
+        SignupForm {
+            email: email.to_string(),
+            username: None,
+            password: "p@ssword".to_string(),
+        }
+    }
+
+    fn refresh_form(session: &JwtSession) -> Json<RefreshForm> {
+        Json(RefreshForm {
+            refresh_token: session.refresh_token.clone(),
+        })
+    }
+
+    fn is_unauthorized<T>(res: Result<T, AppError>) -> bool {
+        matches!(res, Err(AppError(StatusCode::UNAUTHORIZED, _)))
+    }
+
+    fn is_forbidden<T>(res: Result<T, AppError>) -> bool {
+        matches!(res, Err(AppError(StatusCode::FORBIDDEN, _)))
+    }
 
     #[tokio::test]
     async fn test_create_guest() -> Result<(), AppError> {
         let state = create_test_state(false)?;
-        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 3000);
 
-        let create_guest = create_guest_user(ConnectInfo(addr), State(state)).await;
+        let create_guest = create_guest_user(State(state)).await;
 
         assert!(create_guest.is_ok());
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_signup_login_logout() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let form = Json(signup_form("ada@example.com"));
+        let Json(first) = signup(State(state.clone()), form).await?;
+        let user = session_user(&state, &first)?;
+        assert!(!user.is_guest);
+        assert!(uuid::Uuid::parse_str(&user.id).is_ok());
+
+        let taken = signup(State(state.clone()), Json(signup_form("Ada@example.com"))).await;
+        assert!(matches!(taken, Err(AppError(StatusCode::CONFLICT, _))));
+
+        let form = |password: &str| {
+            Json(LoginForm {
+                email: "ADA@example.com".to_string(),
+                password: password.to_string(),
+            })
+        };
+        let wrong = login(State(state.clone()), form("password")).await;
+        assert!(is_unauthorized(wrong));
+        let unknown = LoginForm {
+            email: "grace@example.com".to_string(),
+            password: "p@ssword".to_string(),
+        };
+        assert!(is_unauthorized(
+            login(State(state.clone()), Json(unknown)).await
+        ));
+
+        let Json(session) = login(State(state.clone()), form("p@ssword")).await?;
+        let logged = session_user(&state, &session)?;
+        assert_eq!(logged.id, user.id);
+
+        logout(AuthExtractor(logged), State(state.clone())).await?;
+        assert!(is_unauthorized(session_user(&state, &session)));
+        assert!(is_unauthorized(
+            refresh(State(state.clone()), refresh_form(&session)).await
+        ));
+        // Other sessions of the user are not ended.
+        assert!(session_user(&state, &first).is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_and_revoke_all() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(first) =
+            signup(State(state.clone()), Json(signup_form("bob@example.com"))).await?;
+        let Json(refreshed) = refresh(State(state.clone()), refresh_form(&first)).await?;
+
+        // The previous tokens of the session are not accepted anymore.
+        assert!(is_unauthorized(session_user(&state, &first)));
+        assert!(is_unauthorized(
+            refresh(State(state.clone()), refresh_form(&first)).await
+        ));
+        let user = session_user(&state, &refreshed)?;
+
+        let Json(second) = login(
+            State(state.clone()),
+            Json(LoginForm {
+                email: "bob@example.com".to_string(),
+                password: "p@ssword".to_string(),
+            }),
+        )
+        .await?;
+
+        revoke_all(AuthExtractor(user), State(state.clone())).await?;
+        assert!(is_unauthorized(session_user(&state, &refreshed)));
+        assert!(is_unauthorized(session_user(&state, &second)));
+        assert!(is_unauthorized(
+            refresh(State(state.clone()), refresh_form(&second)).await
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_denied() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(session) =
+            signup(State(state.clone()), Json(signup_form("carl@example.com"))).await?;
+        let user = session_user(&state, &session)?;
+        let key = || {
+            let AuthExtractor(mut key_user) = api_key_extractor(&[]);
+            key_user.id = user.id.clone();
+            AuthExtractor(key_user)
+        };
+
+        // API keys can't end the sessions of their user.
+        assert!(is_forbidden(logout(key(), State(state.clone())).await));
+        assert!(is_forbidden(revoke_all(key(), State(state.clone())).await));
+        let upgraded = upgrade_guest(
+            key(),
+            State(state.clone()),
+            Json(signup_form("other@example.com")),
+        )
+        .await;
+        assert!(is_forbidden(upgraded));
+        assert!(session_user(&state, &session).is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_guest() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(session) = create_guest_user(State(state.clone())).await?;
+        let guest = session_user(&state, &session)?;
+
+        // Guests can't open file data sources, such as the SQLite test database, through
+        // `/core/connect`.
+        let db = Basable::create_connection(&create_test_config(), guest.id.clone())?;
+        state.instance.lock().unwrap().add_connection(&db);
+        let guest_id = guest.id.clone();
+
+        let Json(upgraded) = upgrade_guest(
+            AuthExtractor(guest),
+            State(state.clone()),
+            Json(signup_form("guest@example.com")),
+        )
+        .await?;
+        let user = session_user(&state, &upgraded)?;
+        assert_eq!(user.id, guest_id);
+        assert!(!user.is_guest);
+        assert!(is_unauthorized(session_user(&state, &session)));
+
+        // The guest's connection now belongs to the account.
+        let saved = state.local_db.connections().list(&user.id)?;
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].id, db.id().to_string());
+
+        let again = upgrade_guest(
+            AuthExtractor(user),
+            State(state),
+            Json(signup_form("other@example.com")),
+        )
+        .await;
+        assert!(matches!(again, Err(AppError(StatusCode::BAD_REQUEST, _))));
+
+        Ok(())
+    }
 }
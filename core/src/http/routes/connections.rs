@@ -0,0 +1,499 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, patch, post, put},
+    Json, Router,
+};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+
+use crate::{
+    base::{
+        config::SavedConnection,
+        local::members::Member,
+        user::{Role, User},
+        AppError, AppState,
+    },
+    http::middlewares::AuthExtractor,
+    imp::database::DbConnectionDetails,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct RenameConnection {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MemberRole {
+    pub role: Role,
+}
+
+/// Connections are only saved for registered users, and managed through a session.
+fn registered(user: &User) -> Result<(), AppError> {
+    user.with_session()?;
+
+    match user.is_guest {
+        true => Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Guest users can't save connections.",
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Only the owner of a saved connection can manage its members.
+fn owned(state: &AppState, conn_id: &str, user: &User) -> Result<(), AppError> {
+    registered(user)?;
+
+    match state.local_db.connections().owner(conn_id)? {
+        Some(owner) if owner == user.id => Ok(()),
+        _ => Err(not_found()),
+    }
+}
+
+fn not_found() -> AppError {
+    AppError::new(
+        StatusCode::NOT_FOUND,
+        "Can't find a saved connection with the given id",
+    )
+}
+
+#[debug_handler]
+/// GET: /core/connections
+///
+/// Lists the saved connections of the current user. Passwords are not included.
+pub(crate) async fn list_connections(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SavedConnection>>, AppError> {
+    registered(&user)?;
+    let connections = state.local_db.connections().list(&user.id)?;
+
+    Ok(Json(connections))
+}
+
+#[debug_handler]
+/// PATCH: /core/connections/:conn_id
+///
+/// Renames a saved connection.
+pub(crate) async fn rename_connection(
+    Path(conn_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+    Json(body): Json<RenameConnection>,
+) -> Result<String, AppError> {
+    registered(&user)?;
+
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "Connection name can't be empty.",
+        ));
+    }
+
+    match state
+        .local_db
+        .connections()
+        .rename(&user.id, &conn_id, name)?
+    {
+        true => Ok("Operation successful".to_string()),
+        false => Err(not_found()),
+    }
+}
+
+#[debug_handler]
+/// DELETE: /core/connections/:conn_id
+///
+/// Deletes a saved connection, and closes it if it is open.
+pub(crate) async fn delete_connection(
+    Path(conn_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    registered(&user)?;
+
+    if !state.local_db.connections().delete(&user.id, &conn_id)? {
+        return Err(not_found());
+    }
+
+    if let Ok(id) = conn_id.parse() {
+        let mut bsbl = state.instance.lock().unwrap();
+        bsbl.remove_connection(&id, &user.id);
+    }
+
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// POST: /core/connections/:conn_id/open
+///
+/// Opens a saved connection, under its saved id, and returns its details like `/core/connect`.
+pub(crate) async fn open_connection(
+    Path(conn_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<DbConnectionDetails>, AppError> {
+    registered(&user)?;
+
+    match state.get_connection(&conn_id, &user)? {
+        Some((db, _)) => Ok(Json(db.details()?)),
+        None => Err(not_found()),
+    }
+}
+
+#[debug_handler]
+/// GET: /core/connections/:conn_id/members
+///
+/// Lists the users who have a role on a saved connection of the current user.
+pub(crate) async fn list_members(
+    Path(conn_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Member>>, AppError> {
+    owned(&state, &conn_id, &user)?;
+    let members = state.local_db.members().list(&conn_id)?;
+
+    Ok(Json(members))
+}
+
+#[debug_handler]
+/// PUT: /core/connections/:conn_id/members/:user_id
+///
+/// Gives a registered user the `editor` or `viewer` role on a saved connection of the
+/// current user.
+pub(crate) async fn set_member(
+    Path((conn_id, member_id)): Path<(String, String)>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+    Json(body): Json<MemberRole>,
+) -> Result<String, AppError> {
+    owned(&state, &conn_id, &user)?;
+
+    if body.role == Role::Owner || member_id == user.id {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "A connection can only have one owner.",
+        ));
+    }
+    if state.local_db.users().find_by_id(&member_id)?.is_none() {
+        return Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find a registered user with the given id",
+        ));
+    }
+
+    state
+        .local_db
+        .members()
+        .set(&conn_id, &member_id, body.role)?;
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// DELETE: /core/connections/:conn_id/members/:user_id
+///
+/// Removes a user's role on a saved connection. The owner can remove any member, and
+/// members can remove themselves.
+pub(crate) async fn remove_member(
+    Path((conn_id, member_id)): Path<(String, String)>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    registered(&user)?;
+    if member_id != user.id {
+        owned(&state, &conn_id, &user)?;
+    }
+
+    match state.local_db.members().remove(&conn_id, &member_id)? {
+        true => Ok("Operation successful".to_string()),
+        false => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "The user has no role on this connection",
+        )),
+    }
+}
+
+/// Routes for the saved connections of registered users
+pub(super) fn connections_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_connections))
+        .route(
+            "/:conn_id",
+            patch(rename_connection).delete(delete_connection),
+        )
+        .route("/:conn_id/open", post(open_connection))
+        .route("/:conn_id/members", get(list_members))
+        .route(
+            "/:conn_id/members/:user_id",
+            put(set_member).delete(remove_member),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        extract::{FromRequestParts, Path, State},
+        http::{header::AUTHORIZATION, Method, Request, StatusCode},
+        Json,
+    };
+
+    use crate::{
+        base::{
+            user::{create_jwt, Role, User},
+            AppError, AppState,
+        },
+        http::{
+            middlewares::{AuthExtractor, DbExtractor},
+            routes::connect,
+        },
+        tests::{
+            common::{create_test_config, create_test_state, get_test_user_id},
+            extractors::{api_key_extractor, auth_extractor},
+        },
+    };
+
+    use super::{
+        delete_connection, list_connections, list_members, open_connection, remove_member,
+        rename_connection, set_member, MemberRole, RenameConnection,
+    };
+
+    fn is_forbidden<T>(res: Result<T, AppError>) -> bool {
+        matches!(res, Err(AppError(StatusCode::FORBIDDEN, _)))
+    }
+
+    /// Extracts the connection of a `method` request by `user`.
+    async fn extract_db(
+        state: &AppState,
+        user: User,
+        conn_id: &str,
+        method: Method,
+    ) -> Result<DbExtractor, AppError> {
+        let session = create_jwt(&state.local_db, user)?;
+
+        let (mut parts, _) = Request::builder()
+            .method(method)
+            .header(AUTHORIZATION, format!("Bearer {}", session.token))
+            .header("Connection-Id", conn_id)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        DbExtractor::from_request_parts(&mut parts, state).await
+    }
+
+    #[tokio::test]
+    async fn test_saved_connections() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+
+        let Json(saved) = list_connections(auth_extractor(), State(state.clone())).await?;
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].id, details.id);
+
+        rename_connection(
+            Path(details.id.clone()),
+            auth_extractor(),
+            State(state.clone()),
+            Json(RenameConnection {
+                name: "Sales".to_string(),
+            }),
+        )
+        .await?;
+        let Json(saved) = list_connections(auth_extractor(), State(state.clone())).await?;
+        assert_eq!(saved[0].name, "Sales");
+
+        // After a restart, the connection is opened again under the same id.
+        let restarted = AppState::new(state.local_db.clone());
+        let user = auth_extractor().0;
+        let (db, role) = restarted.get_connection(&details.id, &user)?.unwrap();
+        assert_eq!(role, Role::Owner);
+        assert_eq!(db.id().to_string(), details.id);
+
+        let Json(reopened) = open_connection(
+            Path(details.id.clone()),
+            auth_extractor(),
+            State(restarted.clone()),
+        )
+        .await?;
+        assert_eq!(reopened.id, details.id);
+        assert_eq!(restarted.instance.lock().unwrap().connections.len(), 1);
+
+        // Only the owner can open a saved connection.
+        let other = User {
+            id: format!("{}_other", get_test_user_id()),
+            is_guest: false,
+            ..Default::default()
+        };
+        assert!(restarted.get_connection(&details.id, &other)?.is_none());
+
+        delete_connection(
+            Path(details.id.clone()),
+            auth_extractor(),
+            State(restarted.clone()),
+        )
+        .await?;
+        assert!(restarted.get_connection(&details.id, &user)?.is_none());
+
+        let deleted = open_connection(Path(details.id), auth_extractor(), State(restarted)).await;
+        assert!(matches!(deleted, Err(AppError(StatusCode::NOT_FOUND, _))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connection_roles() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let conn_id = details.id;
+
+        let member = User::register(
+            &state.local_db,
+            uuid::Uuid::new_v4().to_string(),
+            "analyst@example.com",
+            None,
+            "p@ssword",
+        )?;
+        let member_id = member.id.clone();
+        let as_member = || User {
+            id: member_id.clone(),
+            is_guest: false,
+            ..Default::default()
+        };
+
+        // Users without a role can't use the connection.
+        let denied = extract_db(&state, as_member(), &conn_id, Method::GET).await;
+        assert!(matches!(
+            denied,
+            Err(AppError(StatusCode::PRECONDITION_FAILED, _))
+        ));
+
+        let role = |role| Json(MemberRole { role });
+        let path = || Path((conn_id.clone(), member_id.clone()));
+        set_member(
+            path(),
+            auth_extractor(),
+            State(state.clone()),
+            role(Role::Viewer),
+        )
+        .await?;
+
+        let Json(members) = list_members(
+            Path(conn_id.clone()),
+            auth_extractor(),
+            State(state.clone()),
+        )
+        .await?;
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].role, Role::Viewer);
+        let not_owner = list_members(
+            Path(conn_id.clone()),
+            AuthExtractor(as_member()),
+            State(state.clone()),
+        )
+        .await;
+        assert!(matches!(not_owner, Err(AppError(StatusCode::NOT_FOUND, _))));
+
+        // Viewers can read, and share the owner's open connection.
+        let DbExtractor(db) = extract_db(&state, as_member(), &conn_id, Method::GET).await?;
+        let (owned, _) = state
+            .get_connection(&conn_id, &auth_extractor().0)?
+            .unwrap();
+        assert!(Arc::ptr_eq(&db, &owned));
+
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            let write = extract_db(&state, as_member(), &conn_id, method).await;
+            assert!(matches!(write, Err(AppError(StatusCode::FORBIDDEN, _))));
+        }
+
+        set_member(
+            path(),
+            auth_extractor(),
+            State(state.clone()),
+            role(Role::Editor),
+        )
+        .await?;
+        assert!(extract_db(&state, as_member(), &conn_id, Method::DELETE)
+            .await
+            .is_ok());
+
+        let owner = set_member(
+            path(),
+            auth_extractor(),
+            State(state.clone()),
+            role(Role::Owner),
+        );
+        assert!(matches!(
+            owner.await,
+            Err(AppError(StatusCode::BAD_REQUEST, _))
+        ));
+
+        // Members can leave a connection.
+        remove_member(path(), AuthExtractor(as_member()), State(state.clone())).await?;
+        let denied = extract_db(&state, as_member(), &conn_id, Method::GET).await;
+        assert!(denied.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_denied() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let conn_id = details.id;
+        let key = || api_key_extractor(&[&conn_id]);
+
+        // API keys can only use the data of their connections.
+        let listed = list_connections(key(), State(state.clone())).await;
+        assert!(is_forbidden(listed));
+
+        let name = Json(RenameConnection {
+            name: "Sales".to_string(),
+        });
+        let renamed = rename_connection(Path(conn_id.clone()), key(), State(state.clone()), name);
+        assert!(is_forbidden(renamed.await));
+
+        let deleted = delete_connection(Path(conn_id.clone()), key(), State(state.clone())).await;
+        assert!(is_forbidden(deleted));
+
+        let opened = open_connection(Path(conn_id.clone()), key(), State(state.clone())).await;
+        assert!(is_forbidden(opened));
+
+        let members = list_members(Path(conn_id.clone()), key(), State(state.clone())).await;
+        assert!(is_forbidden(members));
+
+        let path = || Path((conn_id.clone(), get_test_user_id()));
+        let role = Json(MemberRole { role: Role::Viewer });
+        let set = set_member(path(), key(), State(state.clone()), role).await;
+        assert!(is_forbidden(set));
+
+        let removed = remove_member(path(), key(), State(state.clone())).await;
+        assert!(is_forbidden(removed));
+
+        // The connection is left as it was.
+        let Json(saved) = list_connections(auth_extractor(), State(state)).await?;
+        assert_eq!(saved.len(), 1);
+
+        Ok(())
+    }
+}
@@ -2,7 +2,6 @@ use std::collections::HashMap;
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
     routing::get,
     Json, Router,
 };
@@ -11,9 +10,8 @@ use axum_macros::debug_handler;
 use crate::{
     base::{
         imp::graphs::{
-            chrono::ChronoAnalysisOpts,
-            trend::{CrossOptions, TrendGraphOpts},
-            AnalysisResults,
+            category::CategoryGraphOpts, chrono::ChronoAnalysisOpts, trend::TrendGraphOpts,
+            AnalysisGraph,
         },
         AppError, AppState,
     },
@@ -21,43 +19,20 @@ use crate::{
 };
 
 #[debug_handler]
+/// GET: /core/graphs/chrono
+///
+/// Counts the rows of a table over time. See [`ChronoAnalysisOpts::from_query_params`] for
+/// the query params.
 pub async fn chrono_graph(
     Query(params): Query<HashMap<String, String>>,
     AuthExtractor(_): AuthExtractor,
     DbExtractor(db): DbExtractor,
     State(_): State<AppState>,
-) -> Result<Json<AnalysisResults>, AppError> {
-    let table = params.get("table");
-    let column = params.get("column");
-    let basis = params.get("basis");
-    let range = params.get("range");
+) -> Result<Json<AnalysisGraph>, AppError> {
+    let opts = ChronoAnalysisOpts::from_query_params(params)?;
+    let results = db.chrono_graph(opts)?;
 
-    match (table, column, basis, range) {
-        (Some(table), Some(column), Some(basis), Some(range)) => {
-            let basis = basis.to_owned().try_into();
-            let basis = basis
-                .map_err(|err: String| AppError::new(StatusCode::EXPECTATION_FAILED, err.as_str()));
-
-            let range = range.to_owned().try_into();
-            let range = range
-                .map_err(|err: String| AppError::new(StatusCode::EXPECTATION_FAILED, err.as_str()));
-
-            let opts = ChronoAnalysisOpts {
-                table: table.to_owned(),
-                chrono_col: column.to_owned(),
-                basis: basis?,
-                range: range?,
-            };
-
-            let results = db.chrono_graph(opts)?;
-
-            Ok(Json(results))
-        }
-        _ => Err(AppError::new(
-            StatusCode::EXPECTATION_FAILED,
-            "Missing query parameters",
-        )),
-    }
+    Ok(Json(results))
 }
 
 #[debug_handler]
@@ -66,15 +41,33 @@ pub async fn trend_graph(
     AuthExtractor(_): AuthExtractor,
     DbExtractor(db): DbExtractor,
     State(_): State<AppState>,
-) -> Result<Json<AnalysisResults>, AppError> {
+) -> Result<Json<AnalysisGraph>, AppError> {
     let opts = TrendGraphOpts::from_query_params(params)?;
     let graph = db.trend_graph(opts)?;
 
     Ok(Json(graph))
 }
 
+#[debug_handler]
+/// GET: /core/graphs/category
+///
+/// Counts the rows of a table by category. See [`CategoryGraphOpts::from_query_params`] for
+/// the query params.
+pub async fn category_graph(
+    Query(params): Query<HashMap<String, String>>,
+    AuthExtractor(_): AuthExtractor,
+    DbExtractor(db): DbExtractor,
+    State(_): State<AppState>,
+) -> Result<Json<AnalysisGraph>, AppError> {
+    let opts = CategoryGraphOpts::from_query_params(params)?;
+    let graph = db.category_graph(opts)?;
+
+    Ok(Json(graph))
+}
+
 pub(super) fn graphs_routes() -> Router<AppState> {
     Router::new()
         .route("/chrono", get(chrono_graph))
         .route("/trend", get(trend_graph))
+        .route("/category", get(category_graph))
 }
@@ -1,4 +1,5 @@
 use graphs::graphs_routes;
+use axum::http::StatusCode;
 use axum::routing::post;
 use axum::Router;
 
@@ -10,22 +11,40 @@ use crate::imp::database::DbConnectionDetails;
 use axum::{extract::State, Json};
 use axum_macros::debug_handler;
 
+use self::api_keys::api_keys_routes;
+use self::audit::audit_routes;
 use self::auth::auth_routes;
+use self::connections::connections_routes;
 use self::table::table_routes;
+use self::teams::teams_routes;
 
+pub(super) mod api_keys;
+pub(super) mod audit;
 pub(super) mod auth;
+pub(super) mod connections;
 pub(super) mod table;
+pub(super) mod teams;
 pub(super) mod graphs;
 
 #[debug_handler]
 /// POST: /core/connect
 ///
 /// Creates a new `BasableConnection` for current user. It expects `Config` as request's body.
+/// The `Config` of a registered user is saved. Guests can't open files on the server, and
+/// API keys can't open connections.
 async fn connect(
     State(state): State<AppState>,
     AuthExtractor(user): AuthExtractor,
     Json(config): Json<ConnectionConfig>,
 ) -> Result<Json<DbConnectionDetails>, AppError> {
+    user.with_session()?;
+    if user.is_guest && config.is_local_file() {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Guest users can't open file data sources.",
+        ));
+    }
+
     let mut bsbl = state.instance.lock().unwrap();
 
     let user_id = user.id.clone();
@@ -34,13 +53,15 @@ async fn connect(
     bsbl.add_connection(&db);
     std::mem::drop(bsbl); // release Mutex lock
 
-    let tables = db.tables();
-    if !tables.is_empty() {
-        tables.iter().for_each(|tbl| {
-            if let Some(config) = tbl.init_config() {
-                // TODO: Save table config to local db
-            }
-        })
+    let conn_id = db.id().to_string();
+    user.save_connection(&state.local_db, &conn_id, &config)?;
+
+    // Save the detected configuration of each table, unless the user already configured it.
+    let configs = state.local_db.table_configs();
+    for tbl in db.tables() {
+        if let Some(config) = tbl.init_config() {
+            configs.save_default(&user.id, &conn_id, tbl.name(), &config)?;
+        }
     }
 
     let resp = db.details()?;
@@ -50,18 +71,26 @@ async fn connect(
 pub(super) fn core_routes() -> Router<AppState> {
     Router::new()
         .route("/connect", post(connect))
+        .nest("/api-keys", api_keys_routes())
+        .nest("/audit", audit_routes())
         .nest("/auth", auth_routes())
+        .nest("/connections", connections_routes())
         .nest("/tables", table_routes())
+        .nest("/teams", teams_routes())
         .nest("/graphs", graphs_routes())
 }
 
 #[cfg(test)]
 mod tests {
-    use axum::{extract::State, Json};
+    use axum::{extract::State, http::StatusCode, Json};
 
     use crate::{
-        base::AppError,
-        tests::{common::{create_test_config, create_test_state}, extractors::auth_extractor},
+        base::{config::ConnectionConfig, AppError},
+        http::middlewares::AuthExtractor,
+        tests::{
+            common::{create_test_config, create_test_state, get_test_db_table, get_test_user_id},
+            extractors::{api_key_extractor, auth_extractor},
+        },
     };
 
     use super::connect;
@@ -73,9 +102,89 @@ mod tests {
 
         let extractor = auth_extractor();
 
-        let c = connect(State(state), extractor, Json(config)).await;
+        let c = connect(State(state.clone()), extractor, Json(config)).await;
         assert!(c.is_ok());
 
+        // The detected primary key of each table is saved.
+        let Json(details) = c?;
+        let config = state.local_db.table_configs().get(
+            &get_test_user_id(),
+            &details.id,
+            &get_test_db_table(),
+        )?;
+        assert_eq!(config.and_then(|c| c.pk).as_deref(), Some("id"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_api_key() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let key = api_key_extractor(&[]);
+        let c = connect(State(state.clone()), key, Json(create_test_config())).await;
+        assert!(matches!(c, Err(AppError(StatusCode::FORBIDDEN, _))));
+        assert!(state.instance.lock().unwrap().connections.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_file_source() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        let sqlite = |path: &str| ConnectionConfig {
+            source_type: "database".to_string(),
+            source: "sqlite".to_string(),
+            path: Some(path.to_string()),
+            ..Default::default()
+        };
+
+        // Files are only opened from the data directory, even through `..`.
+        for path in ["Cargo.toml", "target/../Cargo.toml", "/etc/hosts"] {
+            let outside = connect(State(state.clone()), auth_extractor(), Json(sqlite(path))).await;
+            assert!(matches!(outside, Err(AppError(StatusCode::NOT_FOUND, _))));
+        }
+
+        let guest = || {
+            let AuthExtractor(mut user) = auth_extractor();
+            user.is_guest = true;
+            AuthExtractor(user)
+        };
+        let csv = ConnectionConfig {
+            source_type: "file".to_string(),
+            source: "csv".to_string(),
+            path: Some("target".to_string()),
+            ..Default::default()
+        };
+        let guest_csv = connect(State(state.clone()), guest(), Json(csv)).await;
+        assert!(matches!(guest_csv, Err(AppError(StatusCode::FORBIDDEN, _))));
+
+        let config = create_test_config();
+        let guest = connect(State(state), guest(), Json(config.clone())).await;
+        match config.is_local_file() {
+            true => assert!(matches!(guest, Err(AppError(StatusCode::FORBIDDEN, _)))),
+            false => assert!(guest.is_ok()),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_unsupported_source() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        for (source_type, source) in [("database", "oracle"), ("cloud", "firebase")] {
+            let config = ConnectionConfig {
+                source_type: source_type.to_string(),
+                source: source.to_string(),
+                ..Default::default()
+            };
+            let conn = connect(State(state.clone()), auth_extractor(), Json(config)).await;
+            assert!(matches!(conn, Err(AppError(StatusCode::BAD_REQUEST, _))));
+        }
+
+        // The instance is still usable.
+        assert!(state.instance.lock().is_ok());
+
         Ok(())
     }
 }
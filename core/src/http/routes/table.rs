@@ -1,32 +1,84 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, slice};
 
 use axum::{
     extract::{Path, Query, State}, http::StatusCode, routing::{delete, get, patch, post, put}, Json, Router
 };
 use axum_macros::debug_handler;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     base::{
         column::ColumnList,
-        data::table::{DataQueryFilter, TableConfig, UpdateDataOptions},
-        imp::table::Table,
+        data::{
+            table::{DataPage, DataQueryFilter, TableConfig, UpdateDataOptions},
+            value::BasableValue,
+        },
+        imp::{table::text_row, SharedTable},
+        local::audit::{AuditOperation, AuditRow},
+        query::find_ident,
+        user::User,
         AppError, AppState,
     },
     http::middlewares::{AuthExtractor, DbExtractor, TableExtractor},
-    imp::database::mysql::table::MySqlTable,
 };
 
+/// Primary key column of `table`, from the user's [`TableConfig`]. If the user has not
+/// configured the table, the detected primary key is used.
+fn table_pk(
+    state: &AppState,
+    user: &User,
+    conn_id: &str,
+    table: &SharedTable,
+) -> Result<Option<String>, AppError> {
+    let config = user
+        .get_table_config(&state.local_db, conn_id, table.name())?
+        .or_else(|| table.init_config());
+
+    Ok(config.and_then(|c| c.pk))
+}
+
+/// Value of column `col` in `input`, whose keys are column names given by the user.
+fn input_value<'a>(input: &'a HashMap<String, String>, col: &str) -> Option<&'a String> {
+    find_ident(input.keys().map(String::as_str), col).and_then(|k| input.get(k))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
 #[debug_handler]
 pub(crate) async fn save_configuration(
     Path(table_name): Path<String>,
     AuthExtractor(user): AuthExtractor,
     DbExtractor(db): DbExtractor,
-    TableExtractor(_): TableExtractor,
-    State(_): State<AppState>,
-    Json(config): Json<TableConfig>,
+    TableExtractor(table): TableExtractor,
+    State(state): State<AppState>,
+    Json(mut config): Json<TableConfig>,
 ) -> Result<String, AppError> {
+    if let Some(pk) = &config.pk {
+        config.pk = table.resolve_columns(&[pk])?.pop();
+    }
+    config.table_id = table_name.clone();
+
     let conn_id = db.id().to_string();
-    user.update_table_config(&conn_id, &table_name, config);
+    let before = user.get_table_config(&state.local_db, &conn_id, &table_name)?;
+    let row = AuditRow {
+        pk_value: None,
+        before: before.as_ref().and_then(to_json),
+        after: to_json(&config),
+    };
+    let pk = config.pk.clone();
+    user.update_table_config(&state.local_db, &conn_id, &table_name, config)?;
+
+    state.local_db.audit().record(
+        &user.id,
+        &conn_id,
+        &table_name,
+        AuditOperation::Configure,
+        pk.as_deref(),
+        &[row],
+    )?;
 
     Ok("Operation successful".to_string())
 }
@@ -37,10 +89,10 @@ pub(crate) async fn get_configuration(
     AuthExtractor(user): AuthExtractor,
     DbExtractor(db): DbExtractor,
     TableExtractor(_): TableExtractor,
-    State(_): State<AppState>,
+    State(state): State<AppState>,
 ) -> Result<Json<Option<TableConfig>>, AppError> {
     let conn_id = db.id().to_string();
-    let config = user.get_table_config(&conn_id, &table_name);
+    let config = user.get_table_config(&state.local_db, &conn_id, &table_name)?;
 
     Ok(Json(config))
 }
@@ -59,53 +111,218 @@ pub(crate) async fn get_columns(
 }
 
 #[debug_handler]
+/// GET: /core/tables/data/:table_name
+///
+/// Queries a page of rows of the table. The rows can be filtered, sorted and paginated with
+/// query params, see [`DataQueryFilter::from_query_params`]. Pages are paginated with cursors
+/// by the table's primary key.
 pub(crate) async fn query_data(
+    Query(params): Query<Vec<(String, String)>>,
     Path(_): Path<String>,
-    AuthExtractor(_): AuthExtractor,
-    DbExtractor(_): DbExtractor,
+    AuthExtractor(user): AuthExtractor,
+    DbExtractor(db): DbExtractor,
     TableExtractor(table): TableExtractor,
-    State(_): State<AppState>,
-) -> Result<Json<Vec<HashMap<String, <MySqlTable as Table>::ColumnValue>>>, AppError> {
-    // TODO: Build query filter from url query params
-    let filter = DataQueryFilter::default();
+    State(state): State<AppState>,
+) -> Result<Json<DataPage>, AppError> {
+    let mut filter = DataQueryFilter::from_query_params(&params)?;
+    filter.cursor_key = table_pk(&state, &user, &db.id().to_string(), &table)?;
     let data = table.query_data(filter)?;
 
     Ok(Json(data))
 }
 
+/// Query params of [`insert_data`].
+#[derive(Deserialize, Default)]
+pub(crate) struct InsertDataParams {
+    #[serde(default)]
+    upsert: bool,
+}
+
 #[debug_handler]
+/// POST: /core/tables/data/:table_name
+///
+/// Inserts a row. The inserted row is recorded in the audit log, as the data source stored it.
+/// Data sources that can't return the inserted row read it back by its primary key when the
+/// key is part of the input.
+///
+/// With `upsert=true`, the row with the same primary key is updated instead, if there's one,
+/// and the update is recorded. The primary key must then be part of the input.
 pub(crate) async fn insert_data(
+    Query(params): Query<InsertDataParams>,
     Path(_): Path<String>,
-    AuthExtractor(_): AuthExtractor,
-    DbExtractor(_): DbExtractor,
+    AuthExtractor(user): AuthExtractor,
+    DbExtractor(db): DbExtractor,
     TableExtractor(table): TableExtractor,
-    State(_): State<AppState>,
+    State(state): State<AppState>,
     Json(data): Json<HashMap<String, String>>,
 ) -> Result<String, AppError> {
-    table.insert_data(data)?;
+    let conn_id = db.id().to_string();
+    let pk = table_pk(&state, &user, &conn_id, &table)?;
+    let pk_value = pk.as_ref().and_then(|pk| input_value(&data, pk)).cloned();
+    if params.upsert && pk_value.is_none() {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "Upserted rows must have a value of the table's primary key.",
+        ));
+    }
+
+    // The row is read and written in one transaction, so that the audit log records the
+    // rows that were actually replaced and written.
+    let input = text_row(data);
+    let mut before = None;
+    let mut after = None;
+    db.connector().transaction(&mut |exec| {
+        let written = match (&pk, &pk_value) {
+            (Some(pk), Some(v)) if params.upsert => {
+                before = db
+                    .query_rows(exec, table.name(), pk, slice::from_ref(v))?
+                    .pop();
+                db.upsert_row(exec, table.name(), pk, &input)?
+            }
+            _ => db.insert_row(exec, table.name(), &input)?,
+        };
+
+        after = match (written, &pk, &pk_value) {
+            (Some(row), _, _) => Some(row),
+            (None, Some(pk), Some(v)) => db
+                .query_rows(exec, table.name(), pk, slice::from_ref(v))?
+                .pop(),
+            (None, _, _) => None,
+        };
+
+        Ok(())
+    })?;
+
+    let operation = match before {
+        Some(_) => AuditOperation::Update,
+        None => AuditOperation::Insert,
+    };
+    // The primary key may have been generated by the data source.
+    let pk_value = pk_value.or_else(|| {
+        let pk = find_ident(after.as_ref()?.keys().map(String::as_str), pk.as_ref()?)?;
+        after.as_ref()?.get(pk).map(|v| v.to_string())
+    });
+    let row = AuditRow {
+        pk_value,
+        before: before.as_ref().and_then(to_json),
+        after: after.as_ref().and_then(to_json),
+    };
+
+    state.local_db.audit().record(
+        &user.id,
+        &conn_id,
+        table.name(),
+        operation,
+        pk.as_deref(),
+        &[row],
+    )?;
+
     Ok("Operation successful".to_string())
 }
 
 #[debug_handler]
+/// PATCH: /core/tables/data/:table_name
+///
+/// Updates the rows whose `key` column has `value`. Each updated row is recorded in the
+/// audit log, before and after the update.
 pub(crate) async fn update_data(
     Path(_): Path<String>,
-    AuthExtractor(_): AuthExtractor,
-    DbExtractor(_): DbExtractor,
+    AuthExtractor(user): AuthExtractor,
+    DbExtractor(db): DbExtractor,
     TableExtractor(table): TableExtractor,
-    State(_): State<AppState>,
+    State(state): State<AppState>,
     Json(options): Json<UpdateDataOptions>,
 ) -> Result<String, AppError> {
-    table.update_data(options)?;
+    let conn_id = db.id().to_string();
+    let pk = table_pk(&state, &user, &conn_id, &table)?;
+    let UpdateDataOptions { key, value, input } = options;
+
+    // The rows are read before and after the update in its transaction, so that the audit
+    // log records the rows that were actually updated.
+    let row = text_row(input.clone());
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    db.connector().transaction(&mut |exec| {
+        before = db.query_rows(exec, table.name(), &key, slice::from_ref(&value))?;
+        db.update_row(exec, table.name(), &key, &value, &row)?;
+
+        if let Some(pk) = &pk {
+            // The rows are read again by their primary key, which may have been updated.
+            let pk_values: Vec<String> = match input_value(&input, pk) {
+                Some(v) => vec![v.clone()],
+                None => before
+                    .iter()
+                    .filter_map(|r| r.get(pk))
+                    .map(|v| v.to_string())
+                    .collect(),
+            };
+            after = db.query_rows(exec, table.name(), pk, &pk_values)?;
+        }
+
+        Ok(())
+    })?;
+
+    let rows: Vec<AuditRow> = match &pk {
+        Some(pk) => before
+            .iter()
+            .map(|b| {
+                let old_value = b.get(pk).map(|v| v.to_string());
+                let new_value = input_value(&input, pk).cloned().or(old_value.clone());
+                let a = after
+                    .iter()
+                    .find(|a| a.get(pk).map(|v| v.to_string()) == new_value);
+
+                AuditRow {
+                    pk_value: old_value,
+                    before: to_json(b),
+                    after: a.and_then(to_json),
+                }
+            })
+            .collect(),
+        None => before
+            .iter()
+            .map(|b| {
+                let mut a = b.clone();
+                for (k, v) in &input {
+                    if let Some(col) = find_ident(b.keys().map(String::as_str), k) {
+                        a.insert(col.to_string(), BasableValue::Text(v.clone()));
+                    }
+                }
+
+                AuditRow {
+                    pk_value: None,
+                    before: to_json(b),
+                    after: to_json(&a),
+                }
+            })
+            .collect(),
+    };
+
+    if !rows.is_empty() {
+        state.local_db.audit().record(
+            &user.id,
+            &conn_id,
+            table.name(),
+            AuditOperation::Update,
+            pk.as_deref(),
+            &rows,
+        )?;
+    }
+
     Ok("Operation successful".to_string())
 }
 
+/// DELETE: /core/tables/data/:table_name
+///
+/// Deletes the rows whose `col` column has `value`. Each deleted row is recorded in the
+/// audit log.
 pub(crate) async fn delete_data(
     Query(params): Query<HashMap<String, String>>,
     Path(_): Path<String>,
-    AuthExtractor(_): AuthExtractor,
-    DbExtractor(_): DbExtractor,
+    AuthExtractor(user): AuthExtractor,
+    DbExtractor(db): DbExtractor,
     TableExtractor(table): TableExtractor,
-    State(_): State<AppState>,
+    State(state): State<AppState>,
 ) -> Result<String, AppError> {
     let col = params.get("col");
     let value = params.get("value");
@@ -117,13 +334,201 @@ pub(crate) async fn delete_data(
         (None, Some(_)) => Err(err("Please provide 'value' query param.")),
         (Some(_), None) => Err(err("Please provide 'col' query param.")),
         (Some(col), Some(value)) => {
-            table.delete_data(col.clone(), value.clone())?;
+            let conn_id = db.id().to_string();
+            let pk = table_pk(&state, &user, &conn_id, &table)?;
+            // The rows are read in the transaction of the delete, so that the audit log
+            // records the rows that were actually deleted.
+            let mut before = Vec::new();
+            db.connector().transaction(&mut |exec| {
+                before = db.query_rows(exec, table.name(), col, slice::from_ref(value))?;
+                db.delete_rows(exec, table.name(), col, value)?;
+
+                Ok(())
+            })?;
+
+            let rows: Vec<AuditRow> = before
+                .iter()
+                .map(|b| AuditRow {
+                    pk_value: pk.as_ref().and_then(|pk| b.get(pk)).map(|v| v.to_string()),
+                    before: to_json(b),
+                    after: None,
+                })
+                .collect();
+
+            if !rows.is_empty() {
+                state.local_db.audit().record(
+                    &user.id,
+                    &conn_id,
+                    table.name(),
+                    AuditOperation::Delete,
+                    pk.as_deref(),
+                    &rows,
+                )?;
+            }
+
             Ok("Operation successful".to_string())
         },
     }
 
 }
 
+/// A row of a change to undo: the row expected in the table, and the row to restore.
+struct UndoRow {
+    pk_value: String,
+    expected: Option<HashMap<String, BasableValue>>,
+    restored: Option<HashMap<String, BasableValue>>,
+}
+
+fn from_json(value: &Option<serde_json::Value>) -> Option<HashMap<String, BasableValue>> {
+    value
+        .as_ref()
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+}
+
+#[debug_handler]
+/// POST: /core/tables/data/:table_name/undo/:change_id
+///
+/// Reverts change `change_id` of the audit log: updated rows get their previous values back,
+/// deleted rows are inserted again and inserted rows are deleted. Rows are found with the
+/// primary key of the table when the change was made.
+///
+/// Nothing is reverted if a row was changed after the change, and the request fails with
+/// `409`. Rows are reverted in one transaction, so either all of them are reverted or none
+/// is. The undo is recorded in the audit log, so it can be undone in turn.
+pub(crate) async fn undo_change(
+    Path((table_name, change_id)): Path<(String, i64)>,
+    AuthExtractor(user): AuthExtractor,
+    DbExtractor(db): DbExtractor,
+    TableExtractor(table): TableExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    let conn_id = db.id().to_string();
+    let change = state
+        .local_db
+        .audit()
+        .get(&conn_id, change_id)?
+        .filter(|c| c.table_name == table.name())
+        .ok_or_else(|| {
+            AppError::new(
+                StatusCode::NOT_FOUND,
+                &format!("Can't find change {change_id} of table '{table_name}'."),
+            )
+        })?;
+
+    let operation = match change.operation {
+        AuditOperation::Insert => AuditOperation::Delete,
+        AuditOperation::Delete => AuditOperation::Insert,
+        AuditOperation::Update => AuditOperation::Update,
+        AuditOperation::Configure => {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                "Only changes to the data of a table can be undone.",
+            ))
+        }
+    };
+    let pk = change.pk.ok_or_else(|| {
+        AppError::new(
+            StatusCode::BAD_REQUEST,
+            "The change can't be undone because the table had no primary key.",
+        )
+    })?;
+
+    let rows: Vec<UndoRow> = change
+        .rows
+        .iter()
+        .map(|r| {
+            let before = from_json(&r.before);
+            let after = from_json(&r.after);
+            // An updated row is found with its primary key after the update.
+            let pk_value = after
+                .as_ref()
+                .and_then(|a| a.get(&pk))
+                .map(|v| v.to_string())
+                .or(r.pk_value.clone());
+
+            match (pk_value, &change.operation) {
+                (Some(pk_value), AuditOperation::Update) if after.is_some() => Some(UndoRow {
+                    pk_value,
+                    expected: after,
+                    restored: before,
+                }),
+                (Some(pk_value), AuditOperation::Insert) if after.is_some() => Some(UndoRow {
+                    pk_value,
+                    expected: after,
+                    restored: None,
+                }),
+                (Some(pk_value), AuditOperation::Delete) if before.is_some() => Some(UndoRow {
+                    pk_value,
+                    expected: None,
+                    restored: before,
+                }),
+                _ => None,
+            }
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(|| {
+            AppError::new(
+                StatusCode::CONFLICT,
+                "The change can't be undone because some of its rows can't be identified.",
+            )
+        })?;
+
+    // Rows are checked and reverted in one transaction, so that they can't change in
+    // between, and so that no row is reverted if one of them can't be.
+    let pk_values: Vec<String> = rows.iter().map(|r| r.pk_value.clone()).collect();
+    db.connector().transaction(&mut |exec| {
+        let current = db.query_rows(exec, table.name(), &pk, &pk_values)?;
+        for row in &rows {
+            let found = current
+                .iter()
+                .find(|c| c.get(&pk).map(|v| v.to_string()).as_ref() == Some(&row.pk_value));
+
+            if found.and_then(to_json) != row.expected.as_ref().and_then(to_json) {
+                return Err(AppError::new(
+                    StatusCode::CONFLICT,
+                    &format!(
+                        "The row with {pk} = {} has changed since change {change_id}. Undo the later changes first.",
+                        row.pk_value
+                    ),
+                ));
+            }
+        }
+
+        for row in &rows {
+            match &row.restored {
+                Some(restored) if row.expected.is_some() => {
+                    db.update_row(exec, table.name(), &pk, &row.pk_value, restored)?
+                }
+                Some(restored) => {
+                    db.insert_row(exec, table.name(), restored)?;
+                }
+                None => db.delete_rows(exec, table.name(), &pk, &row.pk_value)?,
+            }
+        }
+
+        Ok(())
+    })?;
+
+    let rows: Vec<AuditRow> = rows
+        .iter()
+        .map(|r| AuditRow {
+            pk_value: Some(r.pk_value.clone()),
+            before: r.expected.as_ref().and_then(to_json),
+            after: r.restored.as_ref().and_then(to_json),
+        })
+        .collect();
+    state.local_db.audit().record(
+        &user.id,
+        &conn_id,
+        table.name(),
+        operation,
+        Some(&pk),
+        &rows,
+    )?;
+
+    Ok("Operation successful".to_string())
+}
+
 /// Routes for database table management
 pub(super) fn table_routes() -> Router<AppState> {
     Router::new()
@@ -134,4 +539,334 @@ pub(super) fn table_routes() -> Router<AppState> {
         .route("/data/:table_name", post(insert_data))
         .route("/data/:table_name", patch(update_data))
         .route("/data/:table_name", delete(delete_data))
+        .route("/data/:table_name/undo/:change_id", post(undo_change))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, slice};
+
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        Json,
+    };
+
+    use crate::{
+        base::{
+            data::table::UpdateDataOptions,
+            imp::{SharedDB, SharedTable},
+            local::audit::{AuditFilter, AuditOperation},
+            AppError, AppState,
+        },
+        http::{
+            middlewares::{AuthExtractor, DbExtractor, TableExtractor},
+            routes::{audit::query_audit_log, connect},
+        },
+        tests::{
+            common::{create_test_config, create_test_state, get_test_db_table},
+            extractors::auth_extractor,
+        },
+    };
+
+    use super::{delete_data, insert_data, undo_change, update_data, InsertDataParams};
+
+    type Extractors = (
+        Path<String>,
+        AuthExtractor,
+        DbExtractor,
+        TableExtractor,
+        State<AppState>,
+    );
+
+    fn extractors(state: &AppState, db: &SharedDB, table: &SharedTable) -> Extractors {
+        (
+            Path(table.name().to_string()),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            TableExtractor(table.clone()),
+            State(state.clone()),
+        )
+    }
+
+    /// Inserts a row in the test table, updates it and deletes it. Returns the id of the row.
+    async fn edit_test_row(state: &AppState) -> Result<(SharedDB, SharedTable, String), AppError> {
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let (db, _) = state
+            .get_connection(&details.id, &auth_extractor().0)?
+            .unwrap();
+        let table = db.get_table(&get_test_db_table()).unwrap().clone();
+
+        let id = (uuid::Uuid::new_v4().as_u128() % 1_000_000 + 1_000_000).to_string();
+        let input = HashMap::from([
+            ("id".to_string(), id.clone()),
+            ("title".to_string(), "Audit".to_string()),
+        ]);
+        let (path, auth, db_ext, table_ext, st) = extractors(state, &db, &table);
+        let params = Query(InsertDataParams::default());
+        insert_data(params, path, auth, db_ext, table_ext, st, Json(input)).await?;
+
+        let options = UpdateDataOptions {
+            key: "id".to_string(),
+            value: id.clone(),
+            input: HashMap::from([("title".to_string(), "Audit, updated".to_string())]),
+        };
+        let (path, auth, db_ext, table_ext, st) = extractors(state, &db, &table);
+        update_data(path, auth, db_ext, table_ext, st, Json(options)).await?;
+
+        let params = HashMap::from([
+            ("col".to_string(), "id".to_string()),
+            ("value".to_string(), id.clone()),
+        ]);
+        let (path, auth, db_ext, table_ext, st) = extractors(state, &db, &table);
+        delete_data(Query(params), path, auth, db_ext, table_ext, st).await?;
+
+        Ok((db, table, id))
+    }
+
+    #[tokio::test]
+    async fn test_audit_data_changes() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        let table_name = get_test_db_table();
+        let (db, _, id) = edit_test_row(&state).await?;
+
+        let filter = AuditFilter {
+            table: Some(table_name.clone()),
+            pk_value: Some(id.clone()),
+            ..Default::default()
+        };
+        let Json(log) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            State(state.clone()),
+        )
+        .await?;
+
+        let operations: Vec<AuditOperation> = log.changes.iter().map(|c| c.operation).collect();
+        assert_eq!(
+            operations,
+            [
+                AuditOperation::Delete,
+                AuditOperation::Update,
+                AuditOperation::Insert
+            ]
+        );
+        assert_eq!(log.total, 3);
+
+        let title =
+            |row: &Option<serde_json::Value>| row.as_ref().unwrap()["title"]["Text"].clone();
+        let deleted = &log.changes[0].rows[0];
+        assert_eq!(title(&deleted.before), "Audit, updated");
+        assert!(deleted.after.is_none());
+
+        let updated = &log.changes[1];
+        assert_eq!(updated.pk.as_deref(), Some("id"));
+        assert_eq!(updated.rows[0].pk_value.as_deref(), Some(id.as_str()));
+        assert_eq!(title(&updated.rows[0].before), "Audit");
+        assert_eq!(title(&updated.rows[0].after), "Audit, updated");
+
+        let inserted = &log.changes[2].rows[0];
+        assert!(inserted.before.is_none());
+        assert_eq!(title(&inserted.after), "Audit");
+
+        // Pages of the log
+        let filter = AuditFilter {
+            pk_value: Some(id),
+            page: Some(2),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let Json(page) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db),
+            State(state),
+        )
+        .await?;
+        assert_eq!(page.total, 3);
+        assert_eq!(page.changes.len(), 1);
+        assert_eq!(page.changes[0].operation, AuditOperation::Update);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_change() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        let (db, table, id) = edit_test_row(&state).await?;
+
+        let filter = AuditFilter {
+            pk_value: Some(id.clone()),
+            ..Default::default()
+        };
+        let Json(log) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            State(state.clone()),
+        )
+        .await?;
+        let [deleted, updated, inserted] = [0, 1, 2].map(|i| log.changes[i].id);
+
+        let undo = |change_id| {
+            let (Path(table_name), auth, db_ext, table_ext, st) = extractors(&state, &db, &table);
+            undo_change(Path((table_name, change_id)), auth, db_ext, table_ext, st)
+        };
+        let title = || -> Result<Option<String>, AppError> {
+            let rows = db.query_rows(db.connector(), table.name(), "id", slice::from_ref(&id))?;
+            Ok(rows.first().map(|r| r["title"].to_string()))
+        };
+
+        // Changes are undone from the newest, the row doesn't exist until the delete is undone.
+        let conflict = undo(updated).await;
+        assert!(matches!(conflict, Err(AppError(StatusCode::CONFLICT, _))));
+        let conflict = undo(inserted).await;
+        assert!(matches!(conflict, Err(AppError(StatusCode::CONFLICT, _))));
+
+        undo(deleted).await?;
+        assert_eq!(title()?.as_deref(), Some("Audit, updated"));
+        let conflict = undo(deleted).await;
+        assert!(matches!(conflict, Err(AppError(StatusCode::CONFLICT, _))));
+
+        undo(updated).await?;
+        assert_eq!(title()?.as_deref(), Some("Audit"));
+        let conflict = undo(updated).await;
+        assert!(matches!(conflict, Err(AppError(StatusCode::CONFLICT, _))));
+
+        undo(inserted).await?;
+        assert_eq!(title()?, None);
+
+        let unknown = undo(inserted + 1_000_000).await;
+        assert!(matches!(unknown, Err(AppError(StatusCode::NOT_FOUND, _))));
+
+        // Undos are recorded too.
+        let filter = AuditFilter {
+            pk_value: Some(id),
+            ..Default::default()
+        };
+        let Json(log) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            State(state.clone()),
+        )
+        .await?;
+        let operations: Vec<AuditOperation> = log.changes.iter().map(|c| c.operation).collect();
+        assert_eq!(
+            operations[..3],
+            [
+                AuditOperation::Delete,
+                AuditOperation::Update,
+                AuditOperation::Insert
+            ]
+        );
+        assert_eq!(log.total, 6);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upsert_data() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        // The row of `id` is deleted, so it is inserted first, then updated.
+        let (db, table, id) = edit_test_row(&state).await?;
+
+        let upsert = |title: &str, with_id| {
+            let mut input = HashMap::from([("title".to_string(), title.to_string())]);
+            if with_id {
+                input.insert("id".to_string(), id.clone());
+            }
+
+            let (path, auth, db_ext, table_ext, st) = extractors(&state, &db, &table);
+            let params = Query(InsertDataParams { upsert: true });
+            insert_data(params, path, auth, db_ext, table_ext, st, Json(input))
+        };
+        upsert("Upsert", true).await?;
+        upsert("Upsert, updated", true).await?;
+        let no_pk = upsert("Upsert", false).await;
+        assert!(matches!(no_pk, Err(AppError(StatusCode::BAD_REQUEST, _))));
+
+        let rows = db.query_rows(db.connector(), table.name(), "id", slice::from_ref(&id))?;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["title"].to_string(), "Upsert, updated");
+
+        let filter = AuditFilter {
+            pk_value: Some(id.clone()),
+            ..Default::default()
+        };
+        let Json(log) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            State(state.clone()),
+        )
+        .await?;
+        let operations: Vec<AuditOperation> = log.changes.iter().map(|c| c.operation).collect();
+        assert_eq!(
+            operations[..2],
+            [AuditOperation::Update, AuditOperation::Insert]
+        );
+
+        let title =
+            |row: &Option<serde_json::Value>| row.as_ref().unwrap()["title"]["Text"].clone();
+        assert_eq!(title(&log.changes[0].rows[0].before), "Upsert");
+        assert_eq!(title(&log.changes[0].rows[0].after), "Upsert, updated");
+
+        db.delete_rows(db.connector(), table.name(), "id", &id)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_generated_key() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+        let (db, table, _) = edit_test_row(&state).await?;
+
+        // The id of the row is generated by the data source.
+        let title = format!("Generated {}", uuid::Uuid::new_v4());
+        let input = HashMap::from([("title".to_string(), title.clone())]);
+        let (path, auth, db_ext, table_ext, st) = extractors(&state, &db, &table);
+        let params = Query(InsertDataParams::default());
+        insert_data(params, path, auth, db_ext, table_ext, st, Json(input)).await?;
+
+        let rows = db.query_rows(
+            db.connector(),
+            table.name(),
+            "title",
+            slice::from_ref(&title),
+        )?;
+        let id = rows[0]["id"].to_string();
+
+        let filter = AuditFilter {
+            pk_value: Some(id.clone()),
+            ..Default::default()
+        };
+        let Json(log) = query_audit_log(
+            Query(filter),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            State(state.clone()),
+        )
+        .await?;
+        assert_eq!(log.total, 1);
+
+        // The row is recorded as it was stored, with the types of its values.
+        let inserted = &log.changes[0];
+        let after = inserted.rows[0].after.as_ref().unwrap();
+        assert_eq!(after["id"]["Int"].to_string(), id);
+        assert_eq!(after["title"]["Text"], title);
+
+        let (Path(table_name), auth, db_ext, table_ext, st) = extractors(&state, &db, &table);
+        undo_change(Path((table_name, inserted.id)), auth, db_ext, table_ext, st).await?;
+        let rows = db.query_rows(db.connector(), table.name(), "id", slice::from_ref(&id))?;
+        assert!(rows.is_empty());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,556 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+
+use crate::{
+    base::{
+        local::{invitations::Invitation, members::Member, teams::Team},
+        user::{Role, User},
+        AppError, AppState,
+    },
+    http::middlewares::AuthExtractor,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct NewTeam {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct InviteForm {
+    /// Email or username of the invited user.
+    pub user: String,
+    pub role: Role,
+}
+
+/// Teams are only for registered users, and managed through a session.
+fn registered(user: &User) -> Result<(), AppError> {
+    user.with_session()?;
+
+    match user.is_guest {
+        true => Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Guest users can't join teams.",
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Team `team_id`, if the user is a member.
+fn member(state: &AppState, team_id: &str, user: &User) -> Result<Team, AppError> {
+    registered(user)?;
+
+    match state.local_db.teams().get(team_id, &user.id)? {
+        Some(team) => Ok(team),
+        None => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find a team with the given id",
+        )),
+    }
+}
+
+/// Team `team_id`, if the user owns it.
+fn owned(state: &AppState, team_id: &str, user: &User) -> Result<Team, AppError> {
+    let team = member(state, team_id, user)?;
+
+    match team.role {
+        Role::Owner => Ok(team),
+        _ => Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner of the team can do this.",
+        )),
+    }
+}
+
+#[debug_handler]
+/// POST: /core/teams
+///
+/// Creates a team owned by the current user.
+pub(crate) async fn create_team(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+    Json(body): Json<NewTeam>,
+) -> Result<Json<Team>, AppError> {
+    registered(&user)?;
+
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "Team name can't be empty.",
+        ));
+    }
+
+    let team = state.local_db.teams().create(&user.id, name)?;
+    Ok(Json(team))
+}
+
+#[debug_handler]
+/// GET: /core/teams
+///
+/// Lists the teams of the current user, with the user's role in each.
+pub(crate) async fn list_teams(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Team>>, AppError> {
+    registered(&user)?;
+    let teams = state.local_db.teams().list(&user.id)?;
+
+    Ok(Json(teams))
+}
+
+#[debug_handler]
+/// DELETE: /core/teams/:team_id
+///
+/// Deletes a team of the current user. Its members lose their roles on the connections
+/// shared with it.
+pub(crate) async fn delete_team(
+    Path(team_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    owned(&state, &team_id, &user)?;
+    state.local_db.teams().delete(&team_id, &user.id)?;
+
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// GET: /core/teams/:team_id/members
+///
+/// Lists the members of a team of the current user.
+pub(crate) async fn list_members(
+    Path(team_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Member>>, AppError> {
+    member(&state, &team_id, &user)?;
+    let members = state.local_db.teams().members(&team_id)?;
+
+    Ok(Json(members))
+}
+
+#[debug_handler]
+/// DELETE: /core/teams/:team_id/members/:user_id
+///
+/// Removes a member from a team. The owner can remove any member, and members can leave.
+/// Connections of the removed member are no longer shared with the team.
+pub(crate) async fn remove_member(
+    Path((team_id, member_id)): Path<(String, String)>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    let team = match member_id == user.id {
+        true => member(&state, &team_id, &user)?,
+        false => owned(&state, &team_id, &user)?,
+    };
+
+    if member_id == team.owner_id {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "The owner of a team can't leave it.",
+        ));
+    }
+
+    match state.local_db.teams().remove_member(&team_id, &member_id)? {
+        true => Ok("Operation successful".to_string()),
+        false => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "The user is not a member of this team",
+        )),
+    }
+}
+
+#[debug_handler]
+/// POST: /core/teams/:team_id/invitations
+///
+/// Invites a registered user, found by email or username, to a team of the current user.
+/// The user joins the team with the given role by accepting the invitation.
+pub(crate) async fn invite_member(
+    Path(team_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+    Json(body): Json<InviteForm>,
+) -> Result<Json<Invitation>, AppError> {
+    owned(&state, &team_id, &user)?;
+
+    if body.role == Role::Owner {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "A team can only have one owner.",
+        ));
+    }
+
+    let users = state.local_db.users();
+    let name = body.user.trim();
+    let invitee = match users.find_by_email(name)? {
+        Some(account) => Some(account),
+        None => users.find_by_username(name)?,
+    };
+    let Some(invitee) = invitee else {
+        return Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find a registered user with the given email or username",
+        ));
+    };
+
+    if state.local_db.teams().get(&team_id, &invitee.id)?.is_some() {
+        return Err(AppError::new(
+            StatusCode::CONFLICT,
+            "The user is already a member of this team.",
+        ));
+    }
+
+    let invitation =
+        state
+            .local_db
+            .invitations()
+            .create(&team_id, &user.id, &invitee.id, body.role)?;
+    Ok(Json(invitation))
+}
+
+#[debug_handler]
+/// PUT: /core/teams/:team_id/connections/:conn_id
+///
+/// Shares a saved connection of the current user with a team of the user. Members of the
+/// team get their team role on the connection, and use the same open connection.
+pub(crate) async fn share_connection(
+    Path((team_id, conn_id)): Path<(String, String)>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    member(&state, &team_id, &user)?;
+
+    if state.local_db.connections().owner(&conn_id)?.as_ref() != Some(&user.id) {
+        return Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find a saved connection with the given id",
+        ));
+    }
+
+    state.local_db.teams().share(&team_id, &conn_id)?;
+    Ok("Operation successful".to_string())
+}
+
+#[debug_handler]
+/// DELETE: /core/teams/:team_id/connections/:conn_id
+///
+/// Stops sharing a connection with a team. The owner of the connection and the owner of
+/// the team can stop sharing it.
+pub(crate) async fn unshare_connection(
+    Path((team_id, conn_id)): Path<(String, String)>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    let team = member(&state, &team_id, &user)?;
+
+    let conn_owner = state.local_db.connections().owner(&conn_id)?;
+    if team.role != Role::Owner && conn_owner.as_ref() != Some(&user.id) {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner of the connection or the team can do this.",
+        ));
+    }
+
+    match state.local_db.teams().unshare(&team_id, &conn_id)? {
+        true => Ok("Operation successful".to_string()),
+        false => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "The connection is not shared with this team",
+        )),
+    }
+}
+
+#[debug_handler]
+/// GET: /core/teams/invitations
+///
+/// Lists the pending invitations of the current user.
+pub(crate) async fn list_invitations(
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Invitation>>, AppError> {
+    registered(&user)?;
+    let invitations = state.local_db.invitations().list(&user.id)?;
+
+    Ok(Json(invitations))
+}
+
+#[debug_handler]
+/// POST: /core/teams/invitations/:invitation_id
+///
+/// Accepts an invitation of the current user, who joins its team.
+pub(crate) async fn accept_invitation(
+    Path(invitation_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<Json<Team>, AppError> {
+    registered(&user)?;
+
+    let invitations = state.local_db.invitations();
+    let Some(team_id) = invitations.accept(&invitation_id, &user.id)? else {
+        return Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find an invitation with the given id",
+        ));
+    };
+
+    let team = member(&state, &team_id, &user)?;
+    Ok(Json(team))
+}
+
+#[debug_handler]
+/// DELETE: /core/teams/invitations/:invitation_id
+///
+/// Declines an invitation of the current user.
+pub(crate) async fn decline_invitation(
+    Path(invitation_id): Path<String>,
+    AuthExtractor(user): AuthExtractor,
+    State(state): State<AppState>,
+) -> Result<String, AppError> {
+    registered(&user)?;
+
+    match state
+        .local_db
+        .invitations()
+        .decline(&invitation_id, &user.id)?
+    {
+        true => Ok("Operation successful".to_string()),
+        false => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "Can't find an invitation with the given id",
+        )),
+    }
+}
+
+/// Routes for teams, their members and invitations, and the connections shared with them
+pub(super) fn teams_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_team).get(list_teams))
+        .route("/invitations", get(list_invitations))
+        .route(
+            "/invitations/:invitation_id",
+            post(accept_invitation).delete(decline_invitation),
+        )
+        .route("/:team_id", delete(delete_team))
+        .route("/:team_id/members", get(list_members))
+        .route("/:team_id/members/:user_id", delete(remove_member))
+        .route("/:team_id/invitations", post(invite_member))
+        .route(
+            "/:team_id/connections/:conn_id",
+            put(share_connection).delete(unshare_connection),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        Json,
+    };
+
+    use crate::{
+        base::{
+            user::{Role, User},
+            AppError, AppState,
+        },
+        http::{middlewares::AuthExtractor, routes::connect},
+        tests::{
+            common::{create_test_config, create_test_state, get_test_user_id},
+            extractors::{api_key_extractor, auth_extractor},
+        },
+    };
+
+    use super::{
+        accept_invitation, create_team, decline_invitation, delete_team, invite_member,
+        list_invitations, list_members, list_teams, remove_member, share_connection,
+        unshare_connection, InviteForm, NewTeam,
+    };
+
+    fn is_forbidden<T>(res: Result<T, AppError>) -> bool {
+        matches!(res, Err(AppError(StatusCode::FORBIDDEN, _)))
+    }
+
+    #[tokio::test]
+    async fn test_team_invitations() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let conn_id = details.id;
+
+        let analyst = User::register(
+            &state.local_db,
+            uuid::Uuid::new_v4().to_string(),
+            "analyst@example.com",
+            Some("analyst"),
+            "p@ssword",
+        )?;
+        let analyst_id = analyst.id.clone();
+        let as_analyst = || {
+            AuthExtractor(User {
+                id: analyst_id.clone(),
+                is_guest: false,
+                ..Default::default()
+            })
+        };
+
+        let Json(team) = create_team(
+            auth_extractor(),
+            State(state.clone()),
+            Json(NewTeam {
+                name: "Analytics".to_string(),
+            }),
+        )
+        .await?;
+        share_connection(
+            Path((team.id.clone(), conn_id.clone())),
+            auth_extractor(),
+            State(state.clone()),
+        )
+        .await?;
+
+        let invite = |user: &str| {
+            invite_member(
+                Path(team.id.clone()),
+                auth_extractor(),
+                State(state.clone()),
+                Json(InviteForm {
+                    user: user.to_string(),
+                    role: Role::Viewer,
+                }),
+            )
+        };
+        let unknown = invite("nobody@example.com").await;
+        assert!(matches!(unknown, Err(AppError(StatusCode::NOT_FOUND, _))));
+        let Json(invitation) = invite("Analyst").await?;
+        assert_eq!(invitation.team_name, "Analytics");
+
+        let Json(invitations) = list_invitations(as_analyst(), State(state.clone())).await?;
+        assert_eq!(invitations.len(), 1);
+        assert!(state.get_connection(&conn_id, &analyst)?.is_none());
+
+        let Json(joined) = accept_invitation(
+            Path(invitations[0].id.clone()),
+            as_analyst(),
+            State(state.clone()),
+        )
+        .await?;
+        assert_eq!(joined.role, Role::Viewer);
+        let member = invite("analyst@example.com").await;
+        assert!(matches!(member, Err(AppError(StatusCode::CONFLICT, _))));
+
+        // Members of the team use the owner's open connection.
+        let restarted = AppState::new(state.local_db.clone());
+        let (db, role) = restarted.get_connection(&conn_id, &analyst)?.unwrap();
+        assert_eq!(role, Role::Viewer);
+        assert_eq!(db.id().to_string(), conn_id);
+        assert_eq!(db.user_id(), auth_extractor().0.id);
+        let owner = auth_extractor().0;
+        assert_eq!(
+            restarted.get_connection(&conn_id, &owner)?.unwrap().1,
+            Role::Owner
+        );
+        assert_eq!(restarted.instance.lock().unwrap().connections.len(), 1);
+
+        // Only the owner can invite users.
+        let not_owner = invite_member(
+            Path(team.id.clone()),
+            as_analyst(),
+            State(state.clone()),
+            Json(InviteForm {
+                user: "analyst".to_string(),
+                role: Role::Editor,
+            }),
+        )
+        .await;
+        assert!(matches!(not_owner, Err(AppError(StatusCode::FORBIDDEN, _))));
+
+        remove_member(
+            Path((team.id.clone(), analyst_id.clone())),
+            as_analyst(),
+            State(state.clone()),
+        )
+        .await?;
+        assert!(state.get_connection(&conn_id, &analyst)?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_denied() -> Result<(), AppError> {
+        let state = create_test_state(false)?;
+
+        let Json(details) = connect(
+            State(state.clone()),
+            auth_extractor(),
+            Json(create_test_config()),
+        )
+        .await?;
+        let conn_id = details.id;
+        let key = || api_key_extractor(&[&conn_id]);
+
+        let new_team = || {
+            Json(NewTeam {
+                name: "Analytics".to_string(),
+            })
+        };
+        let Json(team) = create_team(auth_extractor(), State(state.clone()), new_team()).await?;
+        let team_path = || Path(team.id.clone());
+        let member_path = || Path((team.id.clone(), get_test_user_id()));
+        let conn_path = || Path((team.id.clone(), conn_id.clone()));
+
+        // API keys can only use the data of their connections.
+        let created = create_team(key(), State(state.clone()), new_team()).await;
+        assert!(is_forbidden(created));
+        assert!(is_forbidden(list_teams(key(), State(state.clone())).await));
+        assert!(is_forbidden(
+            delete_team(team_path(), key(), State(state.clone())).await
+        ));
+        assert!(is_forbidden(
+            list_members(team_path(), key(), State(state.clone())).await
+        ));
+        assert!(is_forbidden(
+            remove_member(member_path(), key(), State(state.clone())).await
+        ));
+
+        let form = Json(InviteForm {
+            user: "analyst".to_string(),
+            role: Role::Viewer,
+        });
+        let invited = invite_member(team_path(), key(), State(state.clone()), form).await;
+        assert!(is_forbidden(invited));
+        assert!(is_forbidden(
+            share_connection(conn_path(), key(), State(state.clone())).await
+        ));
+        assert!(is_forbidden(
+            unshare_connection(conn_path(), key(), State(state.clone())).await
+        ));
+
+        let invitation = || Path("invitation".to_string());
+        assert!(is_forbidden(
+            list_invitations(key(), State(state.clone())).await
+        ));
+        assert!(is_forbidden(
+            accept_invitation(invitation(), key(), State(state.clone())).await
+        ));
+        assert!(is_forbidden(
+            decline_invitation(invitation(), key(), State(state.clone())).await
+        ));
+
+        // The team is left as it was.
+        let Json(teams) = list_teams(auth_extractor(), State(state)).await?;
+        assert_eq!(teams.len(), 1);
+
+        Ok(())
+    }
+}
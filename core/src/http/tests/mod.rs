@@ -7,7 +7,10 @@ mod routes {
 
     use crate::{
         base::{data::table::TableConfig, AppError},
-        http::routes::table::{get_columns, get_configuration, save_configuration},
+        http::{
+            middlewares::{DbExtractor, TableExtractor},
+            routes::table::{get_columns, get_configuration, save_configuration},
+        },
         tests::{
             common::{create_test_state, get_test_db_table},
             extractors::{auth_extractor, db_extractor, table_extractor},
@@ -60,6 +63,45 @@ mod routes {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_table_config_round_trip() -> Result<(), AppError> {
+        let state = create_test_state(true)?;
+        let db_extractor = db_extractor()?;
+        let DbExtractor(db) = &db_extractor;
+        let table_name = get_test_db_table();
+        let table = db.get_table(&table_name).unwrap().clone();
+
+        let config = TableConfig {
+            pk: Some("id".to_string()),
+            ..Default::default()
+        };
+
+        save_configuration(
+            Path(table_name.clone()),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            TableExtractor(table.clone()),
+            State(state.clone()),
+            Json(config),
+        )
+        .await?;
+
+        let Json(saved) = get_configuration(
+            Path(table_name.clone()),
+            auth_extractor(),
+            DbExtractor(db.clone()),
+            TableExtractor(table),
+            State(state),
+        )
+        .await?;
+
+        let saved = saved.unwrap();
+        assert_eq!(saved.pk.as_deref(), Some("id"));
+        assert_eq!(saved.table_id, table_name);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_get_table_columns() -> Result<(), AppError> {
         let state = create_test_state(true)?;
@@ -5,6 +5,8 @@ use serde::Serialize;
 use crate::base::data::table::TableSummaries;
 
 pub(crate) mod mysql;
+pub(crate) mod postgres;
+pub(crate) mod sqlite;
 
 pub(crate) type DBVersion = HashMap<String, String>;
 
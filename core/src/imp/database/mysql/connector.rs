@@ -1,6 +1,13 @@
-use mysql::{prelude::Queryable, Opts, Params, Pool, Row};
+use std::{cell::RefCell, sync::Arc};
 
-use crate::base::{config::ConnectionConfig, imp::connector::Connector, AppError};
+use mysql::{prelude::Queryable, IsolationLevel, Opts, Params, Pool, Transaction, TxOpts, Value};
+
+use crate::base::{
+    config::ConnectionConfig,
+    data::{row::BasableRow, value::BasableValue},
+    imp::connector::{Connector, QueryExecutor, TransactionFn},
+    AppError, BasableError,
+};
 
 /// MySQL implementation of `BasableConnection`
 #[derive(Clone, Default)]
@@ -20,9 +27,6 @@ impl MysqlConnector {
 }
 
 impl Connector for MysqlConnector {
-    type Error = mysql::Error;
-    type Row = Row;
-
     fn new(config: ConnectionConfig) -> Result<Self, AppError> {
         let url = config.build_url();
         let opts = Opts::from_url(&url).unwrap();
@@ -34,14 +38,76 @@ impl Connector for MysqlConnector {
         })
     }
 
-    fn exec_query(&self, query: &str) -> Result<Vec<Self::Row>, Self::Error> {
-        let conn = &mut self.pool().get_conn()?;
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        let mut conn = self.pool().get_conn()?;
+        exec_query_params(&mut conn, query, params)
+    }
+
+    /// The transaction is serializable, so that the rows it reads are locked until it ends.
+    fn transaction(&self, f: &mut TransactionFn) -> Result<(), AppError> {
+        let mut conn = self.pool().get_conn()?;
+        let opts = TxOpts::default().set_isolation_level(Some(IsolationLevel::Serializable));
+        let tx = MysqlTransaction(RefCell::new(conn.start_transaction(opts)?));
 
-        let stmt = conn.prep(query)?;
-        conn.exec(stmt, Params::Empty)
+        f(&tx)?;
+        tx.0.into_inner().commit()?;
+
+        Ok(())
     }
 
     fn config(&self) -> &ConnectionConfig {
         &self.config
     }
 }
+
+/// A transaction of [`MysqlConnector::transaction`].
+struct MysqlTransaction<'a>(RefCell<Transaction<'a>>);
+
+impl QueryExecutor for MysqlTransaction<'_> {
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        exec_query_params(&mut *self.0.borrow_mut(), query, params)
+    }
+}
+
+/// Runs `query` on `conn`, which is a connection or a transaction.
+fn exec_query_params<Q: Queryable>(
+    conn: &mut Q,
+    query: &str,
+    params: &[BasableValue],
+) -> Result<Vec<BasableRow>, BasableError> {
+    let params = match params.is_empty() {
+        true => Params::Empty,
+        false => Params::Positional(params.iter().map(Value::from).collect()),
+    };
+
+    let stmt = conn.prep(query)?;
+    let rows: Vec<mysql::Row> = conn.exec(stmt, params)?;
+
+    // All rows of a result set share the same columns.
+    let columns: Arc<[String]> = match rows.first() {
+        Some(r) => r
+            .columns_ref()
+            .iter()
+            .map(|c| c.name_str().to_string())
+            .collect(),
+        None => return Ok(vec![]),
+    };
+
+    let rows = rows
+        .into_iter()
+        .map(|r| {
+            let values = r.unwrap().into_iter().map(BasableValue::from).collect();
+            BasableRow::new(columns.clone(), values)
+        })
+        .collect();
+
+    Ok(rows)
+}
@@ -1,24 +1,27 @@
 use std::{collections::HashMap, sync::Arc};
 
-use mysql::Row;
 use time::Date;
 use uuid::Uuid;
 
 use crate::{
     base::{
         config::ConnectionConfig,
-        data::table::{TableSummaries, TableSummary},
+        data::{
+            row::BasableRow,
+            table::{TableSummaries, TableSummary},
+            value::BasableValue,
+        },
         imp::{
             db::{QuerySqlParser, DB},
             table::Table,
             ConnectorType, SharedTable,
         },
-        AppError,
+        AppError, BasableError,
     },
     imp::database::{DBVersion, DbConnectionDetails},
 };
 
-use super::{table::MySqlTable, MySqlValue};
+use super::{parse_upsert, quote_ident, table::MySqlTable};
 
 pub(crate) struct MySqlDB {
     pub connector: ConnectorType,
@@ -28,12 +31,12 @@ pub(crate) struct MySqlDB {
 }
 
 impl MySqlDB {
-    pub fn new(connector: ConnectorType, user_id: String) -> Self {
+    pub fn new(connector: ConnectorType, user_id: String, id: Uuid) -> Self {
         MySqlDB {
             connector,
             tables: Vec::new(),
             user_id,
-            id: Uuid::new_v4(),
+            id,
         }
     }
 
@@ -66,17 +69,17 @@ impl MySqlDB {
     fn size(&self) -> Result<f64, AppError> {
         let db = self.config().db_name.as_ref().unwrap();
 
-        let query = format!(
-            "
-            SELECT table_schema '{db}', 
+        let query = "
+            SELECT table_schema, 
             ROUND(SUM(data_length + index_length) / 1024 / 1024, 1) 'size' 
             FROM information_schema.tables 
-            WHERE table_schema = '{db}'
+            WHERE table_schema = ?
             GROUP BY table_schema
-        "
-        );
+        ";
 
-        let qr = self.exec_query(&query)?;
+        let qr = self
+            .connector
+            .exec_query_params(query, &[BasableValue::Text(db.clone())])?;
 
         // db size is returned in MB, we may want to write a function
         // to convert for GB, TB...etc
@@ -89,19 +92,15 @@ impl MySqlDB {
     }
 
     fn config(&self) -> &ConnectionConfig {
-        &self.connector.config()
+        self.connector.config()
     }
 
-    fn exec_query(&self, query: &str) -> mysql::Result<Vec<Row>> {
+    fn exec_query(&self, query: &str) -> Result<Vec<BasableRow>, BasableError> {
         self.connector.exec_query(query)
     }
 }
 
 impl DB for MySqlDB {
-    type Error = mysql::Error;
-    type Row = mysql::Row;
-    type ColumnValue = MySqlValue;
-
     fn id(&self) -> &Uuid {
         &self.id
     }
@@ -134,18 +133,17 @@ impl DB for MySqlDB {
         &self.tables
     }
 
-    fn query_tables(&self) -> mysql::Result<Vec<Row>> {
-        let query = format!(
-            "
+    fn query_tables(&self) -> Result<Vec<BasableRow>, BasableError> {
+        let query = "
                 SELECT table_name, table_rows, create_time, update_time
                 FROM information_schema.tables
-                WHERE table_schema = '{}'
+                WHERE table_schema = ?
                 ORDER BY table_name;
-            ",
-            self.config().db_name.clone().unwrap()
-        );
+            ";
+        let db = self.config().db_name.clone().unwrap();
 
-        self.connector.exec_query(&query)
+        self.connector
+            .exec_query_params(query, &[BasableValue::Text(db)])
     }
 
     fn query_table_summaries(&self) -> Result<TableSummaries, AppError> {
@@ -163,8 +161,8 @@ impl DB for MySqlDB {
                     name,
                     col_count,
                     row_count: res.get("TABLE_ROWS").unwrap(),
-                    created: created.map_or(None, |d| Some(d.to_string())),
-                    updated: updated.map_or(None, |d| Some(d.to_string())),
+                    created: created.map(|d| d.to_string()),
+                    updated: updated.map(|d| d.to_string()),
                 }
             })
             .collect();
@@ -173,18 +171,18 @@ impl DB for MySqlDB {
     }
 
     fn query_column_count(&self, tb_name: &str) -> Result<u32, AppError> {
-        let query = format!(
-            "
+        let query = "
                 SELECT count(*) 
                 FROM information_schema.columns 
-                WHERE table_schema = '{}' and table_name = '{}'
+                WHERE table_schema = ? and table_name = ?
                 ORDER BY table_name;
-            ",
-            self.config().db_name.clone().unwrap(),
-            tb_name
-        );
+            ";
+        let params = [
+            BasableValue::Text(self.config().db_name.clone().unwrap()),
+            BasableValue::Text(tb_name.to_string()),
+        ];
 
-        let qr = self.exec_query(&query)?;
+        let qr = self.connector.exec_query_params(query, &params)?;
         let c: u32 = qr.first().map_or(0, |r| r.get("count(*)").unwrap());
 
         Ok(c)
@@ -198,7 +196,7 @@ impl DB for MySqlDB {
         let version = self.show_version()?;
         let tables = self.query_table_summaries()?;
         let size = self.size()?;
-        let id = self.id.clone();
+        let id = self.id;
         let id = id.to_string();
 
         Ok(DbConnectionDetails {
@@ -210,4 +208,17 @@ impl DB for MySqlDB {
     }
 }
 
-impl QuerySqlParser for MySqlDB {}
+impl QuerySqlParser for MySqlDB {
+    fn quote_ident(&self, name: &str) -> String {
+        quote_ident(name)
+    }
+
+    fn parse_upsert(&self, keys: &[String], cols: &[String]) -> String {
+        parse_upsert(keys, cols)
+    }
+
+    // MySQL has no RETURNING clause, written rows are read again by their primary key.
+    fn parse_returning(&self) -> Option<&'static str> {
+        None
+    }
+}
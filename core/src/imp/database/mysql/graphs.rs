@@ -3,110 +3,94 @@ use time::Date;
 use crate::{
     base::{
         imp::{
-            db::{DBError, QuerySqlParser, DB},
+            db::{QuerySqlParser, DB},
             graphs::{
                 category::CategoryGraphOpts,
-                chrono::{ChronoAnalysisBasis, ChronoAnalysisOpts},
+                chrono::ChronoAnalysisOpts,
                 trend::{TrendGraphOpts, TrendGraphType},
-                AnalysisResult, AnalysisResults, AnalysisValue, VisualizeDB,
+                AnalysisGraph, AnalysisResult, AnalysisValue, VisualizeDB,
             },
         },
-        AppError,
+        AppError, BasableError,
+    },
+    globals::{
+        BASABLE_CATEGORY_COL, BASABLE_CATEGORY_VALUE, BASABLE_CHRONO_XCOL, BASABLE_CHRONO_YCOL,
     },
-    globals::{BASABLE_CHRONO_XCOL, BASABLE_CHRONO_YCOL},
 };
 
 use super::db::MySqlDB;
-use mysql::{DriverError::SetupError, Value};
 
 impl VisualizeDB for MySqlDB {
-    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisResults, DBError> {
+    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisGraph, BasableError> {
         let basis = opts.basis.clone();
 
-        let query = opts.into();
+        let gaps = opts.gaps()?;
+        let split = opts.split();
+        let query = opts.into_query(self)?;
         let sql = self.generate_sql(query)?;
 
         let conn = self.connector();
-        let rows = conn.exec_query(&sql)?;
-
-        let results: AnalysisResults = rows
-            .iter()
-            .map(|r| {
-                let x = match basis {
-                    ChronoAnalysisBasis::Daily => {
-                        let date: Date = r.get(BASABLE_CHRONO_XCOL).unwrap();
-                        AnalysisValue::Date(date)
-                    }
-                    _ => AnalysisValue::UInt(r.get(BASABLE_CHRONO_XCOL).unwrap()),
-                };
-
-                let y = AnalysisValue::UInt(r.get(BASABLE_CHRONO_YCOL).unwrap());
-
-                AnalysisResult::new(x, y)
-            })
-            .collect();
-
-        Ok(results)
+        let rows = conn.exec_query_params(&sql.sql, &sql.params)?;
+
+        let graph = split.graph(&rows, |r| {
+            let x = if basis.is_cyclic() {
+                AnalysisValue::UInt(r.get(BASABLE_CHRONO_XCOL).unwrap())
+            } else if basis.has_time() {
+                AnalysisValue::Text(r.get(BASABLE_CHRONO_XCOL).unwrap())
+            } else {
+                let date: Date = r.get(BASABLE_CHRONO_XCOL).unwrap();
+                AnalysisValue::Date(date)
+            };
+
+            let y = AnalysisValue::from_row(r, BASABLE_CHRONO_YCOL);
+
+            AnalysisResult::new(x, y)
+        });
+
+        Ok(graph.map(|results| gaps.fill(results)))
     }
 
-    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisResults, DBError> {
+    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisGraph, BasableError> {
         let xcol = opts.xcol.clone();
         let ycol = opts.ycol.clone();
         let analysis_type = opts.graph_type.clone();
 
-        let query = opts
-            .try_into()
-            .map_err(|_| mysql::Error::DriverError(SetupError));
-
-        let query = query?;
+        let split = opts.split();
+        let query = opts.into_query(self)?;
         let sql = self.generate_sql(query)?;
 
         let conn = self.connector();
-        let rows = conn.exec_query(&sql)?;
-
-        let results: AnalysisResults = rows
-            .iter()
-            .map(|r| {
-                let x = AnalysisValue::Text(r.get(xcol.as_str()).unwrap());
-                let y = match analysis_type {
-                    TrendGraphType::IntraModel => {
-                        AnalysisValue::Double(r.get(ycol.as_str()).unwrap())
-                    }
-                    TrendGraphType::CrossModel => {
-                        AnalysisValue::UInt(r.get(ycol.as_str()).unwrap())
-                    }
-                };
-
-                AnalysisResult::new(x, y)
-            })
-            .collect();
-
-        Ok(results)
+        let rows = conn.exec_query_params(&sql.sql, &sql.params)?;
+
+        let graph = split.graph(&rows, |r| {
+            let x = AnalysisValue::Text(r.get(xcol.as_str()).unwrap());
+            let y = match analysis_type {
+                TrendGraphType::IntraModel => AnalysisValue::Double(r.get(ycol.as_str()).unwrap()),
+                TrendGraphType::CrossModel => AnalysisValue::UInt(r.get(ycol.as_str()).unwrap()),
+            };
+
+            AnalysisResult::new(x, y)
+        });
+
+        Ok(graph)
     }
 
-    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisResults, AppError> {
-        let target_col = opts.target_col.clone();
-        let query = opts.into();
-        
-        let sql = self
-            .generate_sql(query)
-            .map_err(|_| mysql::Error::DriverError(SetupError))?;
+    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisGraph, AppError> {
+        let split = opts.split();
+        let query = opts.into_query(self)?;
+        let sql = self.generate_sql(query)?;
 
         let conn = self.connector();
 
-        let rows = conn.exec_query(&sql)?;
-        let results: AnalysisResults = rows
-            .iter()
-            .map(|r| {
-                let x = AnalysisValue::UInt(r.get("COUNT").unwrap());
+        let rows = conn.exec_query_params(&sql.sql, &sql.params)?;
+        let graph = split.graph(&rows, |r| {
+            let x = AnalysisValue::from_row(r, BASABLE_CATEGORY_VALUE);
 
-                let y_value: Value = r.get(target_col.as_str()).unwrap();
-                let y = y_value.into();
+            let y = AnalysisValue::from_row(r, BASABLE_CATEGORY_COL);
 
-                AnalysisResult::new(x, y)
-            })
-            .collect();
+            AnalysisResult::new(x, y)
+        });
 
-        Ok(results)
+        Ok(graph)
     }
 }
@@ -1,6 +1,5 @@
 use axum::http::StatusCode;
-use serde::{Deserialize, Serialize};
-use crate::base::AppError;
+use crate::base::{data::value::BasableValue, AppError, BasableError};
 use mysql::Value;
 
 pub(crate) mod db;
@@ -8,6 +7,26 @@ pub(crate) mod connector;
 pub(crate) mod table;
 pub(crate) mod graphs;
 
+/// Quotes `name` as a MySQL identifier, with backticks.
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Clause of a MySQL upsert, see [`QuerySqlParser::parse_upsert`]. MySQL updates the row on a
+/// conflict with any unique key, so the `keys` are not part of the clause.
+///
+/// [`QuerySqlParser::parse_upsert`]: crate::base::imp::db::QuerySqlParser::parse_upsert
+pub(crate) fn parse_upsert(keys: &[String], cols: &[String]) -> String {
+    // A conflicting row is left as it is when there's nothing to update.
+    let cols = match cols.is_empty() {
+        true => keys,
+        false => cols,
+    };
+    let cols: Vec<String> = cols.iter().map(|c| format!("{c} = VALUES({c})")).collect();
+
+    format!("ON DUPLICATE KEY UPDATE {}", cols.join(", "))
+}
+
 /// Implements conversion of `mysql::Error` to AppError. At the moment, all variations
 /// of `mysql::Error` resolves to `StatusCode::INTERNAL_SERVER_ERROR`.
 impl From<mysql::Error> for AppError {
@@ -16,37 +35,56 @@ impl From<mysql::Error> for AppError {
     }
 }
 
-/// Client side representation of a value of MySql column.
-///
-/// The `Value` is also used as a parameter to a prepared statement.
-#[derive(Clone, PartialEq, PartialOrd, Deserialize, Serialize)]
-pub(crate) enum MySqlValue {
-    NULL,
-    Text(String),
-    Int(i64),
-    UInt(u64),
-    Float(f32),
-    Double(f64),
-    /// year, month, day, hour, minutes, seconds, micro seconds
-    Date(u16, u8, u8, u8, u8, u8, u32),
-    /// is negative, days, hours, minutes, seconds, micro seconds
-    Time(bool, u32, u8, u8, u8, u32),
+impl From<mysql::Error> for BasableError {
+    fn from(value: mysql::Error) -> Self {
+        match value {
+            mysql::Error::IoError(err) => Self::Connection(err.to_string()),
+            err => Self::Driver(err.to_string()),
+        }
+    }
 }
 
-impl From<Value> for MySqlValue {
+/// `Bytes` are read as text whenever they are valid UTF-8, since MySQL sends text and
+/// `DECIMAL` values as bytes.
+impl From<Value> for BasableValue {
     fn from(value: Value) -> Self {
         match value {
-            Value::NULL => MySqlValue::NULL,
-            Value::Bytes(buf) => {
-                let s = String::from_utf8(buf).unwrap();
-                MySqlValue::Text(s)
+            Value::NULL => BasableValue::Null,
+            Value::Bytes(buf) => match String::from_utf8(buf) {
+                Ok(s) => BasableValue::Text(s),
+                Err(err) => BasableValue::Bytes(err.into_bytes()),
             },
-            Value::Int(v) => MySqlValue::Int(v),
-            Value::UInt(v) => MySqlValue::UInt(v),
-            Value::Float(v) => MySqlValue::Float(v),
-            Value::Double(v) => MySqlValue::Double(v),
-            Value::Date(y, m, d, h, min, sec, ms) => MySqlValue::Date(y, m, d, h, min, sec, ms),
-            Value::Time(neg, d, h, min, sec, ms) => MySqlValue::Time(neg, d, h, min, sec, ms),
+            Value::Int(v) => BasableValue::Int(v),
+            Value::UInt(v) => BasableValue::UInt(v),
+            Value::Float(v) => BasableValue::Float(v),
+            Value::Double(v) => BasableValue::Double(v),
+            Value::Date(y, m, d, h, min, sec, us) => {
+                BasableValue::Date(y.into(), m, d, h, min, sec, us)
+            }
+            Value::Time(neg, d, h, min, sec, us) => BasableValue::Time(neg, d, h, min, sec, us),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Conversion of query parameters.
+impl From<&BasableValue> for Value {
+    fn from(value: &BasableValue) -> Self {
+        match value {
+            BasableValue::Null => Value::NULL,
+            BasableValue::Bool(v) => Value::Int(i64::from(*v)),
+            BasableValue::Int(v) => Value::Int(*v),
+            BasableValue::UInt(v) => Value::UInt(*v),
+            BasableValue::Float(v) => Value::Float(*v),
+            BasableValue::Double(v) => Value::Double(*v),
+            BasableValue::Bytes(v) => Value::Bytes(v.clone()),
+            BasableValue::Date(y, m, d, h, min, sec, us) => match u16::try_from(*y) {
+                Ok(y) => Value::Date(y, *m, *d, *h, *min, *sec, *us),
+                Err(_) => Value::Bytes(value.to_string().into_bytes()),
+            },
+            BasableValue::Time(neg, d, h, min, sec, us) => {
+                Value::Time(*neg, *d, *h, *min, *sec, *us)
+            }
+            v => Value::Bytes(v.to_string().into_bytes()),
+        }
+    }
+}
@@ -1,12 +1,11 @@
-use std::collections::HashMap;
-
 use crate::base::{
     column::{Column, ColumnList},
-    data::table::{DataQueryFilter, DataQueryResult, TableConfig, UpdateDataOptions},
-    imp::{table::{Table, TableColumn, TableError, TableCRUD}, ConnectorType},
+    data::value::BasableValue,
+    imp::{db::QuerySqlParser, table::Table, ConnectorType},
+    BasableError,
 };
 
-use super::MySqlValue;
+use super::{parse_upsert, quote_ident};
 
 pub(crate) struct MySqlTable {
     pub name: String,
@@ -14,10 +13,6 @@ pub(crate) struct MySqlTable {
 }
 
 impl Table for MySqlTable {
-    type Error = mysql::Error;
-    type Row = mysql::Row;
-    type ColumnValue = MySqlValue;
-
     fn new(name: String, conn: ConnectorType) -> Self
     where
         Self: Sized,
@@ -32,10 +27,8 @@ impl Table for MySqlTable {
         &self.name
     }
 
-    fn query_columns(&self) -> Result<ColumnList, Self::Error> {
-        let table_name = &self.name;
-
-        let query = format!("
+    fn query_columns(&self) -> Result<ColumnList, BasableError> {
+        let query = "
             SELECT 
                 cols.column_name,
                 cols.column_type,
@@ -52,11 +45,11 @@ impl Table for MySqlTable {
                 FROM
                     information_schema.statistics
                 WHERE
-                    table_name = '{table_name}'
+                    table_name = ?
                     AND non_unique = 0) AS stats
             ON 
                 cols.column_name = stats.column_name
-                AND cols.table_name = '{table_name}'
+                AND cols.table_name = ?
             LEFT JOIN
                 information_schema.key_column_usage AS kcus
             ON
@@ -64,12 +57,13 @@ impl Table for MySqlTable {
                 AND cols.column_name = kcus.column_name
                 AND kcus.constraint_name = 'PRIMARY'
             WHERE
-                cols.table_name = '{table_name}'
+                cols.table_name = ?
 
-        ");
+        ";
 
         let conn = self.connector();
-        let result = conn.exec_query(&query)?;
+        let params = vec![BasableValue::Text(self.name.clone()); 3];
+        let result = conn.exec_query_params(query, &params)?;
 
         let cols: ColumnList = result
             .iter()
@@ -79,13 +73,13 @@ impl Table for MySqlTable {
                 let default: Option<String> = r.get("COLUMN_DEFAULT").unwrap();
 
                 let nullable: Option<String> = r.get("IS_NULLABLE");
-                let nullable = nullable.map(|s| s == "YES".to_owned()).unwrap();
+                let nullable = nullable.map(|s| s == "YES").unwrap();
 
                 let unique: Option<String> = r.get("IS_UNIQUE");
-                let unique = unique.map(|s| s == "YES".to_owned()).unwrap();
+                let unique = unique.map(|s| s == "YES").unwrap();
                 
                 let primary: Option<String> = r.get("IS_PRIMARY");
-                let primary = primary.map(|s| s == "YES".to_owned()).unwrap();
+                let primary = primary.map(|s| s == "YES").unwrap();
 
                 Column {
                     name,
@@ -104,131 +98,19 @@ impl Table for MySqlTable {
     fn connector(&self) -> &ConnectorType {
         &self.connector
     }
-
-    
-    fn init_config(&self) -> Option<TableConfig> {
-        let mut config = None;
-
-        if let Ok(cols) = self.query_columns() {
-            let mut iter = cols.iter();
-            let mut pk = iter.find(|c| c.primary);
-
-            if let None = pk {
-                pk = iter.find(|c| c.unique);
-            }
-
-            let pk = pk.map(|pk| pk.name.clone());
-            
-            let c = TableConfig {
-                pk,
-                table_id: self.name.clone(),
-                ..TableConfig::default()
-            };
-
-            config = Some(c);
-        }
-
-        config
-    }
 }
 
-impl TableCRUD for MySqlTable {
-    fn query_data(
-        &self,
-        filter: DataQueryFilter,
-    ) -> DataQueryResult<TableColumn, TableError> {
-        let cols = self.query_columns()?;
-        let mut excluded_cols: Vec<&Column> = vec![]; // columns to exclude from query
-
-        if let Some(exclude) = filter.exclude {
-            if !exclude.is_empty() {
-                excluded_cols = cols
-                    .iter()
-                    .filter(|col| {
-                        let m = exclude.iter().find(|ex| col.name == **ex);
-                        m.is_some()
-                    })
-                    .collect();
-            }
-        }
-
-        // TODO: filter excluded columns from the query
-        let query = format!("SELECT * FROM {} LIMIT {}", self.name(), filter.limit);
-        let conn = self.connector();
-        let result = conn.exec_query(&query)?;
-
-        // std::mem::drop(db);
-        let data: Vec<HashMap<String, TableColumn>> = result
-            .iter()
-            .map(|r| {
-                let mut map: HashMap<String, TableColumn> = HashMap::new();
-
-                for col in &cols {
-                    if let None = excluded_cols.iter().find(|c| c.name == col.name) {
-                        let v: mysql::Value = r.get(col.name.as_str()).unwrap();
-
-                        map.insert(col.name.clone(), v.into());
-                    }
-                }
-
-                map
-            })
-            .collect();
-
-        Ok(data)
-    }
-
-    fn insert_data(&self, input: HashMap<String, String>) -> Result<(), TableError> {
-        let len = input.len();
-        let mut data = HashMap::new();
-
-        for (k, v) in input {
-            data.insert(k, format!("'{}'", v));
-        }
-
-        let mut keys = Vec::with_capacity(len);
-        let mut values = Vec::with_capacity(len);
-
-        data.iter().for_each(|(k, v)| {
-            keys.push(k.as_str());
-            values.push(v.as_str());
-        });
-
-        let keys = keys.join(", ");
-        let values = values.join(", ");
-
-        let query = format!("INSERT INTO {} ({}) VALUES ({})", self.name, keys, values);
-        let conn = self.connector();
-        conn.exec_query(&query)?;
-
-        Ok(())
+impl QuerySqlParser for MySqlTable {
+    fn quote_ident(&self, name: &str) -> String {
+        quote_ident(name)
     }
 
-    fn update_data(&self, options: UpdateDataOptions) -> Result<(), TableError> {
-        let UpdateDataOptions { key, value, input } = options;
-
-        let data: Vec<String> = input
-            .iter()
-            .map(|(k, v)| format!("{} = '{}'", k, v))
-            .collect();
-        let data = data.join(", ");
-
-        let query = format!(
-            "UPDATE {} SET {} WHERE {} = '{}'",
-            self.name, data, key, value
-        );
-        let conn = self.connector();
-        conn.exec_query(&query)?;
-
-        Ok(())
+    fn parse_upsert(&self, keys: &[String], cols: &[String]) -> String {
+        parse_upsert(keys, cols)
     }
 
-    fn delete_data(&self, col: String, value: String) -> Result<(), TableError> {
-        let query = format!("DELETE FROM {} WHERE {} = '{}'", self.name, col, value);
-        let conn = self.connector();
-        conn.exec_query(&query)?;
-
-        Ok(())
+    // MySQL has no RETURNING clause, written rows are read again by their primary key.
+    fn parse_returning(&self) -> Option<&'static str> {
+        None
     }
-
-}
\ No newline at end of file
+}
@@ -0,0 +1,136 @@
+use std::{cell::RefCell, ops::Deref, sync::Arc};
+
+use axum::http::StatusCode;
+use postgres::{types::ToSql, IsolationLevel, NoTls, Row, Transaction};
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+
+use crate::base::{
+    config::ConnectionConfig,
+    data::{row::BasableRow, value::BasableValue},
+    imp::connector::{Connector, QueryExecutor, TransactionFn},
+    AppError, BasableError,
+};
+
+use super::{blocking, read_value};
+
+/// Connection pool that closes its connections outside of any async context, see [`blocking`].
+pub struct PostgresPool(Option<Pool<PostgresConnectionManager<NoTls>>>);
+
+impl Deref for PostgresPool {
+    type Target = Pool<PostgresConnectionManager<NoTls>>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl Drop for PostgresPool {
+    fn drop(&mut self) {
+        if let Some(pool) = self.0.take() {
+            blocking(move || drop(pool));
+        }
+    }
+}
+
+/// Postgres implementation of `BasableConnection`
+#[derive(Clone)]
+pub struct PostgresConnector {
+    /// Database connection pool
+    pub pool: Arc<PostgresPool>,
+
+    /// Connection options
+    pub config: ConnectionConfig,
+}
+
+impl Connector for PostgresConnector {
+    fn new(config: ConnectionConfig) -> Result<Self, AppError> {
+        let url = config.build_url();
+        let pg_config = url
+            .parse()
+            .map_err(|err: postgres::Error| AppError(StatusCode::BAD_REQUEST, err.to_string()))?;
+
+        let manager = PostgresConnectionManager::new(pg_config, NoTls);
+        let pool = blocking(|| Pool::new(manager)).map_err(BasableError::from)?;
+
+        Ok(PostgresConnector {
+            pool: Arc::new(PostgresPool(Some(pool))),
+            config,
+        })
+    }
+
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p as _).collect();
+
+        let rows = blocking(|| -> Result<_, BasableError> {
+            let mut client = self.pool.get()?;
+            Ok(client.query(query, &params)?)
+        })?;
+
+        basable_rows(rows)
+    }
+
+    /// The transaction is repeatable read, so it fails instead of changing rows that other
+    /// connections changed since it started.
+    fn transaction(&self, f: &mut TransactionFn) -> Result<(), AppError> {
+        blocking(|| -> Result<_, AppError> {
+            let mut client = self.pool.get().map_err(BasableError::from)?;
+            let tx = client
+                .build_transaction()
+                .isolation_level(IsolationLevel::RepeatableRead)
+                .start()
+                .map_err(BasableError::from)?;
+            let tx = PostgresTransaction(RefCell::new(tx));
+
+            f(&tx)?;
+            tx.0.into_inner().commit().map_err(BasableError::from)?;
+
+            Ok(())
+        })
+    }
+
+    fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+}
+
+/// A transaction of [`PostgresConnector::transaction`].
+struct PostgresTransaction<'a>(RefCell<Transaction<'a>>);
+
+impl QueryExecutor for PostgresTransaction<'_> {
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p as _).collect();
+        let rows = self.0.borrow_mut().query(query, &params)?;
+
+        basable_rows(rows)
+    }
+}
+
+/// Converts the rows of a result set into [`BasableRow`]s.
+fn basable_rows(rows: Vec<Row>) -> Result<Vec<BasableRow>, BasableError> {
+    // All rows of a result set share the same columns.
+    let columns: Arc<[String]> = match rows.first() {
+        Some(r) => r.columns().iter().map(|c| c.name().to_string()).collect(),
+        None => return Ok(vec![]),
+    };
+
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut values = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            values.push(read_value(&row, i)?);
+        }
+
+        results.push(BasableRow::new(columns.clone(), values));
+    }
+
+    Ok(results)
+}
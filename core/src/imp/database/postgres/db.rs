@@ -0,0 +1,199 @@
+use std::{collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+
+use crate::{
+    base::{
+        data::{
+            row::BasableRow,
+            table::{TableSummaries, TableSummary},
+            value::BasableValue,
+        },
+        imp::{
+            db::DB,
+            table::Table,
+            ConnectorType, SharedTable,
+        },
+        AppError, BasableError,
+    },
+    imp::database::{DBVersion, DbConnectionDetails},
+};
+
+use super::{
+    impl_sql_parser,
+    table::{PostgresTable, DEFAULT_SCHEMA},
+};
+
+pub(crate) struct PostgresDB {
+    pub connector: ConnectorType,
+    pub tables: Vec<SharedTable>,
+    user_id: String,
+    id: Uuid,
+}
+
+impl PostgresDB {
+    pub fn new(connector: ConnectorType, user_id: String, id: Uuid) -> Self {
+        PostgresDB {
+            connector,
+            tables: Vec::new(),
+            user_id,
+            id,
+        }
+    }
+
+    /// Get Postgres server version
+    fn show_version(&self) -> Result<DBVersion, AppError> {
+        let qr = self.exec_query(
+            "
+                SELECT current_setting('server_version') AS version,
+                    version() AS version_comment
+            ",
+        )?;
+
+        let mut data = HashMap::new();
+
+        if let Some(r) = qr.first() {
+            for name in ["version", "version_comment"] {
+                if let Some(value) = r.get::<String>(name) {
+                    data.insert(name.to_string(), value);
+                }
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Size of the database in MB.
+    fn size(&self) -> Result<f64, AppError> {
+        let qr = self.exec_query("SELECT pg_database_size(current_database()) AS size")?;
+
+        let size: i64 = qr.first().and_then(|r| r.get("size")).unwrap_or(0);
+        let size = (size as f64 / 1024.0 / 1024.0 * 10.0).round() / 10.0;
+
+        Ok(size)
+    }
+
+    fn exec_query(&self, query: &str) -> Result<Vec<BasableRow>, BasableError> {
+        self.connector.exec_query(query)
+    }
+
+    /// Name used for the table in Basable. Tables outside the default schema are qualified
+    /// with their schema name.
+    fn table_name(row: &BasableRow) -> String {
+        let schema: String = row.get("table_schema").unwrap();
+        let name: String = row.get("table_name").unwrap();
+
+        if schema == DEFAULT_SCHEMA {
+            name
+        } else {
+            format!("{schema}.{name}")
+        }
+    }
+}
+
+impl DB for PostgresDB {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        &self.connector
+    }
+
+    fn load_tables(&mut self, connector: ConnectorType) -> Result<(), AppError> {
+        let tables = self.query_tables()?;
+
+        for t in tables {
+            let table = PostgresTable::new(Self::table_name(&t), connector.clone());
+            self.tables.push(Arc::new(table));
+        }
+
+        Ok(())
+    }
+
+    fn tables(&self) -> &Vec<SharedTable> {
+        &self.tables
+    }
+
+    /// Queries tables in every schema except the Postgres system schemas.
+    fn query_tables(&self) -> Result<Vec<BasableRow>, BasableError> {
+        let query = "
+            SELECT
+                n.nspname AS table_schema,
+                c.relname AS table_name,
+                GREATEST(c.reltuples, 0)::bigint AS table_rows
+            FROM pg_catalog.pg_class c
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.relkind IN ('r', 'p')
+                AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+                AND n.nspname NOT LIKE 'pg_toast%'
+            ORDER BY n.nspname, c.relname
+        ";
+
+        self.exec_query(query)
+    }
+
+    fn query_table_summaries(&self) -> Result<TableSummaries, AppError> {
+        let results = self.query_tables()?;
+        let mut tables = Vec::with_capacity(results.len());
+
+        for res in results {
+            let name = Self::table_name(&res);
+            let row_count: i64 = res.get("table_rows").unwrap_or(0);
+            let col_count = self.query_column_count(&name)?;
+
+            // Postgres does not keep track of when tables are created or updated.
+            tables.push(TableSummary {
+                name,
+                row_count: row_count as u32,
+                col_count,
+                created: None,
+                updated: None,
+            });
+        }
+
+        Ok(tables)
+    }
+
+    fn query_column_count(&self, tb_name: &str) -> Result<u32, AppError> {
+        let (schema, table) = PostgresTable::split_name(tb_name);
+
+        let query = "
+                SELECT COUNT(*) AS col_count
+                FROM information_schema.columns
+                WHERE table_schema = $1 AND table_name = $2
+            ";
+        let params = [
+            BasableValue::Text(schema.to_string()),
+            BasableValue::Text(table.to_string()),
+        ];
+
+        let qr = self.connector.exec_query_params(query, &params)?;
+        let c: u32 = qr.first().and_then(|r| r.get("col_count")).unwrap_or(0);
+
+        Ok(c)
+    }
+
+    fn get_table(&self, name: &str) -> Option<&SharedTable> {
+        self.tables.iter().find(|t| t.name() == name)
+    }
+
+    fn details(&self) -> Result<DbConnectionDetails, AppError> {
+        let version = self.show_version()?;
+        let tables = self.query_table_summaries()?;
+        let size = self.size()?;
+
+        Ok(DbConnectionDetails {
+            id: self.id.to_string(),
+            tables,
+            version,
+            db_size: size,
+        })
+    }
+}
+
+impl_sql_parser!(PostgresDB);
@@ -0,0 +1,87 @@
+use time::Date;
+
+use crate::{
+    base::{
+        imp::{
+            db::{QuerySqlParser, DB},
+            graphs::{
+                category::CategoryGraphOpts,
+                chrono::{ChronoAnalysisBasis, ChronoAnalysisOpts},
+                trend::TrendGraphOpts,
+                AnalysisGraph, AnalysisResult, AnalysisValue, VisualizeDB,
+            },
+        },
+        AppError, BasableError,
+    },
+    globals::{
+        BASABLE_CATEGORY_COL, BASABLE_CATEGORY_VALUE, BASABLE_CHRONO_XCOL, BASABLE_CHRONO_YCOL,
+    },
+};
+
+use super::db::PostgresDB;
+
+impl VisualizeDB for PostgresDB {
+    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisGraph, BasableError> {
+        let basis = opts.basis.clone();
+
+        let gaps = opts.gaps()?;
+        let split = opts.split();
+        let query = opts.into_query(self)?;
+        let sql = self.generate_sql(query)?;
+
+        let rows = self.connector().exec_query_params(&sql.sql, &sql.params)?;
+
+        let graph = split.graph(&rows, |r| {
+            let x = match basis {
+                ChronoAnalysisBasis::Daily => match r.get::<Date>(BASABLE_CHRONO_XCOL) {
+                    Some(date) => AnalysisValue::Date(date),
+                    None => AnalysisValue::from_row(r, BASABLE_CHRONO_XCOL),
+                },
+                _ => AnalysisValue::from_row(r, BASABLE_CHRONO_XCOL),
+            };
+
+            let y = AnalysisValue::from_row(r, BASABLE_CHRONO_YCOL);
+
+            AnalysisResult::new(x, y)
+        });
+
+        Ok(graph.map(|results| gaps.fill(results)))
+    }
+
+    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisGraph, BasableError> {
+        let xcol = opts.xcol.clone();
+        let ycol = opts.ycol.clone();
+
+        let split = opts.split();
+        let query = opts.into_query(self)?;
+        let sql = self.generate_sql(query)?;
+
+        let rows = self.connector().exec_query_params(&sql.sql, &sql.params)?;
+
+        let graph = split.graph(&rows, |r| {
+            let x = AnalysisValue::from_row(r, &xcol);
+            let y = AnalysisValue::from_row(r, &ycol);
+
+            AnalysisResult::new(x, y)
+        });
+
+        Ok(graph)
+    }
+
+    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisGraph, AppError> {
+        let split = opts.split();
+        let query = opts.into_query(self)?;
+
+        let sql = self.generate_sql(query)?;
+        let rows = self.connector().exec_query_params(&sql.sql, &sql.params)?;
+
+        let graph = split.graph(&rows, |r| {
+            let x = AnalysisValue::from_row(r, BASABLE_CATEGORY_VALUE);
+            let y = AnalysisValue::from_row(r, BASABLE_CATEGORY_COL);
+
+            AnalysisResult::new(x, y)
+        });
+
+        Ok(graph)
+    }
+}
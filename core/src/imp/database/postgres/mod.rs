@@ -0,0 +1,612 @@
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use postgres::{
+    types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type},
+    Row,
+};
+
+use crate::{
+    base::{
+        data::value::{BasableValue, FromBasableValue},
+        BasableError,
+    },
+    utils::datetime_parser::{DatePattern, DateValue},
+};
+
+pub(crate) mod connector;
+pub(crate) mod db;
+pub(crate) mod graphs;
+pub(crate) mod table;
+
+/// The synchronous postgres client drives its connections with its own tokio runtime, and
+/// starting a runtime from within a runtime panics. When called from an async context, such
+/// as a request handler, `f` is run on a separate thread.
+pub(crate) fn blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send,
+    T: Send,
+{
+    if tokio::runtime::Handle::try_current().is_err() {
+        return f();
+    }
+
+    std::thread::scope(|s| s.spawn(f).join()).unwrap_or_else(|err| std::panic::resume_unwind(err))
+}
+
+/// Implements [`QuerySqlParser`](crate::base::imp::db::QuerySqlParser) with the SQL dialect of
+/// Postgres for `$t`. [`PostgresDB`](db::PostgresDB) and [`PostgresTable`](table::PostgresTable)
+/// share it, so that their queries are built the same way.
+macro_rules! impl_sql_parser {
+    ($t:ty) => {
+        impl $crate::base::imp::db::QuerySqlParser for $t {
+            fn quote_table(&self, name: &str) -> String {
+                let (schema, table) =
+                    $crate::imp::database::postgres::table::PostgresTable::split_name(name);
+                format!("{}.{}", self.quote_ident(schema), self.quote_ident(table))
+            }
+
+            fn placeholder(&self, index: usize) -> String {
+                format!("${index}")
+            }
+
+            fn regex_operator(&self, negated: bool) -> &'static str {
+                match negated {
+                    true => "!~",
+                    false => "~",
+                }
+            }
+
+            fn parse_text_cast(&self, expr: &str) -> String {
+                format!("CAST({expr} AS TEXT)")
+            }
+
+            fn parse_percentile(
+                &self,
+                percentile: f64,
+                col: &str,
+            ) -> Result<String, $crate::base::BasableError> {
+                Ok(format!(
+                    "PERCENTILE_CONT({percentile}) WITHIN GROUP (ORDER BY {col})"
+                ))
+            }
+
+            fn parse_time_zone(
+                &self,
+                col: &str,
+                time_zone: &$crate::base::imp::graphs::chrono::ChronoTimeZone,
+            ) -> Result<String, $crate::base::BasableError> {
+                use $crate::base::imp::graphs::chrono::ChronoTimeZone;
+
+                // Offsets are read as intervals, since Postgres reads offset time zones the
+                // POSIX way, with the sign inverted.
+                let zone = match time_zone {
+                    ChronoTimeZone::Offset(_) => format!("INTERVAL '{time_zone}'"),
+                    ChronoTimeZone::Named(_) => format!("'{time_zone}'"),
+                };
+
+                Ok(format!("(CAST({col} AS TIMESTAMPTZ) AT TIME ZONE {zone})"))
+            }
+
+            fn parse_chrono_basis(
+                &self,
+                basis: &$crate::base::imp::graphs::chrono::ChronoAnalysisBasis,
+                col: &str,
+            ) -> String {
+                use $crate::base::imp::graphs::chrono::ChronoAnalysisBasis;
+
+                let timestamp = format!("CAST({col} AS TIMESTAMP)");
+                let date_trunc =
+                    |field: &str| format!("CAST(DATE_TRUNC('{field}', {timestamp}) AS DATE)");
+                let to_char = |value: String| format!("TO_CHAR({value}, 'YYYY-MM-DD HH24:MI:SS')");
+
+                match basis {
+                    ChronoAnalysisBasis::Hourly => {
+                        to_char(format!("DATE_TRUNC('hour', {timestamp})"))
+                    }
+                    ChronoAnalysisBasis::Daily => format!("CAST({col} AS DATE)"),
+                    ChronoAnalysisBasis::Weekly => date_trunc("week"),
+                    ChronoAnalysisBasis::Monthly => date_trunc("month"),
+                    ChronoAnalysisBasis::Quarterly => date_trunc("quarter"),
+                    ChronoAnalysisBasis::Yearly => date_trunc("year"),
+                    ChronoAnalysisBasis::DayOfWeek => {
+                        format!("CAST(EXTRACT(ISODOW FROM {timestamp}) AS INTEGER)")
+                    }
+                    ChronoAnalysisBasis::HourOfDay => {
+                        format!("CAST(EXTRACT(HOUR FROM {timestamp}) AS INTEGER)")
+                    }
+                    ChronoAnalysisBasis::Minutes(n) => {
+                        let seconds = *n as u64 * 60;
+                        to_char(format!(
+                            "TO_TIMESTAMP(FLOOR(EXTRACT(EPOCH FROM {timestamp}) / {seconds}) * {seconds}) AT TIME ZONE 'UTC'"
+                        ))
+                    }
+                    ChronoAnalysisBasis::Days(n) => format!(
+                        "DATE '1970-01-01' + CAST(FLOOR((CAST({col} AS DATE) - DATE '1970-01-01') / {n}.0) * {n} AS INTEGER)"
+                    ),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_sql_parser;
+
+/// Implements conversion of `postgres::Error` to [`BasableError`].
+impl From<postgres::Error> for BasableError {
+    fn from(value: postgres::Error) -> Self {
+        if value.is_closed() {
+            return Self::Connection(value.to_string());
+        }
+
+        match value.as_db_error() {
+            Some(err) => Self::Driver(err.message().to_string()),
+            None => Self::Driver(value.to_string()),
+        }
+    }
+}
+
+/// Reads the value at column `idx` of a Postgres [`Row`], according to the column's type.
+/// `TIMESTAMPTZ` values are converted to UTC.
+pub(crate) fn read_value(row: &Row, idx: usize) -> Result<BasableValue, postgres::Error> {
+    let ty = row.columns()[idx].type_();
+
+    let value = match *ty {
+        Type::BOOL => row.try_get::<_, Option<bool>>(idx)?.map(BasableValue::Bool),
+        Type::CHAR => row
+            .try_get::<_, Option<i8>>(idx)?
+            .map(|v| BasableValue::Text((v as u8 as char).to_string())),
+        Type::INT2 => row
+            .try_get::<_, Option<i16>>(idx)?
+            .map(|v| BasableValue::Int(v.into())),
+        Type::INT4 => row
+            .try_get::<_, Option<i32>>(idx)?
+            .map(|v| BasableValue::Int(v.into())),
+        Type::INT8 => row.try_get::<_, Option<i64>>(idx)?.map(BasableValue::Int),
+        Type::OID => row
+            .try_get::<_, Option<u32>>(idx)?
+            .map(|v| BasableValue::UInt(v.into())),
+        Type::FLOAT4 => row.try_get::<_, Option<f32>>(idx)?.map(BasableValue::Float),
+        Type::FLOAT8 => row.try_get::<_, Option<f64>>(idx)?.map(BasableValue::Double),
+        Type::NUMERIC => row
+            .try_get::<_, Option<PgNumeric>>(idx)?
+            .map(|v| BasableValue::Decimal(v.0)),
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN => {
+            row.try_get::<_, Option<String>>(idx)?.map(BasableValue::Text)
+        }
+        Type::BYTEA => row.try_get::<_, Option<Vec<u8>>>(idx)?.map(BasableValue::Bytes),
+        Type::JSON | Type::JSONB => row
+            .try_get::<_, Option<serde_json::Value>>(idx)?
+            .map(BasableValue::Json),
+        Type::UUID => row
+            .try_get::<_, Option<uuid::Uuid>>(idx)?
+            .map(|v| BasableValue::Text(v.to_string())),
+        Type::DATE => row
+            .try_get::<_, Option<time::Date>>(idx)?
+            .map(|d| BasableValue::Date(d.year(), d.month().into(), d.day(), 0, 0, 0, 0)),
+        Type::TIMESTAMP => row
+            .try_get::<_, Option<time::PrimitiveDateTime>>(idx)?
+            .map(datetime_value),
+        Type::TIMESTAMPTZ => row
+            .try_get::<_, Option<time::OffsetDateTime>>(idx)?
+            .map(|dt| {
+                let dt = dt.to_offset(time::UtcOffset::UTC);
+                datetime_value(time::PrimitiveDateTime::new(dt.date(), dt.time()))
+            }),
+        Type::TIME => row.try_get::<_, Option<time::Time>>(idx)?.map(|t| {
+            BasableValue::Time(false, 0, t.hour(), t.minute(), t.second(), t.microsecond())
+        }),
+        _ => row.try_get::<_, Option<PgRaw>>(idx)?.map(|raw| match ty.kind() {
+            // Enum labels are sent as text.
+            Kind::Enum(_) => BasableValue::Text(String::from_utf8_lossy(&raw.0).to_string()),
+            _ => BasableValue::Bytes(raw.0),
+        }),
+    };
+
+    Ok(value.unwrap_or(BasableValue::Null))
+}
+
+fn datetime_value(dt: time::PrimitiveDateTime) -> BasableValue {
+    BasableValue::Date(
+        dt.year(),
+        dt.month().into(),
+        dt.day(),
+        dt.hour(),
+        dt.minute(),
+        dt.second(),
+        dt.microsecond(),
+    )
+}
+
+/// A `NUMERIC` value decoded from Postgres binary format into its text representation.
+struct PgNumeric(String);
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let read = |i: usize| -> Result<u16, Box<dyn Error + Sync + Send>> {
+            match raw.get(i..i + 2) {
+                Some(b) => Ok(u16::from_be_bytes([b[0], b[1]])),
+                None => Err("invalid numeric value".into()),
+            }
+        };
+
+        let ndigits = read(0)? as usize;
+        let weight = read(2)? as i16 as i32;
+        let sign = read(4)?;
+        let dscale = read(6)? as usize;
+
+        match sign {
+            0xC000 => return Ok(PgNumeric("NaN".to_string())),
+            0xD000 => return Ok(PgNumeric("Infinity".to_string())),
+            0xF000 => return Ok(PgNumeric("-Infinity".to_string())),
+            _ => (),
+        }
+
+        // Each digit is a base 10000 group. `weight` is the position of the first group
+        // relative to the decimal point.
+        let mut digits = Vec::with_capacity(ndigits);
+        for i in 0..ndigits {
+            digits.push(read(8 + i * 2)?);
+        }
+
+        let digit = |pos: i32| -> u16 {
+            if pos < 0 {
+                0
+            } else {
+                digits.get(pos as usize).copied().unwrap_or(0)
+            }
+        };
+
+        let mut value = String::new();
+        if sign == 0x4000 {
+            value.push('-');
+        }
+
+        if weight < 0 {
+            value.push('0');
+        } else {
+            value.push_str(&digit(0).to_string());
+            for pos in 1..=weight {
+                value.push_str(&format!("{:04}", digit(pos)));
+            }
+        }
+
+        if dscale > 0 {
+            let mut frac = String::new();
+            let mut pos = weight + 1;
+
+            while frac.len() < dscale {
+                frac.push_str(&format!("{:04}", digit(pos)));
+                pos += 1;
+            }
+
+            frac.truncate(dscale);
+            value.push('.');
+            value.push_str(&frac);
+        }
+
+        Ok(PgNumeric(value))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+/// Encodes a decimal number, such as `-12.50`, in Postgres `NUMERIC` binary format.
+fn encode_numeric(value: &str, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(v) => (0x4000u16, v),
+        None => (0, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if int.is_empty() && frac.is_empty()
+        || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(format!("invalid numeric value: {value}").into());
+    }
+
+    // Pad both parts to whole base 10000 groups around the decimal point.
+    let int_len = int.len().div_ceil(4) * 4;
+    let frac_len = frac.len().div_ceil(4) * 4;
+    let digits = format!("{int:0>int_len$}{frac:0<frac_len$}");
+
+    let mut groups: Vec<u16> = digits
+        .as_bytes()
+        .chunks(4)
+        .map(|c| std::str::from_utf8(c).unwrap().parse().unwrap())
+        .collect();
+    let mut weight = (int_len / 4) as i16 - 1;
+
+    let leading = groups.iter().take_while(|g| **g == 0).count();
+    groups.drain(..leading);
+    weight -= leading as i16;
+
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+
+    let (weight, sign) = if groups.is_empty() {
+        (0, 0)
+    } else {
+        (weight, sign)
+    };
+
+    out.put_u16(groups.len() as u16);
+    out.put_i16(weight);
+    out.put_u16(sign);
+    out.put_u16(frac.len() as u16);
+    for g in groups {
+        out.put_u16(g);
+    }
+
+    Ok(())
+}
+
+/// Date, time or date time of a query parameter. Text is parsed with the supported
+/// [`DatePattern`]s.
+fn date_value(value: &BasableValue) -> Option<DateValue> {
+    match value {
+        BasableValue::Date(y, m, d, h, min, s, us) => {
+            let date = NaiveDate::from_ymd_opt(*y, (*m).into(), (*d).into())?;
+            let time = NaiveTime::from_hms_micro_opt((*h).into(), (*min).into(), (*s).into(), *us)?;
+            Some(DateValue::DateTime(date.and_time(time)))
+        }
+        BasableValue::Time(false, 0, h, min, s, us) => {
+            NaiveTime::from_hms_micro_opt((*h).into(), (*min).into(), (*s).into(), *us)
+                .map(DateValue::Time)
+        }
+        BasableValue::Text(v) => {
+            let v = v.trim();
+            ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(v, f).ok())
+                .map(DateValue::DateTime)
+                .or_else(|| DatePattern::supported().iter().find_map(|p| p.parse(v)))
+        }
+        _ => None,
+    }
+}
+
+/// Query parameters are encoded as the type Postgres infers for their placeholder, since
+/// Postgres doesn't convert them. Text is parsed into that type.
+impl ToSql for BasableValue {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if *self == BasableValue::Null {
+            return Ok(IsNull::Yes);
+        }
+
+        fn convert<T: FromBasableValue>(
+            value: &BasableValue,
+            ty: &Type,
+        ) -> Result<T, Box<dyn Error + Sync + Send>> {
+            T::from_value(value).ok_or_else(|| format!("can't convert {value} to {ty}").into())
+        }
+
+        let invalid =
+            || -> Box<dyn Error + Sync + Send> { format!("can't convert {self} to {ty}").into() };
+
+        match *ty {
+            Type::BOOL => convert::<bool>(self, ty)?.to_sql(ty, out),
+            Type::INT2 => convert::<i16>(self, ty)?.to_sql(ty, out),
+            Type::INT4 => convert::<i32>(self, ty)?.to_sql(ty, out),
+            Type::INT8 => convert::<i64>(self, ty)?.to_sql(ty, out),
+            Type::OID => convert::<u32>(self, ty)?.to_sql(ty, out),
+            Type::FLOAT4 => convert::<f32>(self, ty)?.to_sql(ty, out),
+            Type::FLOAT8 => convert::<f64>(self, ty)?.to_sql(ty, out),
+            Type::NUMERIC => {
+                encode_numeric(&convert::<String>(self, ty)?, out)?;
+                Ok(IsNull::No)
+            }
+            Type::DATE => match date_value(self).ok_or_else(invalid)? {
+                DateValue::Date(d) => d.to_sql(ty, out),
+                DateValue::DateTime(dt) => dt.date().to_sql(ty, out),
+                DateValue::Time(_) => Err(invalid()),
+            },
+            Type::TIMESTAMP | Type::TIMESTAMPTZ => {
+                let dt = match date_value(self).ok_or_else(invalid)? {
+                    DateValue::Date(d) => d.and_time(NaiveTime::MIN),
+                    DateValue::DateTime(dt) => dt,
+                    DateValue::Time(_) => return Err(invalid()),
+                };
+
+                match *ty {
+                    Type::TIMESTAMP => dt.to_sql(ty, out),
+                    _ => dt.and_utc().to_sql(ty, out),
+                }
+            }
+            Type::TIME => match date_value(self).ok_or_else(invalid)? {
+                DateValue::Time(t) => t.to_sql(ty, out),
+                DateValue::DateTime(dt) => dt.time().to_sql(ty, out),
+                DateValue::Date(_) => Err(invalid()),
+            },
+            Type::UUID => convert::<String>(self, ty)?
+                .parse::<uuid::Uuid>()?
+                .to_sql(ty, out),
+            Type::JSON | Type::JSONB => match self {
+                BasableValue::Json(v) => v.to_sql(ty, out),
+                BasableValue::Text(v) => {
+                    serde_json::from_str::<serde_json::Value>(v)?.to_sql(ty, out)
+                }
+                v => serde_json::to_value(v.to_string())?.to_sql(ty, out),
+            },
+            Type::BYTEA => match self {
+                BasableValue::Bytes(v) => v.to_sql(ty, out),
+                v => v.to_string().as_bytes().to_sql(ty, out),
+            },
+            // Text types and enum labels are sent as text.
+            _ => {
+                out.put_slice(convert::<String>(self, ty)?.as_bytes());
+                Ok(IsNull::No)
+            }
+        }
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// Raw binary value of a column whose type Basable does not map.
+struct PgRaw(Vec<u8>);
+
+impl<'a> FromSql<'a> for PgRaw {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(PgRaw(raw.to_vec()))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use chrono::NaiveDateTime;
+    use postgres::types::{FromSql, IsNull, ToSql, Type};
+
+    use crate::base::{
+        data::value::BasableValue,
+        imp::{
+            db::QuerySqlParser,
+            graphs::chrono::{ChronoAnalysisBasis, ChronoTimeZone},
+        },
+        BasableError,
+    };
+
+    use super::{encode_numeric, PgNumeric};
+
+    /// The SQL dialect of Postgres, to build SQL without a connection.
+    struct Dialect;
+
+    impl_sql_parser!(Dialect);
+
+    fn numeric(ndigits: u16, weight: i16, sign: u16, dscale: u16, digits: &[u16]) -> String {
+        let mut raw = Vec::new();
+        for v in [ndigits, weight as u16, sign, dscale] {
+            raw.extend_from_slice(&v.to_be_bytes());
+        }
+        for d in digits {
+            raw.extend_from_slice(&d.to_be_bytes());
+        }
+
+        PgNumeric::from_sql(&Type::NUMERIC, &raw).unwrap().0
+    }
+
+    #[test]
+    fn test_decode_numeric() {
+        // 12345.678
+        assert_eq!(numeric(3, 1, 0, 3, &[1, 2345, 6780]), "12345.678");
+        // -0.0042
+        assert_eq!(numeric(1, -1, 0x4000, 4, &[42]), "-0.0042");
+        // 20000 (trailing zero groups are not sent)
+        assert_eq!(numeric(1, 1, 0, 0, &[2]), "20000");
+        // 0.50
+        assert_eq!(numeric(1, -1, 0, 2, &[5000]), "0.50");
+    }
+
+    #[test]
+    fn test_encode_numeric() {
+        for value in ["12345.678", "-0.0042", "20000", "0.50", "0", "-7"] {
+            let mut out = BytesMut::new();
+            encode_numeric(value, &mut out).unwrap();
+            assert_eq!(PgNumeric::from_sql(&Type::NUMERIC, &out).unwrap().0, value);
+        }
+
+        assert!(encode_numeric("1e5", &mut BytesMut::new()).is_err());
+    }
+
+    #[test]
+    fn test_encode_params() {
+        let encode = |value: BasableValue, ty: &Type| {
+            let mut out = BytesMut::new();
+            value.to_sql(ty, &mut out).map(|_| out)
+        };
+
+        let numeric = |out: &BytesMut| PgNumeric::from_sql(&Type::NUMERIC, out).unwrap().0;
+
+        // Text is sent as the type of the placeholder.
+        let out = encode(BasableValue::Text("-12.50".to_string()), &Type::NUMERIC).unwrap();
+        assert_eq!(numeric(&out), "-12.50");
+        let out = encode(BasableValue::Text("42".to_string()), &Type::INT4).unwrap();
+        assert_eq!(i32::from_sql(&Type::INT4, &out).unwrap(), 42);
+        let out = encode(BasableValue::Double(0.5), &Type::NUMERIC).unwrap();
+        assert_eq!(numeric(&out), "0.5");
+
+        let date = BasableValue::Text("2024-02-29 13:45:00".to_string());
+        let out = encode(date, &Type::TIMESTAMP).unwrap();
+        let timestamp = NaiveDateTime::from_sql(&Type::TIMESTAMP, &out).unwrap();
+        assert_eq!(timestamp.to_string(), "2024-02-29 13:45:00");
+
+        let null = BasableValue::Null.to_sql(&Type::INT4, &mut BytesMut::new());
+        assert!(matches!(null, Ok(IsNull::Yes)));
+        assert!(encode(BasableValue::Text("Halo".to_string()), &Type::INT4).is_err());
+        assert!(encode(BasableValue::Text("Halo".to_string()), &Type::DATE).is_err());
+    }
+
+    #[test]
+    fn test_parse_chrono_basis() {
+        let basis = |basis| Dialect.parse_chrono_basis(&basis, "d");
+
+        assert_eq!(basis(ChronoAnalysisBasis::Daily), "CAST(d AS DATE)");
+        assert_eq!(
+            basis(ChronoAnalysisBasis::Monthly),
+            "CAST(DATE_TRUNC('month', CAST(d AS TIMESTAMP)) AS DATE)"
+        );
+        assert_eq!(
+            basis(ChronoAnalysisBasis::DayOfWeek),
+            "CAST(EXTRACT(ISODOW FROM CAST(d AS TIMESTAMP)) AS INTEGER)"
+        );
+        assert_eq!(
+            basis(ChronoAnalysisBasis::Hourly),
+            "TO_CHAR(DATE_TRUNC('hour', CAST(d AS TIMESTAMP)), 'YYYY-MM-DD HH24:MI:SS')"
+        );
+        assert!(basis(ChronoAnalysisBasis::Minutes(15)).contains(" / 900) * 900)"));
+        assert!(basis(ChronoAnalysisBasis::Days(7)).contains(" / 7.0) * 7 AS INTEGER)"));
+    }
+
+    #[test]
+    fn test_parse_time_zone() -> Result<(), BasableError> {
+        // Offsets are intervals, so that their sign isn't inverted.
+        let offset = Dialect.parse_time_zone("d", &ChronoTimeZone::Offset(-330))?;
+        assert_eq!(
+            offset,
+            "(CAST(d AS TIMESTAMPTZ) AT TIME ZONE INTERVAL '-05:30')"
+        );
+
+        let named = ChronoTimeZone::Named("Europe/Paris".to_string());
+        assert_eq!(
+            Dialect.parse_time_zone("d", &named)?,
+            "(CAST(d AS TIMESTAMPTZ) AT TIME ZONE 'Europe/Paris')"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sql_dialect() -> Result<(), BasableError> {
+        assert_eq!(
+            Dialect.parse_percentile(0.5, "total")?,
+            "PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY total)"
+        );
+        assert_eq!(Dialect.parse_text_cast("id"), "CAST(id AS TEXT)");
+        assert_eq!(Dialect.placeholder(2), "$2");
+        assert_eq!(Dialect.regex_operator(true), "!~");
+
+        // Tables that are not qualified belong to the default schema.
+        assert_eq!(Dialect.quote_table("sales.orders"), r#""sales"."orders""#);
+        assert_eq!(Dialect.quote_table("orders"), r#""public"."orders""#);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::base::{
+    column::{Column, ColumnList},
+    data::value::BasableValue,
+    imp::{table::Table, ConnectorType},
+    BasableError,
+};
+
+use super::impl_sql_parser;
+
+/// Schema of tables whose names are not qualified.
+pub(crate) const DEFAULT_SCHEMA: &str = "public";
+
+pub(crate) struct PostgresTable {
+    pub name: String,
+    pub schema: String,
+    pub table: String,
+    pub connector: ConnectorType,
+}
+
+impl PostgresTable {
+    /// Splits a table name into its schema and table. Names that are not qualified
+    /// belong to [`DEFAULT_SCHEMA`].
+    pub fn split_name(name: &str) -> (&str, &str) {
+        name.split_once('.').unwrap_or((DEFAULT_SCHEMA, name))
+    }
+}
+
+impl Table for PostgresTable {
+    fn new(name: String, conn: ConnectorType) -> Self
+    where
+        Self: Sized,
+    {
+        let (schema, table) = Self::split_name(&name);
+
+        PostgresTable {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            name,
+            connector: conn,
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn query_columns(&self) -> Result<ColumnList, BasableError> {
+        let query = "
+            SELECT
+                a.attname AS column_name,
+                pg_catalog.format_type(a.atttypid, a.atttypmod) AS column_type,
+                NOT a.attnotnull AS is_nullable,
+                pg_catalog.pg_get_expr(d.adbin, d.adrelid) AS column_default,
+                EXISTS (
+                    SELECT 1 FROM pg_catalog.pg_index i
+                    WHERE i.indrelid = c.oid AND i.indisunique AND i.indnatts = 1
+                        AND i.indpred IS NULL AND i.indkey[0] = a.attnum
+                ) AS is_unique,
+                EXISTS (
+                    SELECT 1 FROM pg_catalog.pg_index i
+                    WHERE i.indrelid = c.oid AND i.indisprimary AND i.indnatts = 1
+                        AND i.indkey[0] = a.attnum
+                ) AS is_primary
+            FROM pg_catalog.pg_attribute a
+            JOIN pg_catalog.pg_class c ON c.oid = a.attrelid
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            LEFT JOIN pg_catalog.pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+            WHERE n.nspname = $1
+                AND c.relname = $2
+                AND a.attnum > 0
+                AND NOT a.attisdropped
+            ORDER BY a.attnum
+        ";
+
+        let conn = self.connector();
+        let params = [
+            BasableValue::Text(self.schema.clone()),
+            BasableValue::Text(self.table.clone()),
+        ];
+        let result = conn.exec_query_params(query, &params)?;
+
+        let cols: ColumnList = result
+            .iter()
+            .map(|r| Column {
+                name: r.get("column_name").unwrap(),
+                col_type: r.get("column_type").unwrap(),
+                default_value: r.get("column_default").unwrap_or_default(),
+                nullable: r.get("is_nullable").unwrap_or(true),
+                unique: r.get("is_unique").unwrap_or(false),
+                primary: r.get("is_primary").unwrap_or(false),
+            })
+            .collect();
+
+        Ok(cols)
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        &self.connector
+    }
+}
+
+impl_sql_parser!(PostgresTable);
@@ -0,0 +1,150 @@
+use std::{path::Path, sync::Arc};
+
+use axum::http::StatusCode;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use regex::Regex;
+use rusqlite::{
+    functions::FunctionFlags, params_from_iter, types::ValueRef, Connection, OpenFlags,
+    TransactionBehavior,
+};
+
+use crate::base::{
+    config::{resolve_data_path, ConnectionConfig},
+    data::{row::BasableRow, value::BasableValue},
+    imp::connector::{Connector, QueryExecutor, TransactionFn},
+    AppError, BasableError,
+};
+
+/// SQLite implementation of `BasableConnection`. It connects to an existing SQLite database file.
+#[derive(Clone)]
+pub struct SqliteConnector {
+    /// Database connection pool
+    pub pool: Pool<SqliteConnectionManager>,
+
+    /// Connection options
+    pub config: ConnectionConfig,
+}
+
+impl Connector for SqliteConnector {
+    fn new(config: ConnectionConfig) -> Result<Self, AppError> {
+        let path = match &config.path {
+            Some(path) => path.clone(),
+            None => {
+                return Err(AppError::new(
+                    StatusCode::EXPECTATION_FAILED,
+                    "Please provide the 'path' of the SQLite database.",
+                ))
+            }
+        };
+
+        let path = resolve_data_path(Path::new(&path))?;
+        if !path.is_file() {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                "Can't find a SQLite database at the given path.",
+            ));
+        }
+
+        // We don't want to create a new database file if it doesn't exist. Paths are not read
+        // as URIs, whose parameters could open another file than the checked one.
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+        let manager = SqliteConnectionManager::file(path)
+            .with_flags(flags)
+            .with_init(add_functions);
+        let pool = Pool::new(manager).map_err(BasableError::from)?;
+
+        Ok(SqliteConnector { pool, config })
+    }
+
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        let conn = self.pool.get()?;
+        QueryExecutor::exec_query_params(&*conn, query, params)
+    }
+
+    /// The transaction takes the write lock of the database when it starts, so that other
+    /// connections can only read the database until it ends.
+    fn transaction(&self, f: &mut TransactionFn) -> Result<(), AppError> {
+        let mut conn = self.pool.get().map_err(BasableError::from)?;
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(BasableError::from)?;
+
+        f(&*tx)?;
+        tx.commit().map_err(BasableError::from)?;
+
+        Ok(())
+    }
+
+    fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+}
+
+impl QueryExecutor for Connection {
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        let mut stmt = self.prepare(query)?;
+
+        let columns: Arc<[String]> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = stmt.query(params_from_iter(params))?;
+        let mut results = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            let mut values = Vec::with_capacity(columns.len());
+            for i in 0..columns.len() {
+                values.push(sqlite_value(row.get_ref(i)?));
+            }
+
+            results.push(BasableRow::new(columns.clone(), values));
+        }
+
+        Ok(results)
+    }
+}
+
+/// Adds the SQL functions that SQLite leaves to applications, so that queries built for
+/// other databases run on SQLite too. `REGEXP` calls `regexp(pattern, value)`.
+pub(crate) fn add_functions(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            // The pattern is compiled once per statement.
+            let regex: Arc<Regex> = ctx.get_or_create_aux(0, |pattern| -> Result<_, BoxError> {
+                Ok(Regex::new(pattern.as_str()?)?)
+            })?;
+
+            let value = match ctx.get_raw(1) {
+                ValueRef::Null => return Ok(None),
+                ValueRef::Integer(v) => v.to_string(),
+                ValueRef::Real(v) => v.to_string(),
+                ValueRef::Text(v) | ValueRef::Blob(v) => String::from_utf8_lossy(v).into_owned(),
+            };
+
+            Ok(Some(regex.is_match(&value)))
+        },
+    )
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Converts a SQLite column value into a [`BasableValue`].
+fn sqlite_value(value: ValueRef) -> BasableValue {
+    match value {
+        ValueRef::Null => BasableValue::Null,
+        ValueRef::Integer(v) => BasableValue::Int(v),
+        ValueRef::Real(v) => BasableValue::Double(v),
+        ValueRef::Text(v) => BasableValue::Text(String::from_utf8_lossy(v).into_owned()),
+        ValueRef::Blob(v) => BasableValue::Bytes(v.to_vec()),
+    }
+}
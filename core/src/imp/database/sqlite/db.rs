@@ -0,0 +1,210 @@
+use std::{collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+
+use crate::{
+    base::{
+        data::{
+            row::BasableRow,
+            table::{TableSummaries, TableSummary},
+            value::BasableValue,
+        },
+        imp::{
+            db::{QuerySqlParser, DB},
+            graphs::chrono::{ChronoAnalysisBasis, ChronoTimeZone},
+            table::Table,
+            ConnectorType, SharedTable,
+        },
+        query::quote_ident,
+        AppError, BasableError,
+    },
+    imp::database::{DBVersion, DbConnectionDetails},
+};
+
+use super::table::SqliteTable;
+
+pub(crate) struct SqliteDB {
+    pub connector: ConnectorType,
+    pub tables: Vec<SharedTable>,
+    user_id: String,
+    id: Uuid,
+}
+
+impl SqliteDB {
+    pub fn new(connector: ConnectorType, user_id: String, id: Uuid) -> Self {
+        SqliteDB {
+            connector,
+            tables: Vec::new(),
+            user_id,
+            id,
+        }
+    }
+
+    /// Get SQLite library version
+    fn show_version(&self) -> Result<DBVersion, AppError> {
+        let qr = self.exec_query("SELECT sqlite_version() AS version")?;
+
+        let mut data = HashMap::new();
+        if let Some(version) = qr.first().and_then(|r| r.get::<String>("version")) {
+            data.insert("version".to_string(), version);
+        }
+
+        Ok(data)
+    }
+
+    /// Size of the database file in MB.
+    fn size(&self) -> Result<f64, AppError> {
+        let qr = self.exec_query(
+            "
+                SELECT page_count * page_size AS size
+                FROM pragma_page_count(), pragma_page_size()
+            ",
+        )?;
+
+        let size: i64 = qr.first().and_then(|r| r.get("size")).unwrap_or(0);
+        let size = (size as f64 / 1024.0 / 1024.0 * 10.0).round() / 10.0;
+
+        Ok(size)
+    }
+
+    fn exec_query(&self, query: &str) -> Result<Vec<BasableRow>, BasableError> {
+        self.connector.exec_query(query)
+    }
+}
+
+impl DB for SqliteDB {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        &self.connector
+    }
+
+    fn load_tables(&mut self, connector: ConnectorType) -> Result<(), AppError> {
+        let tables = self.query_tables()?;
+
+        for t in tables {
+            let name: String = t.get("name").unwrap();
+            let table = SqliteTable::new(name, connector.clone());
+            self.tables.push(Arc::new(table));
+        }
+
+        Ok(())
+    }
+
+    fn tables(&self) -> &Vec<SharedTable> {
+        &self.tables
+    }
+
+    fn query_tables(&self) -> Result<Vec<BasableRow>, BasableError> {
+        let query = "
+            SELECT name
+            FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+            ORDER BY name
+        ";
+
+        self.exec_query(query)
+    }
+
+    fn query_table_summaries(&self) -> Result<TableSummaries, AppError> {
+        let results = self.query_tables()?;
+        let mut tables = Vec::with_capacity(results.len());
+
+        for res in results {
+            let name: String = res.get("name").unwrap();
+
+            let query = format!("SELECT COUNT(*) AS row_count FROM {}", quote_ident(&name));
+            let qr = self.exec_query(&query)?;
+            let row_count = qr.first().and_then(|r| r.get("row_count")).unwrap_or(0);
+
+            let col_count = self.query_column_count(&name)?;
+
+            // SQLite does not keep track of when tables are created or updated.
+            tables.push(TableSummary {
+                name,
+                row_count,
+                col_count,
+                created: None,
+                updated: None,
+            });
+        }
+
+        Ok(tables)
+    }
+
+    fn query_column_count(&self, tb_name: &str) -> Result<u32, AppError> {
+        let query = "SELECT COUNT(*) AS col_count FROM pragma_table_info(?)";
+        let params = [BasableValue::Text(tb_name.to_string())];
+
+        let qr = self.connector.exec_query_params(query, &params)?;
+        let c: u32 = qr.first().and_then(|r| r.get("col_count")).unwrap_or(0);
+
+        Ok(c)
+    }
+
+    fn get_table(&self, name: &str) -> Option<&SharedTable> {
+        self.tables.iter().find(|t| t.name() == name)
+    }
+
+    fn details(&self) -> Result<DbConnectionDetails, AppError> {
+        let version = self.show_version()?;
+        let tables = self.query_table_summaries()?;
+        let size = self.size()?;
+
+        Ok(DbConnectionDetails {
+            id: self.id.to_string(),
+            tables,
+            version,
+            db_size: size,
+        })
+    }
+}
+
+impl QuerySqlParser for SqliteDB {
+    fn parse_time_zone(
+        &self,
+        col: &str,
+        time_zone: &ChronoTimeZone,
+    ) -> Result<String, BasableError> {
+        match time_zone {
+            ChronoTimeZone::Offset(offset) => Ok(format!("DATETIME({col}, '{offset:+} minutes')")),
+            ChronoTimeZone::Named(_) => Err(BasableError::Unsupported(
+                "SQLite only supports time zone offsets, such as '+02:00'.".to_string(),
+            )),
+        }
+    }
+
+    fn parse_chrono_basis(&self, basis: &ChronoAnalysisBasis, col: &str) -> String {
+        // Rounds `value` down to a multiple of `n`, for negative values too.
+        let floor = |value: String, n: u64| format!("({value} - ((({value}) % {n}) + {n}) % {n})");
+
+        match basis {
+            ChronoAnalysisBasis::Hourly => format!("STRFTIME('%Y-%m-%d %H:00:00', {col})"),
+            ChronoAnalysisBasis::Daily => format!("DATE({col})"),
+            ChronoAnalysisBasis::Weekly => format!("DATE({col}, 'weekday 0', '-6 days')"),
+            ChronoAnalysisBasis::Monthly => format!("DATE({col}, 'start of month')"),
+            ChronoAnalysisBasis::Quarterly => format!(
+                "DATE({col}, 'start of month', '-' || ((CAST(STRFTIME('%m', {col}) AS INTEGER) - 1) % 3) || ' months')"
+            ),
+            ChronoAnalysisBasis::Yearly => format!("DATE({col}, 'start of year')"),
+            ChronoAnalysisBasis::DayOfWeek => {
+                format!("(CAST(STRFTIME('%w', {col}) AS INTEGER) + 6) % 7 + 1")
+            }
+            ChronoAnalysisBasis::HourOfDay => format!("CAST(STRFTIME('%H', {col}) AS INTEGER)"),
+            ChronoAnalysisBasis::Minutes(n) => {
+                let seconds = format!("CAST(STRFTIME('%s', {col}) AS INTEGER)");
+                format!("DATETIME({}, 'unixepoch')", floor(seconds, *n as u64 * 60))
+            }
+            ChronoAnalysisBasis::Days(n) => {
+                let days = format!("CAST(JULIANDAY(DATE({col})) - 2440587.5 AS INTEGER)");
+                format!("DATE({} + 2440587.5)", floor(days, *n as u64))
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use rusqlite::{
+    types::{ToSqlOutput, Value, ValueRef},
+    ToSql,
+};
+
+use crate::base::{data::value::BasableValue, BasableError};
+
+pub(crate) mod connector;
+pub(crate) mod db;
+pub(crate) mod graphs;
+pub(crate) mod table;
+
+/// Implements conversion of `rusqlite::Error` to [`BasableError`].
+impl From<rusqlite::Error> for BasableError {
+    fn from(value: rusqlite::Error) -> Self {
+        match value {
+            rusqlite::Error::SqliteFailure(err, msg)
+                if err.code == rusqlite::ErrorCode::CannotOpen =>
+            {
+                Self::Connection(msg.unwrap_or(err.to_string()))
+            }
+            err => Self::Driver(err.to_string()),
+        }
+    }
+}
+
+/// Conversion of query parameters. SQLite has no date types, so dates are bound as ISO 8601 text.
+impl ToSql for BasableValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let value = match self {
+            BasableValue::Null => ToSqlOutput::Owned(Value::Null),
+            BasableValue::Bool(v) => ToSqlOutput::Owned(Value::Integer(i64::from(*v))),
+            BasableValue::Int(v) => ToSqlOutput::Owned(Value::Integer(*v)),
+            BasableValue::UInt(v) => match i64::try_from(*v) {
+                Ok(v) => ToSqlOutput::Owned(Value::Integer(v)),
+                Err(_) => ToSqlOutput::Owned(Value::Text(v.to_string())),
+            },
+            BasableValue::Float(v) => ToSqlOutput::Owned(Value::Real(f64::from(*v))),
+            BasableValue::Double(v) => ToSqlOutput::Owned(Value::Real(*v)),
+            BasableValue::Decimal(v) | BasableValue::Text(v) => {
+                ToSqlOutput::Borrowed(ValueRef::Text(v.as_bytes()))
+            }
+            BasableValue::Bytes(v) => ToSqlOutput::Borrowed(ValueRef::Blob(v)),
+            v => ToSqlOutput::Owned(Value::Text(v.to_string())),
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use r2d2::Pool;
+    use r2d2_sqlite::SqliteConnectionManager;
+    use uuid::Uuid;
+
+    use crate::base::{
+        config::ConnectionConfig,
+        data::value::BasableValue,
+        imp::{
+            db::QuerySqlParser, graphs::chrono::ChronoAnalysisBasis, table::Table, ConnectorType,
+        },
+        BasableError,
+    };
+
+    use super::{
+        connector::{add_functions, SqliteConnector},
+        db::SqliteDB,
+        table::SqliteTable,
+    };
+
+    /// A connector to a new in-memory database. It has one connection, since every
+    /// connection to `:memory:` opens a different database.
+    fn memory_connector() -> ConnectorType {
+        let manager = SqliteConnectionManager::memory().with_init(add_functions);
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let config = ConnectionConfig {
+            source: "sqlite".to_string(),
+            ..Default::default()
+        };
+
+        Arc::new(SqliteConnector { pool, config })
+    }
+
+    #[test]
+    fn test_query_columns() -> Result<(), BasableError> {
+        let conn = memory_connector();
+        conn.exec_query(
+            "CREATE TABLE keyed (
+                id INTEGER PRIMARY KEY,
+                email TEXT NOT NULL UNIQUE,
+                name TEXT,
+                code TEXT,
+                kind TEXT DEFAULT 'game',
+                UNIQUE (code, kind)
+            )",
+        )?;
+        conn.exec_query("CREATE UNIQUE INDEX keyed_name ON keyed (name) WHERE name IS NOT NULL")?;
+        conn.exec_query("CREATE TABLE pair (a INTEGER, b INTEGER, PRIMARY KEY (a, b))")?;
+
+        let cols = SqliteTable::new("keyed".to_string(), conn.clone()).query_columns()?;
+        let col = |name: &str| cols.iter().find(|c| c.name == name).unwrap();
+        assert!(col("id").primary && col("id").unique);
+        assert!(!col("email").primary && col("email").unique && !col("email").nullable);
+        assert_eq!(col("kind").default_value.as_deref(), Some("'game'"));
+        assert_eq!(col("code").col_type, "TEXT");
+
+        // Keys of several columns and partial indexes don't make their columns unique.
+        for name in ["name", "code", "kind"] {
+            assert!(!col(name).primary && !col(name).unique, "{name}");
+        }
+
+        let cols = SqliteTable::new("pair".to_string(), conn).query_columns()?;
+        assert!(cols.iter().all(|c| !c.primary && !c.unique));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_values() -> Result<(), BasableError> {
+        let conn = memory_connector();
+        let rows = conn.exec_query_params(
+            "SELECT NULL AS n, 42 AS i, 0.5 AS d, 'Halo' AS t, X'0102' AS b, ?1 AS bool, ?2 AS date",
+            &[
+                BasableValue::Bool(true),
+                BasableValue::Date(2024, 2, 29, 13, 45, 0, 0),
+            ],
+        )?;
+        let row = &rows[0];
+
+        assert_eq!(row.value("n"), Some(&BasableValue::Null));
+        assert_eq!(row.value("i"), Some(&BasableValue::Int(42)));
+        assert_eq!(row.value("d"), Some(&BasableValue::Double(0.5)));
+        assert_eq!(
+            row.value("t"),
+            Some(&BasableValue::Text("Halo".to_string()))
+        );
+        assert_eq!(row.value("b"), Some(&BasableValue::Bytes(vec![1, 2])));
+        assert_eq!(row.get::<i64>("i"), Some(42));
+
+        // SQLite has no booleans nor dates, they are bound as integers and text.
+        assert_eq!(row.value("bool"), Some(&BasableValue::Int(1)));
+        assert_eq!(
+            row.get::<String>("date").as_deref(),
+            Some("2024-02-29 13:45:00")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_chrono_basis() -> Result<(), BasableError> {
+        let conn = memory_connector();
+        let db = SqliteDB::new(conn.clone(), "test_user".to_string(), Uuid::new_v4());
+
+        // 2024-05-15 is a Wednesday.
+        let bucket = |basis| -> Result<String, BasableError> {
+            let sql = db.parse_chrono_basis(&basis, "'2024-05-15 13:47:12'");
+            let rows = conn.exec_query(&format!("SELECT {sql} AS bucket"))?;
+            Ok(rows[0].value("bucket").unwrap().to_string())
+        };
+
+        assert_eq!(bucket(ChronoAnalysisBasis::Hourly)?, "2024-05-15 13:00:00");
+        assert_eq!(bucket(ChronoAnalysisBasis::Daily)?, "2024-05-15");
+        assert_eq!(bucket(ChronoAnalysisBasis::Weekly)?, "2024-05-13");
+        assert_eq!(bucket(ChronoAnalysisBasis::Monthly)?, "2024-05-01");
+        assert_eq!(bucket(ChronoAnalysisBasis::Quarterly)?, "2024-04-01");
+        assert_eq!(bucket(ChronoAnalysisBasis::Yearly)?, "2024-01-01");
+        assert_eq!(bucket(ChronoAnalysisBasis::DayOfWeek)?, "3");
+        assert_eq!(bucket(ChronoAnalysisBasis::HourOfDay)?, "13");
+        assert_eq!(
+            bucket(ChronoAnalysisBasis::Minutes(15))?,
+            "2024-05-15 13:45:00"
+        );
+        // Buckets of days are counted from 1970-01-01, so 19852 days later.
+        assert_eq!(bucket(ChronoAnalysisBasis::Days(7))?, "2024-05-09");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::base::{
+    column::{Column, ColumnList},
+    data::value::BasableValue,
+    imp::{db::QuerySqlParser, table::Table, ConnectorType},
+    BasableError,
+};
+
+pub(crate) struct SqliteTable {
+    pub name: String,
+    pub connector: ConnectorType,
+}
+
+impl SqliteTable {
+    /// Names of columns that are unique on their own: the only column of a unique index.
+    /// Columns of an index of several columns, or of a partial index, can have duplicates.
+    fn query_unique_columns(&self) -> Result<Vec<String>, BasableError> {
+        let query = "
+            SELECT ii.name
+            FROM pragma_index_list(?) AS il,
+                pragma_index_info(il.name) AS ii
+            WHERE il.\"unique\" = 1
+                AND il.partial = 0
+                AND (SELECT COUNT(*) FROM pragma_index_info(il.name)) = 1
+        ";
+
+        let params = [BasableValue::Text(self.name.clone())];
+        let result = self.connector.exec_query_params(query, &params)?;
+        let cols = result.iter().filter_map(|r| r.get("name")).collect();
+
+        Ok(cols)
+    }
+}
+
+impl Table for SqliteTable {
+    fn new(name: String, conn: ConnectorType) -> Self
+    where
+        Self: Sized,
+    {
+        SqliteTable {
+            name,
+            connector: conn,
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn query_columns(&self) -> Result<ColumnList, BasableError> {
+        let query = "
+            SELECT name, type, \"notnull\", dflt_value, pk
+            FROM pragma_table_info(?)
+            ORDER BY cid
+        ";
+
+        let conn = self.connector();
+        let params = [BasableValue::Text(self.name.clone())];
+        let result = conn.exec_query_params(query, &params)?;
+        let unique_cols = self.query_unique_columns()?;
+
+        // Columns of a primary key of several columns aren't unique on their own.
+        let pk_count = result
+            .iter()
+            .filter(|r| r.get::<i64>("pk").unwrap_or(0) > 0)
+            .count();
+
+        let cols: ColumnList = result
+            .iter()
+            .map(|r| {
+                let name: String = r.get("name").unwrap();
+                let not_null: i64 = r.get("notnull").unwrap_or(0);
+                let pk: i64 = r.get("pk").unwrap_or(0);
+                let primary = pk > 0 && pk_count == 1;
+                let unique = primary || unique_cols.contains(&name);
+
+                Column {
+                    col_type: r.get("type").unwrap_or_default(),
+                    default_value: r.get("dflt_value").unwrap_or_default(),
+                    nullable: not_null == 0,
+                    primary,
+                    unique,
+                    name,
+                }
+            })
+            .collect();
+
+        Ok(cols)
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        &self.connector
+    }
+}
+
+impl QuerySqlParser for SqliteTable {}
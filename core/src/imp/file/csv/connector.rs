@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use axum::http::StatusCode;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+
+use crate::{
+    base::{
+        config::{resolve_data_path, ConnectionConfig},
+        data::{row::BasableRow, value::BasableValue},
+        imp::connector::{Connector, TransactionFn},
+        AppError, BasableError,
+    },
+    imp::database::sqlite::connector::{add_functions, SqliteConnector},
+};
+
+use super::{csv_files, load_file};
+
+/// CSV implementation of `BasableConnection`. The CSV files are loaded into an in-memory
+/// SQLite database, so that they can be queried like any other database.
+#[derive(Clone)]
+pub struct CsvConnector {
+    inner: SqliteConnector,
+}
+
+impl Connector for CsvConnector {
+    fn new(config: ConnectionConfig) -> Result<Self, AppError> {
+        let path = match &config.path {
+            Some(path) => path.clone(),
+            None => {
+                return Err(AppError::new(
+                    StatusCode::EXPECTATION_FAILED,
+                    "Please provide the 'path' of the CSV file or directory.",
+                ))
+            }
+        };
+
+        // Files of a directory may link outside the data directory, so each file is checked.
+        let path = resolve_data_path(Path::new(&path))?;
+        let files: Vec<_> = csv_files(&path)
+            .map_err(|_| AppError::new(StatusCode::NOT_FOUND, "Can't find the given path."))?
+            .iter()
+            .filter_map(|file| resolve_data_path(file).ok())
+            .collect();
+
+        if files.is_empty() {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                "Can't find any CSV file at the given path.",
+            ));
+        }
+
+        // Every connection to `:memory:` opens a different database, so the pool must keep
+        // exactly one connection for as long as it lives.
+        let manager = SqliteConnectionManager::memory().with_init(add_functions);
+        let pool = Pool::builder()
+            .max_size(1)
+            .max_lifetime(None)
+            .idle_timeout(None)
+            .build(manager)
+            .map_err(BasableError::from)?;
+
+        {
+            let mut conn = pool.get().map_err(BasableError::from)?;
+
+            for file in &files {
+                load_file(&mut conn, file).map_err(|err| {
+                    let msg = format!("Can't load '{}': {err}", file.display());
+                    AppError(StatusCode::UNPROCESSABLE_ENTITY, msg)
+                })?;
+            }
+        }
+
+        Ok(CsvConnector {
+            inner: SqliteConnector { pool, config },
+        })
+    }
+
+    fn exec_query_params(
+        &self,
+        query: &str,
+        params: &[BasableValue],
+    ) -> Result<Vec<BasableRow>, BasableError> {
+        self.inner.exec_query_params(query, params)
+    }
+
+    /// CSV tables are read-only, so there's nothing to change in a transaction.
+    fn transaction(&self, _: &mut TransactionFn) -> Result<(), AppError> {
+        let msg = "CSV data sources are read-only.".to_string();
+        Err(BasableError::Unsupported(msg).into())
+    }
+
+    fn config(&self) -> &ConnectionConfig {
+        self.inner.config()
+    }
+}
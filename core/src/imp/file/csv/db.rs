@@ -0,0 +1,152 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    base::{
+        data::{row::BasableRow, table::TableSummaries},
+        imp::{
+            db::{QuerySqlParser, DB},
+            graphs::chrono::{ChronoAnalysisBasis, ChronoTimeZone},
+            table::Table,
+            ConnectorType, SharedTable,
+        },
+        AppError, BasableError,
+    },
+    imp::database::{sqlite::db::SqliteDB, DBVersion, DbConnectionDetails},
+};
+
+use super::{csv_files, table::CsvTable, table_name};
+
+/// A CSV file or directory of CSV files. Each file is a table.
+pub(crate) struct CsvDB {
+    pub connector: ConnectorType,
+    pub tables: Vec<SharedTable>,
+
+    /// SQLite database the CSV files are loaded into.
+    pub(super) inner: SqliteDB,
+    files: Vec<PathBuf>,
+    user_id: String,
+    id: Uuid,
+}
+
+impl CsvDB {
+    pub fn new(connector: ConnectorType, user_id: String, id: Uuid) -> Self {
+        let files = connector
+            .config()
+            .path
+            .as_ref()
+            .and_then(|path| csv_files(path.as_ref()).ok())
+            .unwrap_or_default();
+
+        CsvDB {
+            inner: SqliteDB::new(connector.clone(), user_id.clone(), id),
+            connector,
+            tables: Vec::new(),
+            files,
+            user_id,
+            id,
+        }
+    }
+
+    /// Total size of the CSV files in MB.
+    fn size(&self) -> f64 {
+        let size: u64 = self
+            .files
+            .iter()
+            .filter_map(|f| f.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+
+        (size as f64 / 1024.0 / 1024.0 * 10.0).round() / 10.0
+    }
+
+    /// Date the CSV file of table `name` was last modified.
+    fn modified(&self, name: &str) -> Option<String> {
+        let file = self.files.iter().find(|f| table_name(f) == name)?;
+        let modified = file.metadata().ok()?.modified().ok()?;
+
+        Some(OffsetDateTime::from(modified).date().to_string())
+    }
+}
+
+impl DB for CsvDB {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        &self.connector
+    }
+
+    fn load_tables(&mut self, connector: ConnectorType) -> Result<(), AppError> {
+        // Graphs are queried through `inner`, which resolves table names from its own tables.
+        self.inner.load_tables(connector.clone())?;
+        let tables = self.query_tables()?;
+
+        for t in tables {
+            let name: String = t.get("name").unwrap();
+            let table = CsvTable::new(name, connector.clone());
+            self.tables.push(Arc::new(table));
+        }
+
+        Ok(())
+    }
+
+    fn tables(&self) -> &Vec<SharedTable> {
+        &self.tables
+    }
+
+    fn query_tables(&self) -> Result<Vec<BasableRow>, BasableError> {
+        self.inner.query_tables()
+    }
+
+    fn query_table_summaries(&self) -> Result<TableSummaries, AppError> {
+        let mut tables = self.inner.query_table_summaries()?;
+
+        for t in tables.iter_mut() {
+            t.updated = self.modified(&t.name);
+        }
+
+        Ok(tables)
+    }
+
+    fn query_column_count(&self, tb_name: &str) -> Result<u32, AppError> {
+        self.inner.query_column_count(tb_name)
+    }
+
+    fn get_table(&self, name: &str) -> Option<&SharedTable> {
+        self.tables.iter().find(|t| t.name() == name)
+    }
+
+    fn details(&self) -> Result<DbConnectionDetails, AppError> {
+        let mut version: DBVersion = HashMap::new();
+        version.insert("format".to_string(), "csv".to_string());
+
+        Ok(DbConnectionDetails {
+            id: self.id.to_string(),
+            tables: self.query_table_summaries()?,
+            version,
+            db_size: self.size(),
+        })
+    }
+}
+
+impl QuerySqlParser for CsvDB {
+    fn parse_chrono_basis(&self, basis: &ChronoAnalysisBasis, col: &str) -> String {
+        self.inner.parse_chrono_basis(basis, col)
+    }
+
+    fn parse_time_zone(
+        &self,
+        col: &str,
+        time_zone: &ChronoTimeZone,
+    ) -> Result<String, BasableError> {
+        self.inner.parse_time_zone(col, time_zone)
+    }
+}
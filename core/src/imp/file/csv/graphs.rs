@@ -0,0 +1,24 @@
+use crate::base::{
+    imp::graphs::{
+        category::CategoryGraphOpts, chrono::ChronoAnalysisOpts, trend::TrendGraphOpts,
+        AnalysisGraph, VisualizeDB,
+    },
+    AppError, BasableError,
+};
+
+use super::db::CsvDB;
+
+/// CSV files are queried through SQLite.
+impl VisualizeDB for CsvDB {
+    fn chrono_graph(&self, opts: ChronoAnalysisOpts) -> Result<AnalysisGraph, BasableError> {
+        self.inner.chrono_graph(opts)
+    }
+
+    fn trend_graph(&self, opts: TrendGraphOpts) -> Result<AnalysisGraph, BasableError> {
+        self.inner.trend_graph(opts)
+    }
+
+    fn category_graph(&self, opts: CategoryGraphOpts) -> Result<AnalysisGraph, AppError> {
+        self.inner.category_graph(opts)
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{params_from_iter, types::Value, Connection};
+
+use crate::{
+    base::{query::quote_ident, BasableError},
+    utils::datetime_parser::DatePattern,
+};
+
+pub(crate) mod connector;
+pub(crate) mod db;
+pub(crate) mod graphs;
+pub(crate) mod table;
+
+impl From<::csv::Error> for BasableError {
+    fn from(value: ::csv::Error) -> Self {
+        Self::Driver(value.to_string())
+    }
+}
+
+/// Type of a CSV column, inferred from its values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CsvColumnType {
+    Integer,
+    Real,
+    Date,
+    Time,
+    DateTime,
+    Text,
+}
+
+impl CsvColumnType {
+    fn sql_type(&self) -> &'static str {
+        match self {
+            CsvColumnType::Integer => "INTEGER",
+            CsvColumnType::Real => "REAL",
+            CsvColumnType::Date => "DATE",
+            CsvColumnType::Time => "TIME",
+            CsvColumnType::DateTime => "DATETIME",
+            CsvColumnType::Text => "TEXT",
+        }
+    }
+}
+
+struct CsvColumn {
+    name: String,
+    col_type: CsvColumnType,
+
+    /// Pattern shared by all values of a date column.
+    pattern: Option<DatePattern<'static>>,
+}
+
+impl CsvColumn {
+    /// Infers the column's type from its non-empty `values`. A column is a date column when
+    /// all of its values match the same [`DatePattern`].
+    fn infer(name: String, values: &[&str]) -> Self {
+        let col_type = if values.is_empty() {
+            CsvColumnType::Text
+        } else if values.iter().all(|v| v.parse::<i64>().is_ok()) {
+            CsvColumnType::Integer
+        } else if values.iter().all(|v| is_real(v)) {
+            CsvColumnType::Real
+        } else {
+            let pattern = DatePattern::supported()
+                .into_iter()
+                .find(|p| values.iter().all(|v| p.parse(v).is_some()));
+
+            if let Some(pattern) = pattern {
+                let col_type = match pattern.value() {
+                    p if !p.contains("%Y") => CsvColumnType::Time,
+                    p if !p.contains("%H") && !p.contains("%I") => CsvColumnType::Date,
+                    _ => CsvColumnType::DateTime,
+                };
+
+                return CsvColumn {
+                    name,
+                    col_type,
+                    pattern: Some(pattern),
+                };
+            }
+
+            CsvColumnType::Text
+        };
+
+        CsvColumn {
+            name,
+            col_type,
+            pattern: None,
+        }
+    }
+
+    /// Converts a raw CSV value into the value stored for the column. Dates are stored as
+    /// ISO 8601 text, so that SQLite date functions can be used on them.
+    fn value(&self, raw: &str) -> Value {
+        let v = raw.trim();
+        if v.is_empty() {
+            return Value::Null;
+        }
+
+        match (self.col_type, &self.pattern) {
+            (CsvColumnType::Integer, _) => v.parse().map_or(Value::Null, Value::Integer),
+            (CsvColumnType::Real, _) => v.parse().map_or(Value::Null, Value::Real),
+            (CsvColumnType::Text, _) => Value::Text(raw.to_string()),
+            (_, Some(pattern)) => pattern
+                .parse(v)
+                .map_or(Value::Null, |d| Value::Text(d.to_iso())),
+            (_, None) => Value::Text(raw.to_string()),
+        }
+    }
+}
+
+/// `f64` parses `NaN` and `inf`, which we want to keep as text.
+fn is_real(value: &str) -> bool {
+    value.parse::<f64>().is_ok() && value.chars().any(|c| c.is_ascii_digit())
+}
+
+/// CSV files of a data source. `path` may be a CSV file or a directory of CSV files.
+pub(crate) fn csv_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Name of the table created for a CSV file.
+pub(crate) fn table_name(file: &Path) -> String {
+    file.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Creates a table for the CSV `file` in `conn` and loads all of its records. Column names
+/// are read from the header row.
+pub(crate) fn load_file(conn: &mut Connection, file: &Path) -> Result<(), BasableError> {
+    let mut reader = ::csv::ReaderBuilder::new().flexible(true).from_path(file)?;
+
+    let mut names: Vec<String> = Vec::new();
+    for (i, header) in reader.headers()?.iter().enumerate() {
+        let mut name = match header.trim() {
+            "" => format!("column_{}", i + 1),
+            h => h.to_string(),
+        };
+
+        // SQLite column names are case insensitive.
+        let base = name.clone();
+        let mut n = 1;
+        while names.iter().any(|c| c.eq_ignore_ascii_case(&name)) {
+            n += 1;
+            name = format!("{base}_{n}");
+        }
+
+        names.push(name);
+    }
+
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let columns: Vec<CsvColumn> = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let values: Vec<&str> = records
+                .iter()
+                .filter_map(|r| r.get(i).map(str::trim))
+                .filter(|v| !v.is_empty())
+                .collect();
+
+            CsvColumn::infer(name, &values)
+        })
+        .collect();
+
+    let definitions: Vec<String> = columns
+        .iter()
+        .map(|c| format!("{} {}", quote_ident(&c.name), c.col_type.sql_type()))
+        .collect();
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let table = quote_ident(&table_name(file));
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        &format!("CREATE TABLE {table} ({})", definitions.join(", ")),
+        [],
+    )?;
+
+    {
+        let mut stmt = tx.prepare(&format!("INSERT INTO {table} VALUES ({placeholders})"))?;
+
+        for record in &records {
+            let values = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| c.value(record.get(i).unwrap_or_default()));
+
+            stmt.execute(params_from_iter(values))?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use axum::http::StatusCode;
+
+    use crate::base::{
+        config::{data_dir, ConnectionConfig},
+        data::table::DataQueryFilter,
+        foundation::Basable,
+        imp::graphs::{
+            chrono::{ChronoAnalysisBasis, ChronoAnalysisOpts, ChronoAnalysisRange},
+            AnalysisGraph,
+        },
+        AppError,
+    };
+
+    use super::{CsvColumn, CsvColumnType};
+
+    #[test]
+    fn test_infer_column_type() {
+        let col = |values: &[&str]| CsvColumn::infer("col".to_string(), values).col_type;
+
+        assert_eq!(col(&["1", "-20"]), CsvColumnType::Integer);
+        assert_eq!(col(&["1", "2.5"]), CsvColumnType::Real);
+        assert_eq!(col(&["NaN"]), CsvColumnType::Text);
+        assert_eq!(col(&["17/09/2013", "25/12/2020"]), CsvColumnType::Date);
+        assert_eq!(col(&["2024-07-01 14:30:45"]), CsvColumnType::DateTime);
+        assert_eq!(col(&["14:30"]), CsvColumnType::Time);
+        assert_eq!(col(&["2024-07-01", "soon"]), CsvColumnType::Text);
+    }
+
+    #[test]
+    fn test_csv_connection() -> Result<(), AppError> {
+        let dir = data_dir().join(format!("basable_csv_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("sales.csv"),
+            "id,product,amount,sold_on\n1,Pen,2.5,01/07/2024\n2,\"Ink, black\",4,02/07/2024\n3,Pen,2.5,02/07/2024\n",
+        )
+        .unwrap();
+
+        let config = ConnectionConfig {
+            source_type: "file".to_string(),
+            source: "csv".to_string(),
+            path: Some(dir.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let db = Basable::create_connection(&config, "test_user".to_string());
+        fs::remove_dir_all(&dir).unwrap();
+        let db = db?;
+
+        // Files are only read from the data directory.
+        let outside = ConnectionConfig {
+            path: Some(env::temp_dir().to_string_lossy().to_string()),
+            ..config
+        };
+        let outside = Basable::create_connection(&outside, "test_user".to_string());
+        assert!(matches!(outside, Err(AppError(StatusCode::NOT_FOUND, _))));
+
+        let table = db.get_table("sales").unwrap();
+        let cols = table.query_columns()?;
+        let types: Vec<&str> = cols.iter().map(|c| c.col_type.as_str()).collect();
+        assert_eq!(types, ["INTEGER", "TEXT", "REAL", "DATE"]);
+
+        let data = table.query_data(Default::default())?;
+        assert_eq!((data.total, data.rows.len()), (3, 3));
+
+        let filter = [(
+            "filter".to_string(),
+            "basable_filter:regex(product,^Ink)".to_string(),
+        )];
+        let data = table.query_data(DataQueryFilter::from_query_params(&filter)?)?;
+        assert_eq!(data.total, 1);
+
+        let graph = db.chrono_graph(ChronoAnalysisOpts {
+            table: "sales".to_string(),
+            chrono_col: "sold_on".to_string(),
+            basis: ChronoAnalysisBasis::Daily,
+            range: ChronoAnalysisRange("2024-07-01".to_string(), "2024-07-31".to_string()),
+            gap_fill: None,
+            time_zone: None,
+            aggregate: Default::default(),
+            series: None,
+        })?;
+        assert!(matches!(graph, AnalysisGraph::Single(results) if results.len() == 2));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    base::{
+        column::ColumnList,
+        imp::{db::QuerySqlParser, table::Table, ConnectorType},
+        BasableError,
+    },
+    imp::database::sqlite::table::SqliteTable,
+};
+
+/// A CSV file of a CSV data source. CSV tables are read-only, since changes can't be written
+/// back to the file.
+pub(crate) struct CsvTable {
+    inner: SqliteTable,
+}
+
+impl Table for CsvTable {
+    fn new(name: String, conn: ConnectorType) -> Self
+    where
+        Self: Sized,
+    {
+        CsvTable {
+            inner: SqliteTable::new(name, conn),
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn query_columns(&self) -> Result<ColumnList, BasableError> {
+        self.inner.query_columns()
+    }
+
+    fn connector(&self) -> &ConnectorType {
+        self.inner.connector()
+    }
+}
+
+impl QuerySqlParser for CsvTable {}
@@ -1 +1,2 @@
-pub(crate) mod database;
\ No newline at end of file
+pub(crate) mod database;
+pub(crate) mod file;
@@ -2,12 +2,15 @@
 pub(crate) mod common {
     use dotenv::dotenv;
     use std::{
-        env,
-        sync::{Arc, Mutex},
+        env, fs,
+        sync::{Arc, Mutex, Once},
     };
 
-    use crate::base::{
-        config::ConnectionConfig, foundation::Basable, imp::SharedDB, AppError, AppState
+    use crate::{
+        base::{
+            config::ConnectionConfig, foundation::Basable, imp::SharedDB, AppError, AppState
+        },
+        imp::database::postgres::blocking,
     };
 
     /// Get `TEST_USER_ID` from env
@@ -22,7 +25,48 @@ pub(crate) mod common {
         env::var("TEST_DB_TABLE_NAME").unwrap()
     }
 
+    /// Get `TEST_DB_SOURCE` from env, the data source tests run against.
+    pub fn get_test_db_source() -> String {
+        dotenv().ok();
+        env::var("TEST_DB_SOURCE").unwrap()
+    }
+
+    static SEED_SQLITE_DB: Once = Once::new();
+
+    /// Creates a fresh SQLite test database at `path` from `fixtures/basable.sql`. The
+    /// database is only created once per test run.
+    fn seed_sqlite_db(path: &str) {
+        SEED_SQLITE_DB.call_once(|| {
+            let _ = fs::remove_file(path);
+
+            let conn = rusqlite::Connection::open(path).unwrap();
+            conn.execute_batch(include_str!("../fixtures/basable.sql"))
+                .unwrap();
+        });
+    }
+
+    static SEED_POSTGRES_DB: Once = Once::new();
+
+    /// Replaces the tables of the Postgres test database of `config` with
+    /// `fixtures/basable.postgres.sql`. The tables are only replaced once per test run.
+    fn seed_postgres_db(config: &ConnectionConfig) {
+        SEED_POSTGRES_DB.call_once(|| {
+            let url = config.build_url();
+
+            blocking(|| {
+                let mut client = postgres::Client::connect(&url, postgres::NoTls).unwrap();
+                client
+                    .batch_execute(include_str!("../fixtures/basable.postgres.sql"))
+                    .unwrap();
+            });
+        });
+    }
+
     /// Creates a test `Config`.
+    ///
+    /// If `TEST_DB_SOURCE` is `sqlite`, the SQLite test database at `TEST_DB_PATH` is created
+    /// from sample data so that tests can run without a database server. If it's `postgres`,
+    /// the tables of the database `TEST_DB_NAME` are replaced with the same sample data.
     pub fn create_test_config() -> ConnectionConfig {
         dotenv().ok();
 
@@ -33,16 +77,28 @@ pub(crate) mod common {
         let db_port = env::var("TEST_DB_PORT").unwrap();
         let source = env::var("TEST_DB_SOURCE").unwrap();
         let source_type = env::var("TEST_DB_SOURCE_TYPE").unwrap();
+        let path = env::var("TEST_DB_PATH").ok();
+
+        if source == "sqlite" {
+            seed_sqlite_db(path.as_ref().unwrap());
+        }
 
-        ConnectionConfig {
+        let config = ConnectionConfig {
             db_name: Some(db_name),
             username: Some(db_username),
             password: Some(db_password),
             host: Some(db_host),
             port: Some(db_port.parse().unwrap()),
+            path,
             source,
             source_type,
+        };
+
+        if config.source == "postgres" {
+            seed_postgres_db(&config);
         }
+
+        config
     }
 
     pub fn create_test_db() -> Result<SharedDB, AppError> {
@@ -81,6 +137,7 @@ pub(crate) mod common {
             instance: Arc::new(Mutex::new(instance)),
             ..Default::default()
         };
+        state.setup_local_db()?;
 
         Ok(state)
     }
@@ -89,7 +146,10 @@ pub(crate) mod common {
 #[cfg(test)]
 pub(crate) mod extractors {
     use crate::{
-        base::{user::User, AppError},
+        base::{
+            user::{ApiKeyAccess, ApiKeyScope, User},
+            AppError,
+        },
         http::middlewares::{AuthExtractor, DbExtractor, TableExtractor},
     };
 
@@ -100,9 +160,21 @@ pub(crate) mod extractors {
         AuthExtractor(User {
             id,
             is_guest: false,
+            ..Default::default()
         })
     }
 
+    /// The test user, authenticated with a `read` API key for `connections`.
+    pub fn api_key_extractor(connections: &[&str]) -> AuthExtractor {
+        let AuthExtractor(mut user) = auth_extractor();
+        user.api_key = Some(ApiKeyScope {
+            access: ApiKeyAccess::Read,
+            connections: connections.iter().map(|c| c.to_string()).collect(),
+        });
+
+        AuthExtractor(user)
+    }
+
     pub fn db_extractor() -> Result<DbExtractor, AppError> {
         let db = create_test_db()?;
         Ok(DbExtractor(db))
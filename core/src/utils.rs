@@ -4,12 +4,186 @@ pub(crate) fn get_env(key: &str) -> String {
     env::var(key).unwrap()
 }
 
+/// Encryption of secrets, such as passwords of saved connections, before they are written to
+/// the local store. The key is derived from `BASABLE_ENCRYPTION_KEY`, so secrets can't be
+/// read back once it is changed.
+///
+/// Passwords of user accounts are not encrypted but hashed, see [`crypto::hash_password`].
+pub(crate) mod crypto {
+    use aes_gcm::{
+        aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+        Aes256Gcm, Nonce,
+    };
+    use argon2::{
+        password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+        Argon2,
+    };
+    use base64::{
+        engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+        Engine,
+    };
+    use sha2::{Digest, Sha256};
+
+    use crate::base::BasableError;
+
+    use super::get_env;
+
+    /// Length of the nonce, which is stored in front of the encrypted secret.
+    const NONCE_LEN: usize = 12;
+
+    /// Minimum length of `BASABLE_ENCRYPTION_KEY`.
+    const MIN_KEY_LEN: usize = 32;
+
+    /// Keys that were published with Basable, such as the former key of `.env.example`.
+    /// Secrets encrypted with them can be read by anyone.
+    const PUBLISHED_KEYS: [&str; 1] = ["gLmapr2rfCoPHwdFAEwshujTCDM4tMIg35jIsYRO"];
+
+    /// Checks that `BASABLE_ENCRYPTION_KEY` is a real key: it's at least [`MIN_KEY_LEN`]
+    /// characters long and it was not published. The server doesn't start otherwise, so that
+    /// secrets are never encrypted with a key that can be guessed.
+    pub fn check_encryption_key() -> Result<(), String> {
+        check_key(std::env::var("BASABLE_ENCRYPTION_KEY").ok().as_deref())
+    }
+
+    fn check_key(key: Option<&str>) -> Result<(), String> {
+        match key.map(str::trim) {
+            None | Some("") => Err("BASABLE_ENCRYPTION_KEY is not set.".to_string()),
+            Some(key) if key.len() < MIN_KEY_LEN => Err(format!(
+                "BASABLE_ENCRYPTION_KEY must be at least {MIN_KEY_LEN} characters long."
+            )),
+            Some(key) if PUBLISHED_KEYS.contains(&key) => {
+                Err("BASABLE_ENCRYPTION_KEY is a published key, generate a new one.".to_string())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn cipher() -> Aes256Gcm {
+        let key = Sha256::digest(get_env("BASABLE_ENCRYPTION_KEY"));
+        Aes256Gcm::new(&key)
+    }
+
+    /// Encrypts `secret` with a random nonce. The result is base64 encoded.
+    pub fn encrypt(secret: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let encrypted = cipher()
+            .encrypt(&nonce, secret.as_bytes())
+            .expect("Encryption of a secret failed");
+
+        let mut data = nonce.to_vec();
+        data.extend(encrypted);
+        STANDARD.encode(data)
+    }
+
+    /// Decrypts a secret returned by [`encrypt`].
+    pub fn decrypt(encrypted: &str) -> Result<String, BasableError> {
+        let err = || BasableError::Driver("A stored secret could not be decrypted.".to_string());
+
+        let data = STANDARD.decode(encrypted).map_err(|_| err())?;
+        if data.len() < NONCE_LEN {
+            return Err(err());
+        }
+
+        let (nonce, data) = data.split_at(NONCE_LEN);
+        let secret = cipher()
+            .decrypt(Nonce::from_slice(nonce), data)
+            .map_err(|_| err())?;
+
+        String::from_utf8(secret).map_err(|_| err())
+    }
+
+    /// A random token of 256 bits, such as a refresh token. It is URL-safe base64 encoded.
+    pub fn random_token() -> String {
+        let mut token = [0u8; 32];
+        OsRng.fill_bytes(&mut token);
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// Hashes a token returned by [`random_token`] with SHA-256, so that it can be looked up
+    /// without being stored. Random tokens don't need a slow hash like passwords.
+    pub fn hash_token(token: &str) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(token))
+    }
+
+    /// Hashes `password` with Argon2 and a random salt. The result is a PHC string, which
+    /// keeps the salt and parameters along with the hash.
+    pub fn hash_password(password: &str) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("Hashing of a password failed")
+            .to_string()
+    }
+
+    /// Hash of a random password with the parameters of [`hash_password`]. Passwords are
+    /// verified against it when there's no hash to verify them with, so that it takes as long
+    /// as verifying a real hash.
+    pub const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$MnjhvQGxeJnbu+8Q5rFlRg$wCkFD/D6oxzSTjgyD29v5ywvR9Do4mX4BbIL3C9Bdcg";
+
+    /// Checks `password` against a hash returned by [`hash_password`].
+    pub fn verify_password(password: &str, hash: &str) -> bool {
+        PasswordHash::new(hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use dotenv::dotenv;
+
+        use super::{
+            check_key, decrypt, encrypt, hash_password, random_token, verify_password,
+            DUMMY_PASSWORD_HASH, PUBLISHED_KEYS,
+        };
+
+        #[test]
+        fn test_encrypt() {
+            dotenv().ok();
+
+            let encrypted = encrypt("p@ssword");
+            assert!(!encrypted.contains("p@ssword"));
+            // Each encryption uses a new nonce.
+            assert_ne!(encrypted, encrypt("p@ssword"));
+            assert_eq!(decrypt(&encrypted).unwrap(), "p@ssword");
+
+            assert!(decrypt("bm90IGVuY3J5cHRlZA==").is_err());
+        }
+
+        #[test]
+        fn test_check_key() {
+            assert!(check_key(Some(&random_token())).is_ok());
+
+            assert!(check_key(None).is_err());
+            assert!(check_key(Some("  ")).is_err());
+            assert!(check_key(Some("s3cret")).is_err());
+            assert!(check_key(Some(PUBLISHED_KEYS[0])).is_err());
+        }
+
+        #[test]
+        fn test_hash_password() {
+            let hash = hash_password("p@ssword");
+            assert!(hash.starts_with("$argon2"));
+            assert!(verify_password("p@ssword", &hash));
+            assert!(!verify_password("password", &hash));
+            assert!(!verify_password("p@ssword", "not a hash"));
+
+            // The dummy hash costs as much to verify as the hashes of passwords.
+            let params = |hash: &str| hash.rsplitn(3, '$').last().unwrap().to_string();
+            assert_eq!(params(DUMMY_PASSWORD_HASH), params(&hash));
+            assert!(!verify_password("", DUMMY_PASSWORD_HASH));
+        }
+    }
+}
+
 pub(crate) mod datetime_parser {
-    use chrono::NaiveDateTime;
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
 
     /// An implementation of datetime pattern we can use in SQL queries. It's a turple struct with
     /// first item being the actual pattern and second item being a sample of the pattern.
-    pub(crate) struct DatePattern<'a>(pub &'a str, pub &'a str);
+    pub(crate) struct DatePattern<'a>(pub &'a str, #[allow(dead_code)] pub &'a str);
     
     impl<'a> DatePattern<'a> {
         /// List of all supported [`DatePattern`]. We update this list if we need to support a new datetime pattern.
@@ -68,9 +242,50 @@ pub(crate) mod datetime_parser {
         }
 
         /// An example of [`DatePattern`].
+        #[cfg(test)]
         pub fn example(&self) -> &'a str {
             self.1
         }
+
+        /// Parse `value` with the pattern. Values with a UTC offset are converted to UTC.
+        pub fn parse(&self, value: &str) -> Option<DateValue> {
+            let value = value.trim();
+
+            if self.0.contains("%z") || self.0.contains("%:z") {
+                let dt = DateTime::parse_from_str(value, self.0).ok()?;
+                return Some(DateValue::DateTime(dt.naive_utc()));
+            }
+
+            if let Ok(dt) = NaiveDateTime::parse_from_str(value, self.0) {
+                return Some(DateValue::DateTime(dt));
+            }
+
+            if let Ok(d) = NaiveDate::parse_from_str(value, self.0) {
+                return Some(DateValue::Date(d));
+            }
+
+            NaiveTime::parse_from_str(value, self.0)
+                .ok()
+                .map(DateValue::Time)
+        }
+    }
+
+    /// A value parsed with a [`DatePattern`].
+    pub(crate) enum DateValue {
+        Date(NaiveDate),
+        Time(NaiveTime),
+        DateTime(NaiveDateTime),
+    }
+
+    impl DateValue {
+        /// ISO 8601 representation of the value, which SQL date functions understand.
+        pub fn to_iso(&self) -> String {
+            match self {
+                DateValue::Date(d) => d.format("%Y-%m-%d").to_string(),
+                DateValue::Time(t) => t.format("%H:%M:%S%.f").to_string(),
+                DateValue::DateTime(dt) => dt.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+            }
+        }
     }
 
     impl<'a> TryFrom<String> for DatePattern<'a> {
@@ -78,7 +293,7 @@ pub(crate) mod datetime_parser {
     
         fn try_from(value: String) -> Result<Self, Self::Error> {
             for pattern in DatePattern::supported() {
-                if let Ok(_) = NaiveDateTime::parse_from_str(&value, pattern.0) {
+                if pattern.parse(&value).is_some() {
                     return Ok(pattern)
                 }
             }
@@ -89,5 +304,17 @@ pub(crate) mod datetime_parser {
     pub enum ParseError {
         NotAvailable
     }
-}
 
+    #[cfg(test)]
+    mod tests {
+        use super::DatePattern;
+
+        #[test]
+        fn test_date_pattern_example() {
+            for pattern in DatePattern::supported() {
+                let parsed = pattern.parse(pattern.example());
+                assert!(parsed.is_some(), "{} can't parse", pattern.value());
+            }
+        }
+    }
+}